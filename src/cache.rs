@@ -1,10 +1,13 @@
 use std::{
+    collections::HashMap,
     fs::{create_dir_all, read_to_string, File},
     io::{BufWriter, Write},
 };
 
 use anyhow::Result;
 use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
+use regex::Regex;
 use select::{
     document::Document,
     predicate::{Class, Name, Predicate},
@@ -15,9 +18,13 @@ use thiserror::Error;
 use url::Url;
 
 use crate::{
-    constants::{PYTHON_SOURCE_INDEX_URL, PYTHON_WINDOWS_INDEX_URL},
-    download::{download_to_string, HyperDownloader},
-    utils::directory::{PycorsHomeProviderTrait, PycorsPathsProvider},
+    checksum::Digest,
+    download::download_to_string,
+    mirrors::Mirrors,
+    utils::{
+        self,
+        directory::{PycorsHomeProviderTrait, PycorsPathsProvider},
+    },
 };
 
 #[cfg(test)]
@@ -27,19 +34,34 @@ mod tests;
 pub enum CacheError {
     #[error("No compatible version found")]
     NoCompatibleVersionFound,
+    #[error("No compatible pre-built version found for {0}")]
+    NoCompatiblePreBuiltFound(String),
+    #[error("No compatible PyPy release found for {0}")]
+    PyPyNotFound(String),
 }
 
 #[cfg_attr(test, mockall::automock)]
 pub trait ToolchainsCacheFetch {
     fn get_source(&self) -> Result<String>;
     fn get_win_prebuilt(&self) -> Result<String>;
+    fn get_release_page(&self, version: &Version) -> Result<String>;
+    fn get_standalone_releases(&self) -> Result<String>;
+    fn get_pypy_versions(&self) -> Result<String>;
 }
 
-pub struct ToolchainsCacheFetchOnline;
+pub struct ToolchainsCacheFetchOnline {
+    mirrors: Mirrors,
+}
+
+impl ToolchainsCacheFetchOnline {
+    pub fn new(mirrors: Mirrors) -> ToolchainsCacheFetchOnline {
+        ToolchainsCacheFetchOnline { mirrors }
+    }
+}
 
 impl ToolchainsCacheFetch for ToolchainsCacheFetchOnline {
     fn get_source(&self) -> Result<String> {
-        let mut downloader = HyperDownloader::new(PYTHON_SOURCE_INDEX_URL)?;
+        let mut downloader = self.mirrors.downloader(&self.mirrors.source_index)?;
         // HTML file is too small to bother with a progress bar
         let with_progress_bar = false;
         let rt = tokio::runtime::Runtime::new()?;
@@ -49,7 +71,7 @@ impl ToolchainsCacheFetch for ToolchainsCacheFetchOnline {
         Ok(index_html)
     }
     fn get_win_prebuilt(&self) -> Result<String> {
-        let mut downloader = HyperDownloader::new(PYTHON_WINDOWS_INDEX_URL)?;
+        let mut downloader = self.mirrors.downloader(&self.mirrors.windows_index)?;
         // HTML file is too small to bother with a progress bar
         let with_progress_bar = false;
         let rt = tokio::runtime::Runtime::new()?;
@@ -58,14 +80,111 @@ impl ToolchainsCacheFetch for ToolchainsCacheFetchOnline {
 
         Ok(index_html)
     }
+    fn get_release_page(&self, version: &Version) -> Result<String> {
+        let url = release_page_url(&self.mirrors.release_page, version)?;
+        let mut downloader = self.mirrors.downloader(&url)?;
+        // HTML file is too small to bother with a progress bar
+        let with_progress_bar = false;
+        let rt = tokio::runtime::Runtime::new()?;
+        let release_html: String =
+            rt.block_on(download_to_string(&mut downloader, with_progress_bar))?;
+
+        Ok(release_html)
+    }
+    fn get_standalone_releases(&self) -> Result<String> {
+        let mut downloader = self.mirrors.downloader(&self.mirrors.standalone_releases)?;
+        // JSON file is too small to bother with a progress bar
+        let with_progress_bar = false;
+        let rt = tokio::runtime::Runtime::new()?;
+        let releases_json: String =
+            rt.block_on(download_to_string(&mut downloader, with_progress_bar))?;
+
+        Ok(releases_json)
+    }
+    fn get_pypy_versions(&self) -> Result<String> {
+        let mut downloader = self.mirrors.downloader(&self.mirrors.pypy_versions)?;
+        // JSON file is too small to bother with a progress bar
+        let with_progress_bar = false;
+        let rt = tokio::runtime::Runtime::new()?;
+        let versions_json: String =
+            rt.block_on(download_to_string(&mut downloader, with_progress_bar))?;
+
+        Ok(versions_json)
+    }
+}
+
+/// URL of the python.org release page of a version (listing the files checksums)
+///
+/// For example, version `3.9.0-a2` is found at `.../release/python-390a2/`.
+fn release_page_url(release_pages: &Url, version: &Version) -> Result<Url> {
+    Ok(release_pages.join(&format!(
+        "python-{}{}{}{}/",
+        version.major,
+        version.minor,
+        version.patch,
+        version.pre.as_str()
+    ))?)
 }
 
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AvailableToolchain {
     pub version: Version,
     pub base_url: Url,
     pub source_tar_gz: String,
     pub win_pre_built: Option<String>,
+    #[serde(default)]
+    pub source_digest: Option<Digest>,
+    #[serde(default)]
+    pub win_pre_built_digest: Option<Digest>,
+    #[serde(default)]
+    pub standalone_pre_built: Option<StandalonePreBuilt>,
+}
+
+/// A relocatable pre-built archive published by the python-build-standalone project.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StandalonePreBuilt {
+    /// Name of the archive, as listed in its checksum file
+    pub name: String,
+    pub url: Url,
+    /// URL of a file listing the archive's SHA-256 checksum, if published
+    pub digest_url: Option<Url>,
+}
+
+impl StandalonePreBuilt {
+    /// Filename of the archive once downloaded (the last, still percent-encoded, segment
+    /// of its URL).
+    pub fn filename(&self) -> String {
+        self.url
+            .path_segments()
+            .and_then(|mut segments| segments.next_back())
+            .unwrap_or_default()
+            .to_string()
+    }
+}
+
+/// A pre-built PyPy release.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AvailablePyPy {
+    /// Version of Python implemented
+    pub version: Version,
+    pub pypy_version: Version,
+    /// Releases not marked as stable are considered pre-releases
+    pub stable: bool,
+    /// Name of the archive, as listed in the checksums
+    pub name: String,
+    pub url: Url,
+}
+
+impl AvailablePyPy {
+    /// Filename of the archive once downloaded (the last, still percent-encoded, segment
+    /// of its URL).
+    pub fn filename(&self) -> String {
+        self.url
+            .path_segments()
+            .and_then(|mut segments| segments.next_back())
+            .unwrap_or_default()
+            .to_string()
+    }
 }
 
 trait AvailableToolchainTrait {
@@ -124,6 +243,17 @@ impl AvailableToolchain {
         new_url
     }
 
+    /// URL of the detached OpenPGP signature of the source archive
+    #[cfg_attr(windows, allow(dead_code))]
+    pub fn source_signature_url(&self) -> Url {
+        let mut new_url = self.base_url.clone();
+        new_url
+            .path_segments_mut()
+            .unwrap()
+            .extend(&[&format!("{}.asc", self.source_tar_gz)]);
+        new_url
+    }
+
     #[cfg_attr(not(windows), allow(dead_code))]
     pub fn windows_pre_built_url(&self) -> Option<Url> {
         self.win_pre_built.as_ref().map(|win_pre_built| {
@@ -141,6 +271,9 @@ impl AvailableToolchain {
 pub struct AvailableToolchainsCache {
     last_updated: DateTime<Utc>,
     available: Vec<AvailableToolchain>,
+    /// PyPy releases pre-built for this platform, latest first (fetched on demand)
+    #[serde(default)]
+    pypy: Vec<AvailablePyPy>,
 }
 
 impl AvailableToolchainsCache {
@@ -209,6 +342,7 @@ impl AvailableToolchainsCache {
         let mut cache = AvailableToolchainsCache {
             last_updated: Utc::now(),
             available: Vec::new(),
+            pypy: Vec::new(),
         };
         cache.update(paths_provider, downloader)?;
         Ok(cache)
@@ -234,7 +368,16 @@ impl AvailableToolchainsCache {
             available_toolchains_source,
             available_toolchains_win_prebuilt,
         );
+        // Fetched again when needed
+        self.pypy.clear();
+
+        self.save(paths_provider)
+    }
 
+    fn save<P>(&self, paths_provider: &PycorsPathsProvider<P>) -> Result<()>
+    where
+        P: PycorsHomeProviderTrait,
+    {
         let cache_json = serde_json::to_string(&self)?;
         let cache_file = paths_provider.available_toolchains_cache_file();
         let mut output = BufWriter::new(File::create(&cache_file)?);
@@ -243,13 +386,160 @@ impl AvailableToolchainsCache {
         Ok(())
     }
 
-    pub fn query(&self, version_req: &VersionReq) -> Result<&AvailableToolchain> {
-        // Find all compatible versions from the cached list
-        let compatible_toolchains: Vec<&AvailableToolchain> = self
+    /// Fill in the published checksums of the archives of a version.
+    ///
+    /// The checksums are parsed from the version's python.org release page and
+    /// saved in the cache so the page is only fetched once.
+    pub fn fetch_digests<P, D>(
+        &mut self,
+        version: &Version,
+        paths_provider: &PycorsPathsProvider<P>,
+        downloader: &D,
+    ) -> Result<()>
+    where
+        P: PycorsHomeProviderTrait,
+        D: ToolchainsCacheFetch,
+    {
+        let toolchain = self
             .available
+            .iter_mut()
+            .find(|available| available.version == *version)
+            .ok_or(CacheError::NoCompatibleVersionFound)?;
+
+        let source_missing = toolchain.source_digest.is_none();
+        let win_pre_built_missing =
+            toolchain.win_pre_built.is_some() && toolchain.win_pre_built_digest.is_none();
+        if !source_missing && !win_pre_built_missing {
+            log::debug!("Checksums of Python {} already cached", version);
+            return Ok(());
+        }
+
+        log::info!("Fetching checksums of Python {}...", version);
+        let release_html = downloader.get_release_page(version)?;
+        let digests = parse_release_page_digests(&release_html);
+
+        toolchain.source_digest = digests.get(&toolchain.source_tar_gz).cloned();
+        toolchain.win_pre_built_digest = toolchain
+            .win_pre_built
+            .as_ref()
+            .and_then(|win_pre_built| digests.get(win_pre_built).cloned());
+
+        self.save(paths_provider)
+    }
+
+    /// Fill in the python-build-standalone archives built for `target_triple`.
+    ///
+    /// Versions not listed in the python.org index are ignored.
+    pub fn fetch_standalone_pre_built<P, D>(
+        &mut self,
+        target_triple: &str,
+        paths_provider: &PycorsPathsProvider<P>,
+        downloader: &D,
+    ) -> Result<()>
+    where
+        P: PycorsHomeProviderTrait,
+        D: ToolchainsCacheFetch,
+    {
+        log::info!("Fetching python-build-standalone releases...");
+        let releases_json = downloader.get_standalone_releases()?;
+        let mut pre_builts = parse_standalone_releases(&releases_json, target_triple)?;
+
+        for toolchain in &mut self.available {
+            if let Some(pre_built) = pre_builts.remove(&toolchain.version) {
+                toolchain.standalone_pre_built = Some(pre_built);
+            }
+        }
+
+        self.save(paths_provider)
+    }
+
+    /// Fill in the PyPy releases pre-built for `platform` (as named by PyPy, like
+    /// `("linux", "x64")`).
+    pub fn fetch_pypy<P, D>(
+        &mut self,
+        platform: (&str, &str),
+        paths_provider: &PycorsPathsProvider<P>,
+        downloader: &D,
+    ) -> Result<()>
+    where
+        P: PycorsHomeProviderTrait,
+        D: ToolchainsCacheFetch,
+    {
+        log::info!("Fetching PyPy releases...");
+        let versions_json = downloader.get_pypy_versions()?;
+        self.pypy = parse_pypy_versions(&versions_json, platform)?;
+
+        self.save(paths_provider)
+    }
+
+    /// All PyPy releases implementing a Python version compatible with `version_req`,
+    /// latest first.
+    pub fn query_all_pypy(
+        &self,
+        version_req: &VersionReq,
+        include_pre_releases: bool,
+    ) -> Vec<&AvailablePyPy> {
+        self.pypy
             .iter()
-            .filter(|available| version_req.matches(&available.version))
-            .collect();
+            .filter(|available| available.stable || include_pre_releases)
+            .filter(|available| {
+                utils::version_matches(version_req, &available.version, include_pre_releases)
+            })
+            .collect()
+    }
+
+    /// Latest PyPy release implementing a Python version compatible with `version_req`.
+    pub fn query_pypy(
+        &self,
+        version_req: &VersionReq,
+        include_pre_releases: bool,
+    ) -> Result<&AvailablePyPy> {
+        self.query_all_pypy(version_req, include_pre_releases)
+            .first()
+            .copied()
+            .ok_or_else(|| CacheError::PyPyNotFound(version_req.to_string()).into())
+    }
+
+    /// Latest toolchain compatible with `version_req` with a python-build-standalone archive.
+    pub fn query_pre_built(
+        &self,
+        version_req: &VersionReq,
+        include_pre_releases: bool,
+    ) -> Result<&AvailableToolchain> {
+        self.query_all(version_req, include_pre_releases)
+            .into_iter()
+            .find(|available| available.standalone_pre_built.is_some())
+            .ok_or_else(|| CacheError::NoCompatiblePreBuiltFound(version_req.to_string()).into())
+    }
+
+    /// All toolchains compatible with `version_req`, latest first.
+    ///
+    /// Pre-releases (alpha, beta and release candidates) are only returned when
+    /// `include_pre_releases` is set (or when explicitly requested by `version_req`).
+    pub fn query_all(
+        &self,
+        version_req: &VersionReq,
+        include_pre_releases: bool,
+    ) -> Vec<&AvailableToolchain> {
+        self.available
+            .iter()
+            .filter(|available| {
+                utils::version_matches(version_req, &available.version, include_pre_releases)
+            })
+            .collect()
+    }
+
+    /// Latest toolchain compatible with `version_req`.
+    ///
+    /// Pre-releases are only considered when `include_pre_releases` is set (or when
+    /// `version_req` explicitly requests one, for example `=3.9.0-a2`).
+    pub fn query(
+        &self,
+        version_req: &VersionReq,
+        include_pre_releases: bool,
+    ) -> Result<&AvailableToolchain> {
+        // Find all compatible versions from the cached list
+        let compatible_toolchains = self.query_all(version_req, include_pre_releases);
 
         log::debug!("Compatible versions found: {:?}", compatible_toolchains);
 
@@ -282,6 +572,9 @@ fn merge_available_toolchains(
                     base_url: source.base_url.clone(),
                     source_tar_gz: source.source_tar_gz.clone(),
                     win_pre_built: None,
+                    source_digest: None,
+                    win_pre_built_digest: None,
+                    standalone_pre_built: None,
                 });
                 next_source = source_iter.next();
             }
@@ -297,6 +590,9 @@ fn merge_available_toolchains(
                         base_url: source.base_url.clone(),
                         source_tar_gz: source.source_tar_gz.clone(),
                         win_pre_built: None,
+                        source_digest: None,
+                        win_pre_built_digest: None,
+                        standalone_pre_built: None,
                     });
                     next_source = source_iter.next();
                 }
@@ -311,6 +607,9 @@ fn merge_available_toolchains(
                         base_url: pre_built.base_url.clone(),
                         source_tar_gz: source.source_tar_gz.clone(),
                         win_pre_built: Some(pre_built.win_pre_built.clone()),
+                        source_digest: None,
+                        win_pre_built_digest: None,
+                        standalone_pre_built: None,
                     });
                     next_source = source_iter.next();
                     next_pre_built = pre_built_iter.next();
@@ -389,3 +688,246 @@ fn parse_win_pre_built_index_html(
 ) -> Result<Vec<AvailableToolchainWindowsPreBuilt>> {
     parse_index_html::<AvailableToolchainWindowsPreBuilt>(index_html, "-embed-amd64.zip")
 }
+
+#[derive(Debug, Deserialize)]
+struct GithubRelease {
+    assets: Vec<GithubAsset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubAsset {
+    name: String,
+    browser_download_url: Url,
+}
+
+/// Archive flavors usable as an installation, most preferred first.
+///
+/// `install_only` archives contain the installation directly under `python/`, the
+/// `full` ones under `python/install/` (along with build artifacts).
+const STANDALONE_FLAVORS: &[&str] = &[
+    "install_only.tar.gz",
+    "install_only_stripped.tar.gz",
+    "pgo+lto-full.tar.zst",
+    "pgo-full.tar.zst",
+    "lto-full.tar.zst",
+    "noopt-full.tar.zst",
+];
+
+/// Parse the GitHub releases of python-build-standalone, returning the preferred
+/// archive built for `target_triple` of each version.
+///
+/// Archives are named like `cpython-3.11.7+20240107-x86_64-unknown-linux-gnu-install_only.tar.gz`.
+/// When a version is found in multiple releases, the most preferred flavor of the
+/// most recent build is used.
+fn parse_standalone_releases(
+    releases_json: &str,
+    target_triple: &str,
+) -> Result<HashMap<Version, StandalonePreBuilt>> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"^cpython-(\d+\.\d+\.\d+)((?:a|b|rc)\d+)?\+(\d+)-(.+)$")
+            .expect("valid regex");
+    }
+
+    let releases: Vec<GithubRelease> = serde_json::from_str(releases_json)?;
+
+    // Version -> (flavor rank, build date, archive)
+    let mut found: HashMap<Version, (usize, u64, StandalonePreBuilt)> = HashMap::new();
+    for release in &releases {
+        // Checksums are published either per archive or for the whole release
+        let sha256sums_url = release
+            .assets
+            .iter()
+            .find(|asset| asset.name == "SHA256SUMS")
+            .map(|asset| asset.browser_download_url.clone());
+
+        for asset in &release.assets {
+            let captures = match RE.captures(&asset.name) {
+                Some(captures) => captures,
+                None => continue,
+            };
+            let flavor = match captures[4]
+                .strip_prefix(target_triple)
+                .and_then(|rest| rest.strip_prefix('-'))
+            {
+                Some(flavor) => flavor,
+                None => continue,
+            };
+            let rank = match STANDALONE_FLAVORS.iter().position(|f| *f == flavor) {
+                Some(rank) => rank,
+                None => continue,
+            };
+            let version = match captures.get(2) {
+                Some(pre) => format!("{}-{}", &captures[1], pre.as_str()),
+                None => captures[1].to_string(),
+            };
+            let version = match Version::parse(&version) {
+                Ok(version) => version,
+                Err(_) => continue,
+            };
+            let build: u64 = captures[3].parse().unwrap_or_default();
+
+            let better = match found.get(&version) {
+                Some((found_rank, found_build, _)) => {
+                    build > *found_build || (build == *found_build && rank < *found_rank)
+                }
+                None => true,
+            };
+            if better {
+                let digest_url = release
+                    .assets
+                    .iter()
+                    .find(|a| a.name == format!("{}.sha256", asset.name))
+                    .map(|a| a.browser_download_url.clone())
+                    .or_else(|| sha256sums_url.clone());
+                found.insert(
+                    version,
+                    (
+                        rank,
+                        build,
+                        StandalonePreBuilt {
+                            name: asset.name.clone(),
+                            url: asset.browser_download_url.clone(),
+                            digest_url,
+                        },
+                    ),
+                );
+            }
+        }
+    }
+
+    Ok(found
+        .into_iter()
+        .map(|(version, (_, _, pre_built))| (version, pre_built))
+        .collect())
+}
+
+/// Parse the SHA-256 checksum of `filename` from a `sha256sum` output, or from a
+/// file containing only the checksum.
+pub fn parse_sha256_file(content: &str, filename: &str) -> Option<Digest> {
+    for line in content.lines() {
+        let mut words = line.split_whitespace();
+        match (words.next(), words.next()) {
+            (Some(hex), None) => return Some(Digest::sha256(hex)),
+            (Some(hex), Some(name)) if name.trim_start_matches('*') == filename => {
+                return Some(Digest::sha256(hex))
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Parse the SHA-256 checksum of `filename` from pypy.org's checksums page, or from a
+/// `sha256sum` output.
+pub fn parse_pypy_checksum(content: &str, filename: &str) -> Option<Digest> {
+    let document = Document::from(content);
+    let mut blocks: Vec<String> = document.find(Name("pre")).map(|pre| pre.text()).collect();
+    if blocks.is_empty() {
+        blocks.push(content.to_string());
+    }
+
+    blocks
+        .iter()
+        .flat_map(|block| block.lines())
+        .find_map(|line| {
+            let mut words = line.split_whitespace();
+            match (words.next(), words.next()) {
+                (Some(hex), Some(name)) if name.trim_start_matches('*') == filename => {
+                    Some(Digest::sha256(hex))
+                }
+                _ => None,
+            }
+        })
+}
+
+#[derive(Debug, Deserialize)]
+struct PyPyRelease {
+    pypy_version: String,
+    python_version: String,
+    stable: bool,
+    files: Vec<PyPyFile>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PyPyFile {
+    filename: String,
+    platform: String,
+    arch: String,
+    download_url: Url,
+}
+
+/// Parse PyPy's `versions.json`, returning the releases with an archive for
+/// `platform`, latest first.
+///
+/// Releases whose versions are not semantic versions (like `nightly`) are ignored.
+fn parse_pypy_versions(versions_json: &str, platform: (&str, &str)) -> Result<Vec<AvailablePyPy>> {
+    let releases: Vec<PyPyRelease> = serde_json::from_str(versions_json)?;
+
+    let mut found: Vec<AvailablePyPy> = releases
+        .into_iter()
+        .filter_map(|release| {
+            let version = Version::parse(&release.python_version).ok()?;
+            let pypy_version = Version::parse(&release.pypy_version).ok()?;
+            let file = release
+                .files
+                .into_iter()
+                .find(|file| (file.platform.as_str(), file.arch.as_str()) == platform)?;
+            Some(AvailablePyPy {
+                version,
+                pypy_version,
+                stable: release.stable,
+                name: file.filename,
+                url: file.download_url,
+            })
+        })
+        .collect();
+    found.sort_by(|a, b| (&b.version, &b.pypy_version).cmp(&(&a.version, &a.pypy_version)));
+
+    Ok(found)
+}
+
+/// Parse the "Files" table of a python.org release page, returning the
+/// published checksum of each file (indexed by filename).
+///
+/// A SHA-256 column is preferred when available, otherwise the MD5 one is used.
+fn parse_release_page_digests(release_html: &str) -> HashMap<String, Digest> {
+    let mut digests = HashMap::new();
+
+    let document = Document::from(release_html);
+    for table in document.find(Name("table")) {
+        let headers: Vec<String> = table
+            .find(Name("thead").descendant(Name("th")))
+            .map(|th| th.text().trim().to_lowercase())
+            .collect();
+        let sha256_column = headers
+            .iter()
+            .position(|h| h.contains("sha") && h.contains("256"));
+        let md5_column = headers.iter().position(|h| h.contains("md5"));
+        let (column, to_digest): (usize, fn(&str) -> Digest) = match (sha256_column, md5_column) {
+            (Some(column), _) => (column, |hex| Digest::sha256(hex)),
+            (None, Some(column)) => (column, |hex| Digest::md5(hex)),
+            (None, None) => continue,
+        };
+
+        for row in table.find(Name("tbody").descendant(Name("tr"))) {
+            let cells: Vec<_> = row.find(Name("td")).collect();
+            let filename = cells
+                .first()
+                .and_then(|td| td.find(Name("a")).next())
+                .and_then(|a| a.attr("href"))
+                .and_then(|href| Url::parse(href).ok())
+                .and_then(|url| {
+                    url.path_segments()
+                        .and_then(|mut segments| segments.next_back().map(String::from))
+                });
+            let hex = cells.get(column).map(|td| td.text().trim().to_string());
+            if let (Some(filename), Some(hex)) = (filename, hex) {
+                if !hex.is_empty() {
+                    digests.insert(filename, to_digest(&hex));
+                }
+            }
+        }
+    }
+
+    digests
+}
@@ -10,6 +10,11 @@ use mockall::predicate::*;
 
 const SOURCE_INDEX_HTML: &str = include_str!("../../tests/fixtures/html/source/index.html");
 const WIN_PREBUILT_INDEX_HTML: &str = include_str!("../../tests/fixtures/html/windows/index.html");
+const RELEASE_375_HTML: &str = include_str!("../../tests/fixtures/html/release/python-375.html");
+const STANDALONE_RELEASES_JSON: &str =
+    include_str!("../../tests/fixtures/json/standalone/releases.json");
+const PYPY_VERSIONS_JSON: &str = include_str!("../../tests/fixtures/json/pypy/versions.json");
+const PYPY_CHECKSUMS_HTML: &str = include_str!("../../tests/fixtures/html/pypy/checksums.html");
 
 macro_rules! atwfs {
     ($version:expr, $version_url:expr, $version_archive:expr) => {{
@@ -48,6 +53,9 @@ macro_rules! at {
             } else {
                 None
             },
+            source_digest: None,
+            win_pre_built_digest: None,
+            standalone_pre_built: None,
         }
     }};
 }
@@ -113,6 +121,7 @@ fn cache_up_to_date() {
     let dummy_cache = AvailableToolchainsCache {
         last_updated: Utc::now() - Duration::days(1),
         available: Vec::new(),
+        pypy: Vec::new(),
     };
     let cache_json = serde_json::to_string(&dummy_cache).unwrap();
     fs::create_dir_all(cache_file.parent().unwrap()).unwrap();
@@ -166,6 +175,7 @@ fn cache_corrupted() {
     let dummy_cache = AvailableToolchainsCache {
         last_updated: Utc::now() - Duration::days(1),
         available: Vec::new(),
+        pypy: Vec::new(),
     };
     let cache_json = serde_json::to_string(&dummy_cache).unwrap();
     fs::create_dir_all(cache_file.parent().unwrap()).unwrap();
@@ -223,6 +233,7 @@ fn cache_outdated() {
     let dummy_cache = AvailableToolchainsCache {
         last_updated: Utc::now() - Duration::days(11),
         available: Vec::new(),
+        pypy: Vec::new(),
     };
     let cache_json = serde_json::to_string(&dummy_cache).unwrap();
     fs::create_dir_all(cache_file.parent().unwrap()).unwrap();
@@ -620,7 +631,10 @@ fn merge_html_fixtures_available_toolchains() {
                 .parse()
                 .unwrap(),
             source_tar_gz: "Python-3.7.2.tgz".into(),
-            win_pre_built: Some("python-3.7.2.post1-embed-amd64.zip".into())
+            win_pre_built: Some("python-3.7.2.post1-embed-amd64.zip".into()),
+            source_digest: None,
+            win_pre_built_digest: None,
+            standalone_pre_built: None,
         },
         at!("3.7.2-rc1", "3.7.2", "3.7.2rc1", prebuilt = true),
         at!("3.7.1", "3.7.1", "3.7.1", prebuilt = true),
@@ -820,3 +834,361 @@ fn merge_html_fixtures_available_toolchains() {
 
     assert_eq!(merged_available_toolchains, expected);
 }
+
+fn fixture_cache() -> AvailableToolchainsCache {
+    let parsed_src: Vec<AvailableToolchainFromSource> =
+        parse_source_index_html(SOURCE_INDEX_HTML).unwrap();
+    let parsed_wpb: Vec<AvailableToolchainWindowsPreBuilt> =
+        parse_win_pre_built_index_html(WIN_PREBUILT_INDEX_HTML).unwrap();
+
+    AvailableToolchainsCache {
+        last_updated: Utc::now(),
+        available: merge_available_toolchains(parsed_src, parsed_wpb),
+        pypy: Vec::new(),
+    }
+}
+
+#[test]
+fn query_all_without_pre_releases() {
+    let cache = fixture_cache();
+    let versions: Vec<String> = cache
+        .query_all(&VersionReq::parse("~3.8").unwrap(), false)
+        .iter()
+        .map(|t| t.version.to_string())
+        .collect();
+    assert_eq!(versions, vec!["3.8.1", "3.8.0"]);
+}
+
+#[test]
+fn query_all_with_pre_releases() {
+    let cache = fixture_cache();
+    let versions: Vec<String> = cache
+        .query_all(&VersionReq::parse("~3.8.1").unwrap(), true)
+        .iter()
+        .map(|t| t.version.to_string())
+        .collect();
+    assert_eq!(versions, vec!["3.8.1", "3.8.1-rc1"]);
+}
+
+#[test]
+fn query_all_pre_releases_only_when_requested() {
+    let cache = fixture_cache();
+    let version_req = VersionReq::parse("~3.9").unwrap();
+    assert!(cache.query_all(&version_req, false).is_empty());
+    let versions: Vec<String> = cache
+        .query_all(&version_req, true)
+        .iter()
+        .map(|t| t.version.to_string())
+        .collect();
+    assert_eq!(versions, vec!["3.9.0-a2", "3.9.0-a1"]);
+}
+
+#[test]
+fn release_page_urls() {
+    let release_pages: Url = crate::constants::PYTHON_RELEASE_PAGE_URL.parse().unwrap();
+    for (version, expected) in &[
+        (
+            "3.7.5",
+            "https://www.python.org/downloads/release/python-375/",
+        ),
+        (
+            "3.9.0-a2",
+            "https://www.python.org/downloads/release/python-390a2/",
+        ),
+        (
+            "3.10.1-rc1",
+            "https://www.python.org/downloads/release/python-3101rc1/",
+        ),
+    ] {
+        assert_eq!(
+            release_page_url(&release_pages, &Version::parse(version).unwrap())
+                .unwrap()
+                .as_str(),
+            *expected
+        );
+    }
+}
+
+#[test]
+fn parse_release_page_html() {
+    let digests = parse_release_page_digests(RELEASE_375_HTML);
+
+    assert_eq!(digests.len(), 5);
+    assert_eq!(
+        digests["Python-3.7.5.tgz"],
+        Digest::md5("1cd071f78ff6d9c7524c95303a3057aa")
+    );
+    assert_eq!(
+        digests["python-3.7.5-embed-amd64.zip"],
+        Digest::md5("436b0f803d2a0b393590030b1cd59853")
+    );
+}
+
+#[test]
+fn parse_release_page_html_prefers_sha256() {
+    let release_html = r#"<table>
+        <thead><tr><th>Version</th><th>MD5 Sum</th><th>SHA-256</th></tr></thead>
+        <tbody><tr>
+            <td><a href="https://www.python.org/ftp/python/3.12.0/Python-3.12.0.tgz">Gzipped source tarball</a></td>
+            <td>F6F4616584B23254D165F4DB90C247D6</td>
+            <td>51412956D24A1EF7C97F1CB5F70E185C13E3DE1F50D131C0AAC6338080687AFB</td>
+        </tr></tbody>
+    </table>"#;
+    let digests = parse_release_page_digests(release_html);
+
+    assert_eq!(
+        digests["Python-3.12.0.tgz"],
+        Digest::sha256("51412956d24a1ef7c97f1cb5f70e185c13e3de1f50d131c0aac6338080687afb")
+    );
+}
+
+#[test]
+fn fetch_digests_cached() {
+    let home = create_test_temp_dir!();
+    let project_home = home.join(".hygeia");
+    fs::create_dir_all(project_home.join("cache")).unwrap();
+
+    let mut mock = MockPycorsHomeProviderTrait::new();
+    mock.expect_project_home().return_const(Some(project_home));
+    mock.expect_home().times(0).return_const(Some(home));
+    let paths_provider = PycorsPathsProvider::from(mock);
+
+    let mut mock = MockToolchainsCacheFetch::new();
+    mock.expect_get_release_page()
+        .with(eq(Version::new(3, 7, 5)))
+        .times(1) // Second call uses the cached checksums
+        .returning(|_| Ok(RELEASE_375_HTML.to_string()));
+
+    let mut cache = fixture_cache();
+    let version = Version::new(3, 7, 5);
+    cache
+        .fetch_digests(&version, &paths_provider, &mock)
+        .unwrap();
+    cache
+        .fetch_digests(&version, &paths_provider, &mock)
+        .unwrap();
+
+    let toolchain = cache
+        .query(&VersionReq::parse("=3.7.5").unwrap(), false)
+        .unwrap();
+    assert_eq!(
+        toolchain.source_digest,
+        Some(Digest::md5("1cd071f78ff6d9c7524c95303a3057aa"))
+    );
+    assert_eq!(
+        toolchain.win_pre_built_digest,
+        Some(Digest::md5("436b0f803d2a0b393590030b1cd59853"))
+    );
+
+    // Checksums are persisted in the cache file
+    let cache_json = fs::read_to_string(paths_provider.available_toolchains_cache_file()).unwrap();
+    assert!(cache_json.contains("1cd071f78ff6d9c7524c95303a3057aa"));
+}
+
+#[test]
+fn query_pre_releases() {
+    let cache = fixture_cache();
+    let version_req = VersionReq::parse("~3.9").unwrap();
+    assert!(cache.query(&version_req, false).is_err());
+    assert_eq!(
+        cache.query(&version_req, true).unwrap().version,
+        Version::parse("3.9.0-a2").unwrap()
+    );
+    // Explicitly requested pre-release
+    assert_eq!(
+        cache
+            .query(&VersionReq::parse("=3.9.0-a1").unwrap(), false)
+            .unwrap()
+            .version,
+        Version::parse("3.9.0-a1").unwrap()
+    );
+    assert_eq!(
+        cache.query(&VersionReq::STAR, false).unwrap().version,
+        Version::new(3, 8, 1)
+    );
+}
+
+#[test]
+fn parse_standalone_releases_json() {
+    let pre_builts =
+        parse_standalone_releases(STANDALONE_RELEASES_JSON, "x86_64-unknown-linux-gnu").unwrap();
+
+    // Most recent build, preferred flavor
+    let pre_built = &pre_builts[&Version::new(3, 8, 1)];
+    assert_eq!(
+        pre_built.name,
+        "cpython-3.8.1+20200217-x86_64-unknown-linux-gnu-install_only.tar.gz"
+    );
+    assert_eq!(
+        pre_built.filename(),
+        "cpython-3.8.1%2B20200217-x86_64-unknown-linux-gnu-install_only.tar.gz"
+    );
+    assert_eq!(
+        pre_built.digest_url.as_ref().unwrap().as_str(),
+        "https://github.com/indygreg/python-build-standalone/releases/download/20200217/SHA256SUMS"
+    );
+
+    // Checksum published next to the archive
+    let pre_built = &pre_builts[&Version::new(3, 7, 5)];
+    assert_eq!(
+        pre_built.name,
+        "cpython-3.7.5+20191025-x86_64-unknown-linux-gnu-pgo-full.tar.zst"
+    );
+    assert!(pre_built
+        .digest_url
+        .as_ref()
+        .unwrap()
+        .as_str()
+        .ends_with("pgo-full.tar.zst.sha256"));
+
+    assert!(pre_builts.contains_key(&Version::parse("3.9.0-a2").unwrap()));
+    assert_eq!(pre_builts.len(), 4);
+
+    let pre_builts =
+        parse_standalone_releases(STANDALONE_RELEASES_JSON, "aarch64-unknown-linux-gnu").unwrap();
+    assert_eq!(
+        pre_builts.keys().collect::<Vec<_>>(),
+        vec![&Version::new(3, 8, 1)]
+    );
+}
+
+#[test]
+fn fetch_standalone_pre_built_and_query() {
+    let home = create_test_temp_dir!();
+    let project_home = home.join(".hygeia");
+    fs::create_dir_all(project_home.join("cache")).unwrap();
+
+    let mut mock = MockPycorsHomeProviderTrait::new();
+    mock.expect_project_home().return_const(Some(project_home));
+    mock.expect_home().times(0).return_const(Some(home));
+    let paths_provider = PycorsPathsProvider::from(mock);
+
+    let mut mock = MockToolchainsCacheFetch::new();
+    mock.expect_get_standalone_releases()
+        .times(1)
+        .returning(|| Ok(STANDALONE_RELEASES_JSON.to_string()));
+
+    let mut cache = fixture_cache();
+    let version_req = VersionReq::parse("~3.7").unwrap();
+    assert!(cache.query_pre_built(&version_req, false).is_err());
+
+    cache
+        .fetch_standalone_pre_built("x86_64-unknown-linux-gnu", &paths_provider, &mock)
+        .unwrap();
+
+    // 3.7.6 is more recent but has no pre-built archive
+    assert_eq!(
+        cache.query(&version_req, false).unwrap().version,
+        Version::new(3, 7, 6)
+    );
+    assert_eq!(
+        cache.query_pre_built(&version_req, false).unwrap().version,
+        Version::new(3, 7, 5)
+    );
+}
+
+#[test]
+fn parse_sha256_files() {
+    let hex = "9d1b6e9d2a0e4f1b8c7a6d5e4f3a2b1c0d9e8f7a6b5c4d3e2f1a0b9c8d7e6f5a";
+    assert_eq!(
+        parse_sha256_file(&format!("{}\n", hex), "python.tar.gz"),
+        Some(Digest::sha256(hex))
+    );
+    let sums = format!(
+        "0000  other.tar.gz\n{}  cpython-3.8.1+20200217-x86_64-unknown-linux-gnu-install_only.tar.gz\n",
+        hex
+    );
+    assert_eq!(
+        parse_sha256_file(
+            &sums,
+            "cpython-3.8.1+20200217-x86_64-unknown-linux-gnu-install_only.tar.gz"
+        ),
+        Some(Digest::sha256(hex))
+    );
+    assert_eq!(parse_sha256_file(&sums, "missing.tar.gz"), None);
+}
+
+#[test]
+fn parse_pypy_versions_json() {
+    let pypys = parse_pypy_versions(PYPY_VERSIONS_JSON, ("linux", "x64")).unwrap();
+
+    // Nightly and release candidates do not have semantic versions
+    assert_eq!(
+        pypys
+            .iter()
+            .map(|pypy| (pypy.version.to_string(), pypy.pypy_version.to_string()))
+            .collect::<Vec<_>>(),
+        vec![
+            ("3.10.13".to_string(), "7.3.15".to_string()),
+            ("3.10.12".to_string(), "7.3.12".to_string()),
+            ("3.9.18".to_string(), "7.3.15".to_string()),
+        ]
+    );
+    assert_eq!(pypys[0].name, "pypy3.10-v7.3.15-linux64.tar.bz2");
+    assert_eq!(pypys[0].filename(), "pypy3.10-v7.3.15-linux64.tar.bz2");
+    assert!(pypys[0].stable);
+
+    let pypys = parse_pypy_versions(PYPY_VERSIONS_JSON, ("darwin", "arm64")).unwrap();
+    assert_eq!(pypys.len(), 1);
+    assert_eq!(pypys[0].name, "pypy3.9-v7.3.15-macos_arm64.tar.bz2");
+
+    assert!(parse_pypy_versions(PYPY_VERSIONS_JSON, ("win64", "x64"))
+        .unwrap()
+        .iter()
+        .all(|pypy| pypy.name.ends_with(".zip")));
+}
+
+#[test]
+fn parse_pypy_checksums() {
+    assert_eq!(
+        parse_pypy_checksum(PYPY_CHECKSUMS_HTML, "pypy3.10-v7.3.12-linux64.tar.bz2"),
+        Some(Digest::sha256(
+            "6c577993160b6f5ee8cab73cd1a807affcefafe2f7441c87bd926c10505e8731"
+        ))
+    );
+    assert_eq!(
+        parse_pypy_checksum(PYPY_CHECKSUMS_HTML, "pypy3.10-v7.3.15-win64.zip"),
+        None
+    );
+
+    // Plain sha256sum output, as served by a mirror
+    let hex = "33c584e9a70a71afd0cb7dd8ba9996720b911b3b8ed0156aea298d4487ad22c3";
+    let sums = format!("{}  pypy3.10-v7.3.15-linux64.tar.bz2\n", hex);
+    assert_eq!(
+        parse_pypy_checksum(&sums, "pypy3.10-v7.3.15-linux64.tar.bz2"),
+        Some(Digest::sha256(hex))
+    );
+}
+
+#[test]
+fn fetch_pypy_and_query() {
+    let home = create_test_temp_dir!();
+    let project_home = home.join(".hygeia");
+    fs::create_dir_all(project_home.join("cache")).unwrap();
+
+    let mut mock = MockPycorsHomeProviderTrait::new();
+    mock.expect_project_home().return_const(Some(project_home));
+    mock.expect_home().times(0).return_const(Some(home));
+    let paths_provider = PycorsPathsProvider::from(mock);
+
+    let mut mock = MockToolchainsCacheFetch::new();
+    mock.expect_get_pypy_versions()
+        .times(1)
+        .returning(|| Ok(PYPY_VERSIONS_JSON.to_string()));
+
+    let mut cache = fixture_cache();
+    let version_req = VersionReq::parse("~3.10").unwrap();
+    assert!(cache.query_pypy(&version_req, false).is_err());
+
+    cache
+        .fetch_pypy(("linux", "x64"), &paths_provider, &mock)
+        .unwrap();
+
+    assert_eq!(
+        cache.query_pypy(&version_req, false).unwrap().version,
+        Version::new(3, 10, 13)
+    );
+    assert_eq!(cache.query_all_pypy(&VersionReq::STAR, false).len(), 3);
+    let version_req = VersionReq::parse("~3.8").unwrap();
+    assert!(cache.query_pypy(&version_req, false).is_err());
+}
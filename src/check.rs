@@ -0,0 +1,294 @@
+//! Verification that an interpreter can import the extension modules of its standard
+//! library that depend on optional system libraries.
+//!
+//! CPython's build succeeds even when such a module could not be compiled (for
+//! example `_ssl` without the OpenSSL headers), so this is only noticed once the
+//! interpreter is used.
+
+use std::{
+    env::consts::EXE_SUFFIX,
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use semver::Version;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{utils, Result};
+
+/// Line of the install manifest recording the missing modules.
+pub const MISSING_MODULES_PREFIX: &str = "Missing modules:";
+
+/// Modules verified by default, with the first version of Python providing them.
+const DEFAULT_MODULES: &[(&str, (u64, u64))] = &[
+    ("_ssl", (2, 6)),
+    ("_hashlib", (2, 6)),
+    ("_sqlite3", (2, 6)),
+    ("_ctypes", (2, 6)),
+    ("_bz2", (3, 3)),
+    ("_lzma", (3, 3)),
+    ("zlib", (2, 6)),
+    ("readline", (2, 6)),
+];
+
+/// Imports the modules given as arguments, printing the ones that fail. Valid for
+/// both Python 2 and 3.
+const SCRIPT: &str = r#"import sys
+for name in sys.argv[1:]:
+    try:
+        __import__(name)
+    except Exception:
+        sys.stdout.write("missing " + name + "\n")
+sys.stdout.write("done\n")
+"#;
+
+#[derive(Debug, Error)]
+pub enum CheckError {
+    #[error("Failed to run {0:?} to verify its modules: {1}")]
+    NotRun(PathBuf, String),
+    #[error("No interpreter found in {0:?}")]
+    NoInterpreter(PathBuf),
+    #[error("{0} is missing modules: {}", .1.join(", "))]
+    MissingModules(String, Vec<String>),
+}
+
+/// `[check]` table of the configuration file: the modules verified after building
+/// from source and by `hygeia check`.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CheckConfig {
+    /// Modules to import (default: the ones depending on optional system libraries,
+    /// like `_ssl`, `_sqlite3`, `_ctypes`, `_lzma` and `readline`)
+    pub modules: Option<Vec<String>>,
+    /// Fail the installation when a module is missing instead of only warning
+    pub fail_on_missing: bool,
+}
+
+impl CheckConfig {
+    /// Modules to verify for Python `version`.
+    pub fn modules(&self, version: &Version) -> Vec<String> {
+        match &self.modules {
+            Some(modules) => modules.clone(),
+            None => DEFAULT_MODULES
+                .iter()
+                .filter(|(_, since)| (version.major, version.minor) >= *since)
+                .map(|(module, _)| module.to_string())
+                .collect(),
+        }
+    }
+}
+
+/// Interpreter found in `bin_dir`, preferring the most specific name.
+pub fn interpreter(bin_dir: &Path, version: &Version) -> Result<PathBuf> {
+    [
+        format!("python{}.{}", version.major, version.minor),
+        format!("python{}", version.major),
+        "python".to_string(),
+    ]
+    .iter()
+    .map(|name| bin_dir.join(format!("{}{}", name, EXE_SUFFIX)))
+    .find(|path| path.is_file())
+    .ok_or_else(|| CheckError::NoInterpreter(bin_dir.to_path_buf()).into())
+}
+
+/// Modules of `modules` that `python` fails to import.
+pub fn missing_modules(python: &Path, modules: &[String]) -> Result<Vec<String>> {
+    log::debug!("Importing {:?} with {:?}", modules, python);
+
+    let output = Command::new(python)
+        // Ignore `PYTHONPATH` and friends: only the standard library is verified
+        .arg("-E")
+        .arg("-c")
+        .arg(SCRIPT)
+        .args(modules)
+        .output()
+        .map_err(|e| CheckError::NotRun(python.to_path_buf(), e.to_string()))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() || !stdout.lines().any(|line| line == "done") {
+        return Err(CheckError::NotRun(
+            python.to_path_buf(),
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        )
+        .into());
+    }
+
+    Ok(stdout
+        .lines()
+        .filter_map(|line| line.strip_prefix("missing "))
+        .map(str::to_string)
+        .collect())
+}
+
+/// Record the missing modules in the install manifest of `install_dir`, replacing
+/// the result of a previous verification.
+pub fn record_in_info_file(install_dir: &Path, missing: &[String]) -> Result<()> {
+    let info_file = utils::get_info_file(install_dir);
+    let content = fs::read_to_string(&info_file)?;
+    let mut lines: Vec<&str> = content
+        .lines()
+        .filter(|line| !line.starts_with(MISSING_MODULES_PREFIX))
+        .collect();
+    let missing = if missing.is_empty() {
+        "none".to_string()
+    } else {
+        missing.join(", ")
+    };
+    let record = format!("{} {}", MISSING_MODULES_PREFIX, missing);
+    lines.push(&record);
+    fs::write(&info_file, format!("{}\n", lines.join("\n")))?;
+
+    Ok(())
+}
+
+/// Verify the modules of the interpreter freshly installed in `install_dir`, failing
+/// or warning about the missing ones (or an interpreter that cannot be run) depending
+/// on `config`. A failed installation is removed.
+pub fn verify_installation(
+    config: &CheckConfig,
+    install_dir: &Path,
+    bin_dir: &Path,
+    version: &Version,
+) -> Result<()> {
+    let missing = match interpreter(bin_dir, version)
+        .and_then(|python| missing_modules(&python, &config.modules(version)))
+    {
+        Ok(missing) => missing,
+        Err(e) if config.fail_on_missing => return Err(remove_installation(install_dir, e)),
+        Err(e) => {
+            log::warn!("{}", e);
+            return Ok(());
+        }
+    };
+    record_in_info_file(install_dir, &missing)?;
+
+    if missing.is_empty() {
+        log::info!("All modules of Python {} can be imported", version);
+        return Ok(());
+    }
+
+    let error = CheckError::MissingModules(format!("Python {}", version), missing);
+    if config.fail_on_missing {
+        Err(remove_installation(install_dir, error.into()))
+    } else {
+        log::warn!("{}", error);
+        log::warn!("Install the missing build dependencies and reinstall with '--force'.");
+        Ok(())
+    }
+}
+
+/// Remove the installation in `install_dir` that failed its verification, so that it
+/// is never selected.
+fn remove_installation(install_dir: &Path, error: anyhow::Error) -> anyhow::Error {
+    match fs::remove_dir_all(install_dir) {
+        Ok(()) => error.context(format!("Removed the failed installation {:?}", install_dir)),
+        Err(e) => error.context(format!(
+            "Failed to remove {:?} ({}): the failed installation is still in place",
+            install_dir, e
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use hygeia_test_helpers::{create_test_temp_dir, mock_executable, MockedOutput};
+
+    #[test]
+    fn default_modules() {
+        let config = CheckConfig::default();
+        let modules = config.modules(&Version::new(3, 8, 1));
+        assert!(modules.contains(&"_ssl".to_string()));
+        assert!(modules.contains(&"_lzma".to_string()));
+        let modules = config.modules(&Version::new(2, 7, 18));
+        assert!(modules.contains(&"_ctypes".to_string()));
+        assert!(!modules.contains(&"_lzma".to_string()));
+
+        let config = CheckConfig {
+            modules: Some(vec!["_tkinter".to_string()]),
+            fail_on_missing: false,
+        };
+        assert_eq!(config.modules(&Version::new(2, 7, 18)), vec!["_tkinter"]);
+    }
+
+    #[test]
+    fn missing_modules_reported() {
+        let tmp_dir = create_test_temp_dir!();
+        mock_executable(
+            &tmp_dir,
+            "python3.8",
+            MockedOutput {
+                out: Some("missing _ssl\nmissing readline\ndone\n"),
+                err: None,
+            },
+        )
+        .unwrap();
+        let python = interpreter(&tmp_dir, &Version::new(3, 8, 1)).unwrap();
+        let modules = vec!["_ssl".to_string(), "readline".to_string()];
+        assert_eq!(
+            missing_modules(&python, &modules).unwrap(),
+            vec!["_ssl", "readline"]
+        );
+    }
+
+    #[test]
+    fn missing_modules_not_run() {
+        let tmp_dir = create_test_temp_dir!();
+        // Not an interpreter: it ignores the script
+        mock_executable(
+            &tmp_dir,
+            "python3.8",
+            MockedOutput {
+                out: Some("Python 3.8.1\n"),
+                err: None,
+            },
+        )
+        .unwrap();
+        let python = interpreter(&tmp_dir, &Version::new(3, 8, 1)).unwrap();
+        let err = missing_modules(&python, &["_ssl".to_string()]).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<CheckError>(),
+            Some(CheckError::NotRun(..))
+        ));
+    }
+
+    #[test]
+    fn record_replaces_previous() {
+        let install_dir = create_test_temp_dir!();
+        utils::create_info_file(&install_dir, &Version::new(3, 8, 1)).unwrap();
+
+        record_in_info_file(&install_dir, &["_lzma".to_string()]).unwrap();
+        record_in_info_file(&install_dir, &[]).unwrap();
+
+        let content = fs::read_to_string(utils::get_info_file(&install_dir)).unwrap();
+        assert!(content.starts_with("Python 3.8.1 installed using "));
+        assert!(!content.contains("_lzma"));
+        assert!(content.ends_with("Missing modules: none\n"));
+    }
+
+    #[test]
+    fn verify_without_interpreter() {
+        let install_dir = create_test_temp_dir!();
+        utils::create_info_file(&install_dir, &Version::new(3, 8, 1)).unwrap();
+        let bin_dir = install_dir.join("bin");
+        let version = Version::new(3, 8, 1);
+
+        // Only a warning by default
+        let config = CheckConfig::default();
+        verify_installation(&config, &install_dir, &bin_dir, &version).unwrap();
+        assert!(install_dir.exists());
+
+        let config = CheckConfig {
+            modules: None,
+            fail_on_missing: true,
+        };
+        let err = verify_installation(&config, &install_dir, &bin_dir, &version).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<CheckError>(),
+            Some(CheckError::NoInterpreter(_))
+        ));
+        assert!(!install_dir.exists());
+    }
+}
@@ -0,0 +1,195 @@
+use std::{
+    fmt,
+    fs::File,
+    io::{self, BufReader, Write},
+    path::{Path, PathBuf},
+};
+
+use md5::Md5;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+use thiserror::Error;
+
+use crate::Result;
+
+#[derive(Debug, Error)]
+pub enum ChecksumError {
+    #[error("Checksum mismatch for {path:?}: expected {expected}, got {actual}")]
+    Mismatch {
+        path: PathBuf,
+        expected: Digest,
+        actual: Digest,
+    },
+}
+
+/// A published checksum (lowercase hex encoded) of a downloaded file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Digest {
+    Sha256(String),
+    Md5(String),
+}
+
+impl Digest {
+    pub fn sha256<S: AsRef<str>>(hex: S) -> Digest {
+        Digest::Sha256(hex.as_ref().trim().to_lowercase())
+    }
+
+    pub fn md5<S: AsRef<str>>(hex: S) -> Digest {
+        Digest::Md5(hex.as_ref().trim().to_lowercase())
+    }
+
+    /// Create a hasher using the same algorithm as this digest.
+    pub fn hasher(&self) -> Hasher {
+        match self {
+            Digest::Sha256(_) => Hasher::Sha256(Sha256::new()),
+            Digest::Md5(_) => Hasher::Md5(Md5::new()),
+        }
+    }
+
+    /// Compute the digest of a file using the same algorithm as this digest.
+    pub fn of_file<P>(&self, path: P) -> Result<Digest>
+    where
+        P: AsRef<Path>,
+    {
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut writer = HashingWriter::new(io::sink(), self.hasher());
+        io::copy(&mut reader, &mut writer)?;
+        Ok(writer.finalize()?)
+    }
+
+    /// Verify that the given file matches this digest.
+    pub fn verify_file<P>(&self, path: P) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let actual = self.of_file(path)?;
+        self.verify(path, actual)
+    }
+
+    pub(crate) fn verify(&self, path: &Path, actual: Digest) -> Result<()> {
+        if *self == actual {
+            Ok(())
+        } else {
+            Err(ChecksumError::Mismatch {
+                path: path.to_path_buf(),
+                expected: self.clone(),
+                actual,
+            }
+            .into())
+        }
+    }
+}
+
+impl fmt::Display for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Digest::Sha256(hex) => write!(f, "sha256:{}", hex),
+            Digest::Md5(hex) => write!(f, "md5:{}", hex),
+        }
+    }
+}
+
+pub enum Hasher {
+    Sha256(Sha256),
+    Md5(Md5),
+}
+
+impl Hasher {
+    pub fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Sha256(hasher) => hasher.update(data),
+            Hasher::Md5(hasher) => hasher.update(data),
+        }
+    }
+
+    pub fn finalize(self) -> Digest {
+        match self {
+            Hasher::Sha256(hasher) => Digest::Sha256(hex::encode(hasher.finalize())),
+            Hasher::Md5(hasher) => Digest::Md5(hex::encode(hasher.finalize())),
+        }
+    }
+}
+
+/// Writer hashing everything written through it.
+pub struct HashingWriter<W> {
+    inner: W,
+    hasher: Hasher,
+}
+
+impl<W> HashingWriter<W>
+where
+    W: Write,
+{
+    pub fn new(inner: W, hasher: Hasher) -> HashingWriter<W> {
+        HashingWriter { inner, hasher }
+    }
+
+    /// Flush the inner writer and return the digest of the written data.
+    pub fn finalize(mut self) -> io::Result<Digest> {
+        self.inner.flush()?;
+        Ok(self.hasher.finalize())
+    }
+}
+
+impl<W> Write for HashingWriter<W>
+where
+    W: Write,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    use hygeia_test_helpers::create_test_temp_dir;
+
+    #[test]
+    fn hashing_writer_sha256() {
+        let mut writer = HashingWriter::new(Vec::new(), Digest::sha256("").hasher());
+        writer.write_all(b"Hello world").unwrap();
+        assert_eq!(
+            writer.finalize().unwrap(),
+            Digest::sha256("64ec88ca00b268e5ba1a35678a1b5316d212f4f366b2477232534a8aeca37f3c")
+        );
+    }
+
+    #[test]
+    fn hashing_writer_md5() {
+        let mut writer = HashingWriter::new(Vec::new(), Digest::md5("").hasher());
+        writer.write_all(b"Hello world").unwrap();
+        assert_eq!(
+            writer.finalize().unwrap(),
+            Digest::md5("3e25960a79dbc69b674cd4ec67a72c62")
+        );
+    }
+
+    #[test]
+    fn verify_file() {
+        let dir = create_test_temp_dir!();
+        let file = dir.join("file.txt");
+        fs::write(&file, "Hello world").unwrap();
+
+        Digest::sha256("64EC88CA00B268E5BA1A35678A1B5316D212F4F366B2477232534A8AECA37F3C")
+            .verify_file(&file)
+            .unwrap();
+        let err = Digest::md5("00000000000000000000000000000000")
+            .verify_file(&file)
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ChecksumError>(),
+            Some(ChecksumError::Mismatch { .. })
+        ));
+    }
+}
@@ -2,23 +2,58 @@ use std::path::PathBuf;
 
 use structopt::{self, StructOpt};
 
+pub mod check;
+pub mod doctor;
+pub mod global;
 pub mod install;
 pub mod list;
 pub mod path;
+pub mod rescan;
 pub mod run;
 pub mod select;
 pub mod setup;
+pub mod uninstall;
+#[cfg(feature = "self-update")]
+pub mod update;
+pub mod venv;
 pub mod version;
+pub mod which;
 
 #[derive(StructOpt, Debug)]
 pub struct VersionOrPath {
-    version_or_path: String,
+    #[structopt(required_unless = "venv")]
+    version_or_path: Option<String>,
+
+    /// Select a virtual environment (created with 'hygeia venv create') instead
+    #[structopt(long, conflicts_with = "version-or-path")]
+    venv: Option<String>,
+
+    /// Consider installed pre-releases (alpha, beta and release candidates)
+    #[structopt(long)]
+    pre: bool,
 }
 #[derive(StructOpt, Debug)]
 pub enum Command {
     /// List installed Python versions
+    ///
+    /// Use '--available' to list the versions that can be installed instead.
+    ///
+    /// For example:
+    ///     hygeia list --available ~3.8
     #[structopt(name = "list")]
-    List,
+    List {
+        /// List versions available for installation
+        #[structopt(long, short)]
+        available: bool,
+
+        /// Only list available versions compatible with this one
+        #[structopt(requires = "available")]
+        version: Option<String>,
+
+        /// Include pre-releases (alpha, beta and release candidates)
+        #[structopt(long, requires = "available")]
+        pre: bool,
+    },
 
     /// Get path to active interpreter
     ///
@@ -58,11 +93,40 @@ pub enum Command {
     #[structopt(name = "select")]
     Select(VersionOrPath),
 
+    /// Set the global Python version, used outside of projects
+    ///
+    /// The global version is used when no '.python-version' is found, instead
+    /// of the latest installed interpreter. Without argument, print it.
+    ///
+    /// For example:
+    ///   hygeia global ~3.8
+    ///   hygeia global system
+    ///   hygeia global --unset
+    #[structopt(name = "global")]
+    Global {
+        /// Version requirement, path or 'system'
+        version: Option<String>,
+
+        /// Remove the global version
+        #[structopt(long, conflicts_with = "version")]
+        unset: bool,
+    },
+
     /// Install version, either from the provided version or from '.python-version'
     #[structopt(name = "install")]
     Install {
-        /// Specified version to install
-        from_version: Option<String>,
+        /// Specified versions to install, prefixed by 'pypy' for PyPy (for example
+        /// 'pypy3.10')
+        ///
+        /// Several versions are downloaded concurrently, then built `--jobs` at a time.
+        from_versions: Vec<String>,
+
+        /// Build from a source archive ('.tgz' or '.tar.xz'), a source directory (for
+        /// example a git checkout) or the URL of an archive, instead of a released version
+        ///
+        /// The version is read from 'Include/patchlevel.h'.
+        #[structopt(long, conflicts_with_all = &["from-versions", "prebuilt"])]
+        from_source: Option<String>,
 
         /// Build toolchain in release mode, with optimizations (slower)
         #[structopt(short, long)]
@@ -76,10 +140,99 @@ pub enum Command {
         #[structopt(long = "select", short = "s")]
         select: bool,
 
+        /// Consider pre-releases (alpha, beta and release candidates)
+        #[structopt(long)]
+        pre: bool,
+
+        /// Do not verify the downloaded package against its published checksum and signature
+        #[structopt(long)]
+        skip_verify: bool,
+
+        /// Only skip the verification of the source archive's OpenPGP signature,
+        /// keeping the checksum verification
+        #[structopt(long)]
+        skip_signature: bool,
+
+        /// Install a pre-built interpreter from python-build-standalone instead of
+        /// compiling it (Linux only)
+        #[structopt(long)]
+        prebuilt: bool,
+
+        /// Build profile to compile with, defined in the configuration file as
+        /// '[profiles.<name>]' (default: 'default')
+        #[structopt(long)]
+        profile: Option<String>,
+
+        /// Number of versions to build at the same time (default: 'parallel_builds'
+        /// from the configuration file, or 1)
+        #[structopt(short, long)]
+        jobs: Option<usize>,
+
+        /// Build even if libraries or tools Python needs are missing (the modules
+        /// depending on them are then not built)
+        #[structopt(long)]
+        ignore_missing_deps: bool,
+
         #[structopt(flatten)]
         install_extra_packages: InstallExtraPackagesOptions,
     },
 
+    /// Uninstall a Python version installed by hygeia
+    ///
+    /// Python interpreters not installed by hygeia (for example system ones) are
+    /// never removed. Shims that are not provided anymore by any remaining
+    /// toolchain are removed too.
+    ///
+    /// For example:
+    ///     hygeia uninstall =3.7.2
+    #[structopt(name = "uninstall")]
+    Uninstall {
+        /// Version to uninstall
+        version: String,
+    },
+
+    /// Manage virtual environments
+    ///
+    /// Virtual environments are stored in '${HYGEIA_HOME}/venvs'. Use
+    /// 'hygeia select --venv <name>' to use one.
+    ///
+    /// For example:
+    ///     hygeia venv create web --version ~3.8
+    ///     hygeia venv list
+    #[structopt(name = "venv")]
+    Venv(VenvCommand),
+
+    /// Rebuild the index of the Python interpreters found in PATH
+    ///
+    /// The index avoids running every interpreter found in PATH each time a
+    /// shim is used. It is updated automatically when interpreters are added,
+    /// modified or removed; use this command to force a full rescan.
+    #[structopt(name = "rescan")]
+    Rescan,
+
+    /// Diagnose the setup, suggesting how to fix the problems found
+    ///
+    /// Checks that the shims come first in PATH and are up to date, that the shell
+    /// configuration contains the block added by 'setup', that the caches are
+    /// valid and that every installed interpreter works.
+    #[structopt(name = "doctor")]
+    Doctor,
+
+    /// Verify that an installed Python version can import its extension modules
+    ///
+    /// Modules like '_ssl' or '_sqlite3' are silently skipped when their system
+    /// library is missing while building from source. The modules verified are
+    /// set by the '[check]' table of the configuration file. The result is
+    /// recorded in the installation's manifest.
+    ///
+    /// For example:
+    ///     hygeia check 3.8
+    #[structopt(name = "check")]
+    Check {
+        /// Version to verify
+        version: String,
+    },
+
     /// Run a binary from the installed '.python-version'
     ///
     /// For example:
@@ -94,6 +247,20 @@ pub enum Command {
         command: String,
     },
 
+    /// Print the absolute path of the binary a shim would run
+    ///
+    /// The same toolchain as the shim is used (taking into account, for
+    /// example, the version in 'python3.8').
+    ///
+    /// For example:
+    ///     hygeia which pip3
+    ///     /home/user/.hygeia/installed/cpython/3.8.1/bin/pip3
+    #[structopt(name = "which")]
+    Which {
+        /// Command to look for
+        command: String,
+    },
+
     /// Setup the shim
     ///
     /// This will install pycor's binary to '~/.hygeia/bin' and add the
@@ -109,6 +276,31 @@ pub enum Command {
 }
 
 #[derive(StructOpt, Debug)]
+pub enum VenvCommand {
+    /// Create a virtual environment
+    #[structopt(name = "create")]
+    Create {
+        /// Name of the virtual environment
+        name: String,
+
+        /// Base interpreter version (defaults to the active one)
+        #[structopt(long = "version", short = "v")]
+        version: Option<String>,
+    },
+
+    /// List virtual environments, with their base interpreter version
+    #[structopt(name = "list")]
+    List,
+
+    /// Remove a virtual environment
+    #[structopt(name = "remove")]
+    Remove {
+        /// Name of the virtual environment
+        name: String,
+    },
+}
+
+#[derive(StructOpt, Debug, Clone)]
 pub struct InstallExtraPackagesOptions {
     /// Install extra Python packages from file at default location
     /// ('${HYGEIA_HOME}/extra-packages-to-install.txt')
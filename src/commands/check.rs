@@ -0,0 +1,70 @@
+use anyhow::Result;
+use semver::VersionReq;
+use thiserror::Error;
+
+use crate::{
+    check::{self, CheckError},
+    config::Config,
+    constants::EXECUTABLE_NAME,
+    toolchain::{
+        find_compatible_toolchain_of, find_installed_toolchains, implementation::Implementation,
+    },
+    utils::{self, directory::PycorsPathsProviderFromEnv},
+};
+
+#[derive(Debug, Error)]
+pub enum CheckCommandError {
+    #[error("Python version {0} not found!")]
+    NotInstalled(VersionReq),
+}
+
+pub fn run(requested_version: String) -> Result<()> {
+    let (implementation, version_req) = Implementation::parse_requirement(&requested_version)?;
+
+    let paths_provider = PycorsPathsProviderFromEnv::new();
+    let config = Config::load(&paths_provider)?;
+    let installed_toolchains = find_installed_toolchains(&paths_provider)?;
+    let toolchain = find_compatible_toolchain_of(
+        implementation,
+        &version_req,
+        &installed_toolchains,
+        config.pre_releases,
+    )
+    .ok_or_else(|| CheckCommandError::NotInstalled(version_req.clone()))?;
+    let label = format!("{} {}", implementation.label(), toolchain.version);
+
+    let python = check::interpreter(&toolchain.location, &toolchain.version)?;
+    let modules = config.check.modules(&toolchain.version);
+    let missing = check::missing_modules(&python, &modules)?;
+
+    // Only the manifests of our own installations are updated
+    let install_dir = toolchain
+        .location
+        .parent()
+        .filter(|dir| utils::get_info_file(dir).is_file());
+    if let Some(install_dir) = install_dir {
+        check::record_in_info_file(install_dir, &missing)?;
+    }
+
+    for module in &modules {
+        if missing.contains(module) {
+            println!("❌ {}", module);
+        } else {
+            println!("✅ {}", module);
+        }
+    }
+
+    if missing.is_empty() {
+        println!("All modules of {} can be imported.", label);
+        Ok(())
+    } else {
+        if install_dir.is_some() {
+            println!(
+                "Install the missing build dependencies and reinstall with '{} install --force {}'.",
+                EXECUTABLE_NAME,
+                implementation.pin(&toolchain.version)
+            );
+        }
+        Err(CheckError::MissingModules(label, missing).into())
+    }
+}
@@ -0,0 +1,807 @@
+//! Diagnose a broken setup, suggesting a fix for each problem found.
+
+use std::{
+    env::{self, consts::EXE_SUFFIX},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use semver::Version;
+use thiserror::Error;
+
+use crate::{
+    cache::AvailableToolchainsCache,
+    check,
+    constants::{
+        EXECUTABLE_NAME, INFO_FILE, SHELL_CONFIG_IDENTIFYING_PATTERN_END,
+        SHELL_CONFIG_IDENTIFYING_PATTERN_START, SHIMS_DIRECTORY_IDENTIFIER_FILE,
+    },
+    toolchain::{implementation::Implementation, index::InterpreterIndex, python_version_of},
+    utils::directory::{
+        shell::{Bash, ShellPathProvider, Zsh},
+        PycorsHomeProviderTrait, PycorsPathsProvider, PycorsPathsProviderFromEnv,
+    },
+    Result,
+};
+
+#[derive(Debug, Error)]
+pub enum DoctorError {
+    #[error("{0} problem(s) found")]
+    ProblemsFound(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Status {
+    Ok,
+    /// Something unusual that does not prevent hygeia from working
+    Warning,
+    Error,
+}
+
+/// Outcome of a check.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Check {
+    pub status: Status,
+    pub message: String,
+    /// How to fix the problem
+    pub fix: Option<String>,
+}
+
+impl Check {
+    fn ok<S: Into<String>>(message: S) -> Check {
+        Check {
+            status: Status::Ok,
+            message: message.into(),
+            fix: None,
+        }
+    }
+
+    fn warning<S: Into<String>, F: Into<String>>(message: S, fix: F) -> Check {
+        Check {
+            status: Status::Warning,
+            message: message.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn error<S: Into<String>, F: Into<String>>(message: S, fix: F) -> Check {
+        Check {
+            status: Status::Error,
+            message: message.into(),
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+/// Shells whose configuration files `setup` modifies.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SetupShell {
+    Bash,
+    Zsh,
+    Other,
+}
+
+impl SetupShell {
+    /// Shell from the `SHELL` environment variable (like `/bin/bash`).
+    pub fn from_env_value(shell: Option<&str>) -> SetupShell {
+        match shell.and_then(|shell| Path::new(shell).file_name()?.to_str()) {
+            Some("bash") => SetupShell::Bash,
+            Some("zsh") => SetupShell::Zsh,
+            _ => SetupShell::Other,
+        }
+    }
+
+    /// Command configuring this shell.
+    fn setup_command(self) -> String {
+        let shell = match self {
+            SetupShell::Bash => "bash",
+            SetupShell::Zsh => "zsh",
+            SetupShell::Other if cfg!(windows) => "powershell",
+            SetupShell::Other => "<shell>",
+        };
+        format!("{} setup {}", EXECUTABLE_NAME, shell)
+    }
+}
+
+pub fn run() -> Result<()> {
+    let paths_provider = PycorsPathsProviderFromEnv::new();
+    let shell = SetupShell::from_env_value(env::var("SHELL").ok().as_deref());
+
+    let checks = all_checks(&paths_provider, &env::current_exe()?, shell);
+    for check in &checks {
+        let symbol = match check.status {
+            Status::Ok => "✅",
+            Status::Warning => "⚠️ ",
+            Status::Error => "❌",
+        };
+        println!("{} {}", symbol, check.message);
+        if let Some(fix) = &check.fix {
+            println!("   ↳ {}", fix);
+        }
+    }
+
+    let problems = checks
+        .iter()
+        .filter(|check| check.status == Status::Error)
+        .count();
+    if problems == 0 {
+        println!();
+        println!("🩺 No problem found!");
+        Ok(())
+    } else {
+        Err(DoctorError::ProblemsFound(problems).into())
+    }
+}
+
+/// Run all the checks, `current_exe` being the binary the shims should be copies of.
+pub fn all_checks<P>(
+    paths_provider: &PycorsPathsProvider<P>,
+    current_exe: &Path,
+    shell: SetupShell,
+) -> Vec<Check>
+where
+    P: PycorsHomeProviderTrait,
+{
+    let mut checks = vec![
+        check_shims_in_path(paths_provider, shell),
+        check_shims_identifier_file(paths_provider, shell),
+        check_shim_executable(paths_provider, current_exe, shell),
+        check_shim_links(paths_provider, shell),
+    ];
+    checks.extend(check_shell_config(paths_provider, shell));
+    checks.push(check_cache(paths_provider));
+    checks.push(check_interpreter_index(paths_provider));
+    checks.extend(check_installed(paths_provider));
+    checks
+}
+
+/// Path of the shim executable, copied from hygeia by `setup`.
+fn shim_executable<P>(paths_provider: &PycorsPathsProvider<P>) -> PathBuf
+where
+    P: PycorsHomeProviderTrait,
+{
+    paths_provider
+        .shims()
+        .join(format!("{}{}", EXECUTABLE_NAME, EXE_SUFFIX))
+}
+
+/// Whether two paths are the same directory, even if written differently.
+fn same_dir(dir1: &Path, dir2: &Path) -> bool {
+    match (dir1.canonicalize(), dir2.canonicalize()) {
+        (Ok(dir1), Ok(dir2)) => dir1 == dir2,
+        _ => dir1 == dir2,
+    }
+}
+
+/// The shims directory should come first in `PATH`: a Python interpreter found
+/// before would be used instead of the shims.
+pub fn check_shims_in_path<P>(paths_provider: &PycorsPathsProvider<P>, shell: SetupShell) -> Check
+where
+    P: PycorsHomeProviderTrait,
+{
+    let shims_dir = paths_provider.shims();
+    let paths = paths_provider.paths();
+    let position = match paths.iter().position(|path| same_dir(path, &shims_dir)) {
+        Some(position) => position,
+        None => {
+            return Check::error(
+                format!("Shims directory {:?} is not in PATH", shims_dir),
+                format!("Run '{}' and open a new shell", shell.setup_command()),
+            )
+        }
+    };
+    if position == 0 {
+        return Check::ok("Shims directory is first in PATH");
+    }
+
+    let shadowing: Vec<String> = paths[..position]
+        .iter()
+        .filter(|path| {
+            ["python", "python3"]
+                .iter()
+                .any(|python| path.join(format!("{}{}", python, EXE_SUFFIX)).is_file())
+        })
+        .map(|path| path.display().to_string())
+        .collect();
+    if shadowing.is_empty() {
+        Check::warning(
+            "Shims directory is in PATH, but not first",
+            "No Python interpreter is found before it, but one added there later would be used instead of the shims",
+        )
+    } else {
+        Check::error(
+            format!(
+                "Python interpreters in {} are found before the shims in PATH",
+                shadowing.join(", ")
+            ),
+            format!(
+                "Make sure {}'s configuration block is at the end of your shell configuration, after other changes to PATH",
+                EXECUTABLE_NAME
+            ),
+        )
+    }
+}
+
+/// Without its identifier file, the shims would be mistaken for Python interpreters.
+pub fn check_shims_identifier_file<P>(
+    paths_provider: &PycorsPathsProvider<P>,
+    shell: SetupShell,
+) -> Check
+where
+    P: PycorsHomeProviderTrait,
+{
+    let identifier_file = paths_provider.shims_directory_identifier_file();
+    if identifier_file.is_file() {
+        Check::ok(format!(
+            "Shims directory identifier file {:?} exists",
+            SHIMS_DIRECTORY_IDENTIFIER_FILE
+        ))
+    } else {
+        Check::error(
+            format!(
+                "Shims directory identifier file {:?} is missing",
+                identifier_file
+            ),
+            format!("Run '{}' to recreate it", shell.setup_command()),
+        )
+    }
+}
+
+/// Whether two files are hard links to the same data.
+#[cfg(unix)]
+fn same_file(file1: &Path, file2: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    match (fs::metadata(file1), fs::metadata(file2)) {
+        (Ok(metadata1), Ok(metadata2)) => {
+            metadata1.dev() == metadata2.dev() && metadata1.ino() == metadata2.ino()
+        }
+        _ => false,
+    }
+}
+
+/// Whether two files are hard links to the same data, approximated by their size
+/// and modification time (shared by hard links).
+#[cfg(not(unix))]
+fn same_file(file1: &Path, file2: &Path) -> bool {
+    match (fs::metadata(file1), fs::metadata(file2)) {
+        (Ok(metadata1), Ok(metadata2)) => {
+            metadata1.len() == metadata2.len()
+                && metadata1.modified().ok() == metadata2.modified().ok()
+        }
+        _ => false,
+    }
+}
+
+/// Whether two files have the same content.
+fn same_content(file1: &Path, file2: &Path) -> bool {
+    if same_file(file1, file2) {
+        return true;
+    }
+    match (fs::metadata(file1), fs::metadata(file2)) {
+        (Ok(metadata1), Ok(metadata2)) if metadata1.len() == metadata2.len() => {
+            match (fs::read(file1), fs::read(file2)) {
+                (Ok(content1), Ok(content2)) => content1 == content2,
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+/// The shim executable is a copy of hygeia, made by `setup`. Updating hygeia without
+/// running `setup` again leaves an older version behind.
+pub fn check_shim_executable<P>(
+    paths_provider: &PycorsPathsProvider<P>,
+    current_exe: &Path,
+    shell: SetupShell,
+) -> Check
+where
+    P: PycorsHomeProviderTrait,
+{
+    let shim = shim_executable(paths_provider);
+    if !shim.is_file() {
+        Check::error(
+            format!("Shim executable {:?} is missing", shim),
+            format!("Run '{}' to copy it", shell.setup_command()),
+        )
+    } else if !same_content(&shim, current_exe) {
+        Check::error(
+            format!(
+                "Shim executable {:?} differs from {:?} (stale shim)",
+                shim, current_exe
+            ),
+            format!(
+                "Run '{}' to copy the current {} into the shims directory",
+                shell.setup_command(),
+                EXECUTABLE_NAME
+            ),
+        )
+    } else {
+        Check::ok(format!(
+            "Shim executable is up to date with {:?}",
+            current_exe
+        ))
+    }
+}
+
+/// Every shim (`python3`, `pip`, ...) should be a hard link to the shim executable.
+pub fn check_shim_links<P>(paths_provider: &PycorsPathsProvider<P>, shell: SetupShell) -> Check
+where
+    P: PycorsHomeProviderTrait,
+{
+    let shim = shim_executable(paths_provider);
+    let entries = match fs::read_dir(paths_provider.shims()) {
+        Ok(entries) => entries,
+        Err(e) => {
+            return Check::error(
+                format!("Failed to read shims directory: {}", e),
+                format!("Run '{}' to create it", shell.setup_command()),
+            )
+        }
+    };
+
+    let shims: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && *path != shim)
+        .filter(|path| {
+            path.file_name()
+                .map_or(false, |name| name != SHIMS_DIRECTORY_IDENTIFIER_FILE)
+        })
+        .collect();
+    let mut stale: Vec<String> = shims
+        .iter()
+        .filter(|path| !same_file(path, &shim))
+        .filter_map(|path| Some(path.file_name()?.to_string_lossy().into_owned()))
+        .collect();
+    stale.sort();
+
+    if stale.is_empty() {
+        Check::ok(format!(
+            "All {} shims are hard links to the shim executable",
+            shims.len()
+        ))
+    } else {
+        Check::error(
+            format!(
+                "{} shim(s) are not hard links to the shim executable: {}",
+                stale.len(),
+                stale.join(", ")
+            ),
+            format!("Run '{}' to recreate them", shell.setup_command()),
+        )
+    }
+}
+
+/// The configuration files of the shell should contain the block written by `setup`.
+pub fn check_shell_config<P>(
+    paths_provider: &PycorsPathsProvider<P>,
+    shell: SetupShell,
+) -> Vec<Check>
+where
+    P: PycorsHomeProviderTrait,
+{
+    let (rc_files, config_file) = match shell {
+        SetupShell::Bash => (Bash::new().shell_rcs(), Bash::new().file_path()),
+        SetupShell::Zsh => (Zsh::new().shell_rcs(), Zsh::new().file_path()),
+        SetupShell::Other => {
+            return vec![Check::ok(
+                "Shell configuration not checked (only bash and zsh configuration files are)",
+            )]
+        }
+    };
+    let home = match paths_provider.home() {
+        Some(home) => home,
+        None => {
+            return vec![Check::warning(
+                "Shell configuration not checked: home directory not found",
+                "Set the HOME environment variable",
+            )]
+        }
+    };
+
+    let mut checks: Vec<Check> = rc_files
+        .iter()
+        .map(|rc_file| {
+            let rc_file = home.join(rc_file);
+            let content = fs::read_to_string(&rc_file).unwrap_or_default();
+            let start = content.find(SHELL_CONFIG_IDENTIFYING_PATTERN_START);
+            let end = content.find(SHELL_CONFIG_IDENTIFYING_PATTERN_END);
+            match (start, end) {
+                (Some(start), Some(end)) if start < end => {
+                    Check::ok(format!("{:?} contains the configuration block", rc_file))
+                }
+                (None, None) => Check::error(
+                    format!("{:?} does not contain the configuration block", rc_file),
+                    format!("Run '{}' to add it", shell.setup_command()),
+                ),
+                _ => Check::error(
+                    format!("The configuration block of {:?} is incomplete", rc_file),
+                    format!(
+                        "Remove what is left of it, then run '{}'",
+                        shell.setup_command()
+                    ),
+                ),
+            }
+        })
+        .collect();
+
+    let config_file = paths_provider.project_home().join(config_file);
+    if !config_file.is_file() {
+        checks.push(Check::error(
+            format!("Shell configuration {:?} is missing", config_file),
+            format!("Run '{}' to recreate it", shell.setup_command()),
+        ));
+    }
+
+    checks
+}
+
+/// The cache of available versions should be valid JSON.
+pub fn check_cache<P>(paths_provider: &PycorsPathsProvider<P>) -> Check
+where
+    P: PycorsHomeProviderTrait,
+{
+    let cache_file = paths_provider.available_toolchains_cache_file();
+    let content = match fs::read_to_string(&cache_file) {
+        Ok(content) => content,
+        Err(_) => return Check::ok("No cache of available versions yet"),
+    };
+    match serde_json::from_str::<AvailableToolchainsCache>(&content) {
+        Ok(_) => Check::ok("Cache of available versions is valid"),
+        Err(e) => Check::error(
+            format!("Cache {:?} is corrupted: {}", cache_file, e),
+            format!(
+                "Delete it, it is downloaded again the next time it is needed (for example by '{} list --available')",
+                EXECUTABLE_NAME
+            ),
+        ),
+    }
+}
+
+/// The index of the interpreters found in `PATH` should be valid JSON.
+pub fn check_interpreter_index<P>(paths_provider: &PycorsPathsProvider<P>) -> Check
+where
+    P: PycorsHomeProviderTrait,
+{
+    let index_file = paths_provider.interpreter_index_cache_file();
+    let content = match fs::read_to_string(&index_file) {
+        Ok(content) => content,
+        Err(_) => return Check::ok("No index of the interpreters in PATH yet"),
+    };
+    match serde_json::from_str::<InterpreterIndex>(&content) {
+        Ok(_) => Check::ok("Index of the interpreters in PATH is valid"),
+        Err(e) => Check::error(
+            format!("Index {:?} is corrupted: {}", index_file, e),
+            format!("Run '{} rescan' to rebuild it", EXECUTABLE_NAME),
+        ),
+    }
+}
+
+/// Every directory of `installed/` should contain a working interpreter of its version.
+pub fn check_installed<P>(paths_provider: &PycorsPathsProvider<P>) -> Vec<Check>
+where
+    P: PycorsHomeProviderTrait,
+{
+    let mut checks = Vec::new();
+    for implementation in &Implementation::ALL {
+        let installed_dir = paths_provider.installed_implementation(*implementation);
+        let mut dirs: Vec<PathBuf> = match fs::read_dir(&installed_dir) {
+            Ok(entries) => entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.is_dir())
+                .collect(),
+            Err(_) => continue,
+        };
+        dirs.sort();
+
+        for dir in dirs {
+            let name = dir
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            match Version::parse(&name) {
+                Ok(version) => checks.push(check_installation(
+                    paths_provider,
+                    *implementation,
+                    &version,
+                )),
+                Err(_) => checks.push(Check::warning(
+                    format!("{:?} is not named after a version, it is ignored", dir),
+                    "Remove it if it is not needed",
+                )),
+            }
+        }
+    }
+
+    if checks.is_empty() {
+        checks.push(Check::ok(format!(
+            "No interpreter installed by {}",
+            EXECUTABLE_NAME
+        )));
+    }
+    checks
+}
+
+fn check_installation<P>(
+    paths_provider: &PycorsPathsProvider<P>,
+    implementation: Implementation,
+    version: &Version,
+) -> Check
+where
+    P: PycorsHomeProviderTrait,
+{
+    let pin = implementation.pin(version);
+    let reinstall = format!(
+        "Reinstall it with '{} install --force {}' or remove it with '{} uninstall {}'",
+        EXECUTABLE_NAME, pin, EXECUTABLE_NAME, pin
+    );
+    let label = format!("{} {}", implementation.label(), version);
+
+    let bin_dir = paths_provider.implementation_bin_dir(implementation, version);
+    let interpreter = match check::interpreter(&bin_dir, version) {
+        Ok(interpreter) => interpreter,
+        Err(_) => {
+            return Check::error(
+                format!("{}: no interpreter found in {:?}", label, bin_dir),
+                reinstall,
+            )
+        }
+    };
+
+    match python_version_of(&interpreter) {
+        None => Check::error(
+            format!("{}: {:?} does not run", label, interpreter),
+            reinstall,
+        ),
+        // Pre-releases are reported like '3.12.0rc1', not as semantic versions
+        Some(found)
+            if (found.major, found.minor, found.patch)
+                != (version.major, version.minor, version.patch) =>
+        {
+            Check::error(
+                format!("{}: {:?} is Python {}", label, interpreter, found),
+                reinstall,
+            )
+        }
+        Some(_) => {
+            let install_dir = paths_provider.implementation_install_dir(implementation, version);
+            if install_dir.join(INFO_FILE).is_file() {
+                Check::ok(format!("{} works", label))
+            } else {
+                Check::warning(
+                    format!(
+                        "{} works, but {:?} is missing (it is then not listed as installed by {})",
+                        label, INFO_FILE, EXECUTABLE_NAME
+                    ),
+                    reinstall,
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use hygeia_test_helpers::{create_test_temp_dir, mock_executable, MockedOutput};
+
+    use crate::utils::directory::MockPycorsHomeProviderTrait;
+
+    fn paths_provider(
+        home: &Path,
+        paths: Vec<PathBuf>,
+    ) -> PycorsPathsProvider<MockPycorsHomeProviderTrait> {
+        let mut mock = MockPycorsHomeProviderTrait::new();
+        mock.expect_project_home()
+            .return_const(Some(home.join(".hygeia")));
+        mock.expect_home().return_const(Some(home.to_path_buf()));
+        mock.expect_paths().return_const(paths);
+        PycorsPathsProvider::from(mock)
+    }
+
+    #[test]
+    fn shell_from_env() {
+        assert_eq!(
+            SetupShell::from_env_value(Some("/bin/bash")),
+            SetupShell::Bash
+        );
+        assert_eq!(
+            SetupShell::from_env_value(Some("/usr/bin/zsh")),
+            SetupShell::Zsh
+        );
+        assert_eq!(
+            SetupShell::from_env_value(Some("/usr/bin/fish")),
+            SetupShell::Other
+        );
+        assert_eq!(SetupShell::from_env_value(None), SetupShell::Other);
+    }
+
+    #[test]
+    fn shims_in_path() {
+        let home = create_test_temp_dir!();
+        let shims_dir = home.join(".hygeia").join("shims");
+        let usr_bin = home.join("usr_bin");
+        let empty_bin = home.join("empty_bin");
+        for dir in &[&shims_dir, &usr_bin, &empty_bin] {
+            fs::create_dir_all(dir).unwrap();
+        }
+        fs::write(usr_bin.join(format!("python3{}", EXE_SUFFIX)), "").unwrap();
+
+        let status = |paths: Vec<PathBuf>| {
+            check_shims_in_path(&paths_provider(&home, paths), SetupShell::Bash).status
+        };
+        assert_eq!(status(vec![usr_bin.clone()]), Status::Error);
+        assert_eq!(status(vec![shims_dir.clone(), usr_bin.clone()]), Status::Ok);
+        assert_eq!(
+            status(vec![empty_bin, shims_dir.clone(), usr_bin.clone()]),
+            Status::Warning
+        );
+
+        let check = check_shims_in_path(
+            &paths_provider(&home, vec![usr_bin.clone(), shims_dir]),
+            SetupShell::Bash,
+        );
+        assert_eq!(check.status, Status::Error);
+        assert!(check.message.contains(&usr_bin.display().to_string()));
+    }
+
+    #[test]
+    fn shims_executable_and_links() {
+        let home = create_test_temp_dir!();
+        let paths_provider = paths_provider(&home, Vec::new());
+        let shims_dir = paths_provider.shims();
+        fs::create_dir_all(&shims_dir).unwrap();
+
+        let current_exe = home.join("hygeia-current");
+        fs::write(&current_exe, "hygeia v2").unwrap();
+        assert_eq!(
+            check_shim_executable(&paths_provider, &current_exe, SetupShell::Bash).status,
+            Status::Error
+        );
+
+        let shim = shim_executable(&paths_provider);
+        fs::write(&shim, "hygeia v1").unwrap();
+        let check = check_shim_executable(&paths_provider, &current_exe, SetupShell::Bash);
+        assert_eq!(check.status, Status::Error);
+        assert!(check.message.contains("stale shim"));
+        assert_eq!(
+            check.fix.unwrap(),
+            "Run 'hygeia setup bash' to copy the current hygeia into the shims directory"
+        );
+
+        fs::copy(&current_exe, &shim).unwrap();
+        assert_eq!(
+            check_shim_executable(&paths_provider, &current_exe, SetupShell::Bash).status,
+            Status::Ok
+        );
+
+        fs::write(paths_provider.shims_directory_identifier_file(), "").unwrap();
+        fs::hard_link(&shim, shims_dir.join(format!("python3{}", EXE_SUFFIX))).unwrap();
+        assert_eq!(
+            check_shim_links(&paths_provider, SetupShell::Bash),
+            Check::ok("All 1 shims are hard links to the shim executable")
+        );
+
+        fs::write(shims_dir.join(format!("pip{}", EXE_SUFFIX)), "hygeia v0").unwrap();
+        let check = check_shim_links(&paths_provider, SetupShell::Bash);
+        assert_eq!(check.status, Status::Error);
+        assert!(check.message.ends_with(&format!(": pip{}", EXE_SUFFIX)));
+    }
+
+    #[test]
+    fn shell_config() {
+        let home = create_test_temp_dir!();
+        let paths_provider = paths_provider(&home, Vec::new());
+        let config_file = paths_provider.project_home().join(Zsh::new().file_path());
+        fs::create_dir_all(config_file.parent().unwrap()).unwrap();
+        fs::write(&config_file, "").unwrap();
+
+        let checks = check_shell_config(&paths_provider, SetupShell::Zsh);
+        assert_eq!(checks.len(), 1);
+        assert_eq!(checks[0].status, Status::Error);
+
+        fs::write(
+            home.join(".zshrc"),
+            format!(
+                "# {}\nexport HYGEIA_HOME=...\n# {}\n",
+                SHELL_CONFIG_IDENTIFYING_PATTERN_START, SHELL_CONFIG_IDENTIFYING_PATTERN_END
+            ),
+        )
+        .unwrap();
+        let checks = check_shell_config(&paths_provider, SetupShell::Zsh);
+        assert_eq!(checks.len(), 1);
+        assert_eq!(checks[0].status, Status::Ok);
+
+        fs::write(
+            home.join(".zshrc"),
+            format!("# {}\n", SHELL_CONFIG_IDENTIFYING_PATTERN_START),
+        )
+        .unwrap();
+        let checks = check_shell_config(&paths_provider, SetupShell::Zsh);
+        assert!(checks[0].message.contains("incomplete"));
+
+        fs::remove_file(&config_file).unwrap();
+        let checks = check_shell_config(&paths_provider, SetupShell::Zsh);
+        assert_eq!(checks.len(), 2);
+        assert_eq!(checks[1].status, Status::Error);
+    }
+
+    #[test]
+    fn corrupted_caches() {
+        let home = create_test_temp_dir!();
+        let paths_provider = paths_provider(&home, Vec::new());
+        assert_eq!(check_cache(&paths_provider).status, Status::Ok);
+        assert_eq!(check_interpreter_index(&paths_provider).status, Status::Ok);
+
+        fs::create_dir_all(paths_provider.cache()).unwrap();
+        fs::write(
+            paths_provider.available_toolchains_cache_file(),
+            "{\"last_up",
+        )
+        .unwrap();
+        fs::write(paths_provider.interpreter_index_cache_file(), "[]").unwrap();
+        assert_eq!(check_cache(&paths_provider).status, Status::Error);
+        let check = check_interpreter_index(&paths_provider);
+        assert_eq!(check.status, Status::Error);
+        assert_eq!(check.fix.unwrap(), "Run 'hygeia rescan' to rebuild it");
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn installed_interpreters() {
+        let home = create_test_temp_dir!();
+        let paths_provider = paths_provider(&home, Vec::new());
+        assert_eq!(
+            check_installed(&paths_provider),
+            vec![Check::ok("No interpreter installed by hygeia")]
+        );
+
+        let working = Version::new(3, 8, 1);
+        let bin_dir = paths_provider.bin_dir(&working);
+        fs::create_dir_all(&bin_dir).unwrap();
+        mock_executable(
+            &bin_dir,
+            "python3.8",
+            MockedOutput {
+                out: Some("Python 3.8.1"),
+                err: None,
+            },
+        )
+        .unwrap();
+        fs::write(paths_provider.install_dir(&working).join(INFO_FILE), "").unwrap();
+
+        let mismatched = Version::new(3, 7, 5);
+        let bin_dir = paths_provider.bin_dir(&mismatched);
+        fs::create_dir_all(&bin_dir).unwrap();
+        mock_executable(
+            &bin_dir,
+            "python3",
+            MockedOutput {
+                out: Some("Python 3.7.4"),
+                err: None,
+            },
+        )
+        .unwrap();
+
+        let empty = Version::parse("3.12.0-rc1").unwrap();
+        fs::create_dir_all(paths_provider.bin_dir(&empty)).unwrap();
+
+        let checks = check_installed(&paths_provider);
+        assert_eq!(
+            checks.iter().map(|check| check.status).collect::<Vec<_>>(),
+            // Sorted by directory name
+            vec![Status::Error, Status::Error, Status::Ok]
+        );
+        assert!(checks[0].message.contains("no interpreter found"));
+        assert_eq!(
+            checks[0].fix.as_deref(),
+            Some("Reinstall it with 'hygeia install --force =3.12.0-rc1' or remove it with 'hygeia uninstall =3.12.0-rc1'")
+        );
+        assert!(checks[1].message.ends_with("is Python 3.7.4"));
+        assert_eq!(checks[2], Check::ok("Python 3.8.1 works"));
+    }
+}
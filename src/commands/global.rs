@@ -0,0 +1,75 @@
+use std::fs;
+
+use anyhow::{Context, Result};
+use thiserror::Error;
+
+use crate::{
+    config::Config,
+    toolchain::{find_installed_toolchains, ToolchainFile},
+    utils::directory::PycorsPathsProviderFromEnv,
+};
+
+#[derive(Debug, Error)]
+pub enum GlobalError {
+    #[error("No version, path or 'system' given")]
+    MissingVersion,
+}
+
+pub fn run(version: Option<String>, unset: bool) -> Result<()> {
+    let paths_provider = PycorsPathsProviderFromEnv::new();
+    let global_toolchain_file = paths_provider.global_toolchain_file();
+
+    if unset {
+        if global_toolchain_file.exists() {
+            fs::remove_file(&global_toolchain_file)
+                .with_context(|| format!("Failed to remove file {:?}", global_toolchain_file))?;
+            log::info!("Removed global toolchain file {:?}", global_toolchain_file);
+        }
+        return Ok(());
+    }
+
+    match version {
+        None => match ToolchainFile::load_global(&paths_provider)? {
+            Some(toolchain_file) => {
+                for entry in toolchain_file.entries {
+                    println!("{}", entry);
+                }
+            }
+            None => log::info!("No global toolchain set."),
+        },
+        Some(version) => {
+            log::debug!("Requested global version: {:?}", version);
+            let toolchain_file: ToolchainFile = version.parse()?;
+            if toolchain_file.entries.is_empty() {
+                return Err(GlobalError::MissingVersion.into());
+            }
+
+            let installed_toolchains = find_installed_toolchains(&paths_provider)?;
+            let include_pre_releases = Config::load(&paths_provider)?.pre_releases;
+            if toolchain_file
+                .compatible_toolchain(&installed_toolchains, None, include_pre_releases)
+                .is_none()
+            {
+                log::warn!(
+                    "No installed toolchain satisfies {:?}; it will be used once installed.",
+                    version
+                );
+            }
+
+            let content: String = toolchain_file
+                .entries
+                .iter()
+                .map(|entry| format!("{}\n", entry))
+                .collect();
+            fs::create_dir_all(paths_provider.project_home())?;
+            log::debug!(
+                "Writing global toolchain to file {:?}",
+                global_toolchain_file
+            );
+            fs::write(&global_toolchain_file, content)
+                .with_context(|| format!("Failed to write file {:?}", global_toolchain_file))?;
+        }
+    }
+
+    Ok(())
+}
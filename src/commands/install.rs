@@ -1,176 +1,667 @@
 use std::{
-    fs::File,
+    collections::VecDeque,
+    fs::{remove_file, File},
     io::{self, BufRead, Write},
-    path::PathBuf,
+    sync::{Arc, Mutex},
 };
 
-use anyhow::{anyhow, Result};
-use semver::VersionReq;
+use anyhow::{anyhow, Context, Result};
+use futures::future::join_all;
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use semver::{Version, VersionReq};
 use thiserror::Error;
 
 use crate::{
-    cache::{AvailableToolchain, AvailableToolchainsCache, ToolchainsCacheFetchOnline},
+    cache::{
+        parse_sha256_file, AvailablePyPy, AvailableToolchain, AvailableToolchainsCache,
+        StandalonePreBuilt, ToolchainsCacheFetchOnline,
+    },
+    checksum::Digest,
     commands,
+    config::Config,
     constants::{EXECUTABLE_NAME, TOOLCHAIN_FILE},
-    download::{download_to_path, HyperDownloader},
-    toolchain::{find_installed_toolchains, installed::InstalledToolchain, ToolchainFile},
-    utils::directory::PycorsPathsProviderFromEnv,
+    download::{download_to_path_with_progress_bar, download_to_string, Downloader},
+    mirrors::Mirrors,
+    os,
+    profile::BuildProfile,
+    signature::Keyring,
+    toolchain::{
+        find_installed_toolchains, implementation::Implementation, installed::InstalledToolchain,
+        ToolchainFile, ToolchainFileEntry,
+    },
+    utils::{
+        self,
+        directory::{PycorsHomeProviderTrait, PycorsPathsProvider, PycorsPathsProviderFromEnv},
+    },
 };
 
+mod dependencies;
 mod pip;
+pub mod pypy;
+mod source;
+mod standalone;
 mod unix;
 mod windows;
 
 #[derive(Debug, Error)]
 pub enum InstallError {
-    #[error("Cannot install toolchain from file when specified as path ({0:?})")]
-    ToolchainFileContainsPath(PathBuf),
+    #[error(
+        "Cannot install toolchain from file {:?}: no version listed",
+        TOOLCHAIN_FILE
+    )]
+    ToolchainFileWithoutVersion,
+    #[error("No published checksum found for {0:?}, use '--skip-verify' to install it anyway")]
+    MissingChecksum(String),
+    #[error("No pre-built interpreters are available for this platform")]
+    PreBuiltUnsupportedPlatform,
+    #[error("Failed to install Python {0}")]
+    Failed(String),
+    #[error("Installing from source is not supported on Windows")]
+    FromSourceUnsupportedPlatform,
 }
 
+/// Options shared by all the versions installed by a single command.
+#[derive(Clone)]
+struct InstallOptions {
+    release: bool,
+    skip_verify: bool,
+    #[cfg_attr(windows, allow(dead_code))]
+    skip_signature: bool,
+    prebuilt: bool,
+    profile: BuildProfile,
+    ignore_missing_deps: bool,
+    install_extra_packages: Option<commands::InstallExtraPackagesOptions>,
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn run(
     release: bool,
-    requested_version: Option<String>,
+    requested_versions: Vec<String>,
+    from_source: Option<String>,
     force_install: bool,
     install_extra_packages: &commands::InstallExtraPackagesOptions,
     select: bool,
+    skip_verify: bool,
+    skip_signature: bool,
+    pre: bool,
+    prebuilt: bool,
+    profile: Option<String>,
+    jobs: Option<usize>,
+    ignore_missing_deps: bool,
 ) -> Result<()> {
-    let requested_version_req: VersionReq = match requested_version {
-        Some(requested_version) => {
-            log::debug!("Parsing string {:?} as VersionReq", requested_version);
-            if requested_version == "latest" {
-                "*"
-            } else {
-                &requested_version
-            }
-            .parse()?
-        }
-        None => {
+    let paths_provider = PycorsPathsProviderFromEnv::new();
+    let config = Config::load(&paths_provider)?;
+    let include_pre_releases = pre || config.pre_releases;
+    let parallel_builds = jobs.unwrap_or(config.parallel_builds).max(1);
+    if let Some(profile) = &profile {
+        if prebuilt || cfg!(windows) {
             log::warn!(
-                "No version passed as argument, reading from file ({:?}).",
-                TOOLCHAIN_FILE
+                "Build profile {:?} ignored: profiles only apply when compiling from source",
+                profile
             );
-            match ToolchainFile::load()? {
-                None => Ok(selected_version_from_user_input()?),
-                Some(ToolchainFile::VersionReq(version_req)) => Ok(version_req),
-                Some(ToolchainFile::Path(path)) => {
-                    log::error!(
-                        "Cannot install toolchain from file when specified as path ({:?})",
-                        path
-                    );
-                    Err(InstallError::ToolchainFileContainsPath(path))
-                }
-            }?
         }
+    }
+    let profile = BuildProfile::from_config(&config, profile.as_deref())?;
+    let mirrors = Mirrors::load(&paths_provider)?;
+
+    let options = InstallOptions {
+        release,
+        skip_verify,
+        skip_signature,
+        prebuilt,
+        profile,
+        ignore_missing_deps,
+        // Install extras?
+        install_extra_packages: if install_extra_packages.install_extra_packages
+            || install_extra_packages.install_extra_packages_from.is_some()
+        {
+            Some(install_extra_packages.clone())
+        } else {
+            None
+        },
     };
 
-    let paths_provider = PycorsPathsProviderFromEnv::new();
-    let downloader = ToolchainsCacheFetchOnline {};
-    let cache = AvailableToolchainsCache::new(&paths_provider, &downloader)?;
+    if let Some(from_source) = from_source {
+        let version = install_from_source(
+            &from_source,
+            force_install,
+            &options,
+            &paths_provider,
+            &mirrors,
+        )?;
+        return report_installed(&[(Implementation::CPython, version)], select);
+    }
 
-    let requested_version = cache.query(&requested_version_req)?;
+    let requested_version_reqs: Vec<(Implementation, VersionReq)> = if requested_versions.is_empty()
+    {
+        log::warn!(
+            "No version passed as argument, reading from file ({:?}).",
+            TOOLCHAIN_FILE
+        );
+        vec![version_req_from_file()?]
+    } else {
+        requested_versions
+            .iter()
+            .map(|requested_version| parse_version_req(requested_version))
+            .collect::<Result<_>>()?
+    };
 
-    // Already installed? Force installation?
+    let downloader = ToolchainsCacheFetchOnline::new(mirrors.clone());
+    let mut cache = AvailableToolchainsCache::new(&paths_provider, &downloader)?;
     let installed_toolchains = find_installed_toolchains(&paths_provider)?;
-    let matching_installed_version: Option<&InstalledToolchain> =
-        installed_toolchains.iter().find(|installed_python| {
-            requested_version.version == installed_python.version
-                && installed_python.is_custom_install()
-        });
-
-    match (matching_installed_version, force_install) {
-        (Some(matching_installed_version), false) => {
-            log::warn!(
-                "Python version {} already installed!",
-                requested_version.version
-            );
-            log::warn!(
-                "Compatible version found: {} (in {})",
-                matching_installed_version.version,
-                matching_installed_version.location.display(),
-            );
+
+    let mut requested_toolchains: Vec<AvailableToolchain> = Vec::new();
+    let mut requested_pypys: Vec<AvailablePyPy> = Vec::new();
+    // All the versions to install, in the requested order
+    let mut requested_versions: Vec<(Implementation, Version)> = Vec::new();
+    for (implementation, requested_version_req) in &requested_version_reqs {
+        if *implementation == Implementation::PyPy {
+            let platform = os::pypy_platform().ok_or(pypy::PyPyError::UnsupportedPlatform)?;
+            if cache
+                .query_pypy(requested_version_req, include_pre_releases)
+                .is_err()
+            {
+                cache.fetch_pypy(platform, &paths_provider, &downloader)?;
+            }
+            let requested_pypy = cache.query_pypy(requested_version_req, include_pre_releases)?;
+            let requested_version = (Implementation::PyPy, requested_pypy.version.clone());
+            if !requested_versions.contains(&requested_version) {
+                requested_pypys.push(requested_pypy.clone());
+                requested_versions.push(requested_version);
+            }
+            continue;
+        }
+
+        let requested_toolchain = if prebuilt {
+            let target_triple =
+                os::standalone_target_triple().ok_or(InstallError::PreBuiltUnsupportedPlatform)?;
+            if cache
+                .query_pre_built(requested_version_req, include_pre_releases)
+                .is_err()
+            {
+                cache.fetch_standalone_pre_built(target_triple, &paths_provider, &downloader)?;
+            }
+            cache.query_pre_built(requested_version_req, include_pre_releases)?
+        } else {
+            let version = cache
+                .query(requested_version_req, include_pre_releases)?
+                .version
+                .clone();
+            // The digests are only needed to download it
+            let already_installed = !force_install
+                && find_custom_install(&installed_toolchains, Implementation::CPython, &version)
+                    .is_some();
+            if !skip_verify && !already_installed {
+                cache.fetch_digests(&version, &paths_provider, &downloader)?;
+            }
+            cache.query(requested_version_req, include_pre_releases)?
+        };
+        // Different requirements can resolve to the same version
+        let requested_version = (Implementation::CPython, requested_toolchain.version.clone());
+        if !requested_versions.contains(&requested_version) {
+            requested_toolchains.push(requested_toolchain.clone());
+            requested_versions.push(requested_version);
         }
-        (_, true) | (None, _) => {
+    }
+
+    // Already installed? Force installation?
+    let to_install: Vec<&AvailableToolchain> = requested_toolchains
+        .iter()
+        .filter(|requested_toolchain| {
+            force_install
+                || !is_installed(
+                    &installed_toolchains,
+                    Implementation::CPython,
+                    &requested_toolchain.version,
+                )
+        })
+        .collect();
+
+    let rt = tokio::runtime::Runtime::new()?;
+    // PyPy is only extracted, installing its releases one after the other is fast enough
+    for requested_pypy in &requested_pypys {
+        if !force_install
+            && is_installed(
+                &installed_toolchains,
+                Implementation::PyPy,
+                &requested_pypy.version,
+            )
+        {
+            continue;
+        }
+        if options.install_extra_packages.is_some() {
+            log::warn!("Extra packages are not installed in PyPy");
+        }
+        log::info!(
+            "Installing PyPy {} (from {})",
+            requested_pypy.version,
+            requested_pypy.url
+        );
+        let pb = ProgressBar::new_spinner();
+        rt.block_on(pypy::download(
+            requested_pypy,
+            options.skip_verify,
+            &mirrors,
+            &pb,
+        ))?;
+        pb.finish();
+        pypy::install_package(requested_pypy)?;
+    }
+    if requested_toolchains.len() == 1 {
+        if let Some(toolchain) = to_install.first() {
             log::info!(
                 "Installing Python {} (from {})",
-                requested_version.version,
-                requested_version.base_url
+                toolchain.version,
+                toolchain.base_url
             );
+            let pb = ProgressBar::new_spinner();
+            rt.block_on(download(toolchain, &options, &mirrors, &pb))?;
+            pb.finish();
+            build(toolchain, &options, &paths_provider, &mirrors)?;
+        }
+    } else if !to_install.is_empty() {
+        let failed = install_concurrently(
+            &rt,
+            &to_install,
+            &options,
+            &paths_provider,
+            &mirrors,
+            parallel_builds,
+        );
+        if !failed.is_empty() {
+            for version in &requested_toolchains {
+                if !failed.contains(&version.version) {
+                    println!("🐍 Python {} successfully installed!", version.version);
+                }
+            }
+            return Err(InstallError::Failed(
+                failed
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            )
+            .into());
+        }
+    }
 
-            // Install extras?
-            let install_extra_packages: Option<&commands::InstallExtraPackagesOptions> =
-                if install_extra_packages.install_extra_packages
-                    || install_extra_packages.install_extra_packages_from.is_some()
-                {
-                    Some(install_extra_packages)
-                } else {
-                    None
-                };
-
-            // Configure make make install
-            let with_progress_bar = true;
-            let rt = tokio::runtime::Runtime::new()?;
+    report_installed(&requested_versions, select)
+}
 
-            #[cfg(windows)]
-            let download_url = requested_version.windows_pre_built_url().ok_or_else(|| {
-                anyhow::anyhow!("Requested version should have a pre-built package url")
-            })?;
-            #[cfg(not(windows))]
-            let download_url = requested_version.source_url();
+/// Installation of `version` of `implementation` made by hygeia, if any.
+fn find_custom_install<'a>(
+    installed_toolchains: &'a [InstalledToolchain],
+    implementation: Implementation,
+    version: &Version,
+) -> Option<&'a InstalledToolchain> {
+    installed_toolchains.iter().find(|installed_python| {
+        implementation == installed_python.implementation
+            && *version == installed_python.version
+            && installed_python.is_custom_install()
+    })
+}
 
-            let mut downloader = HyperDownloader::new(download_url)?;
-            let download_dir = PycorsPathsProviderFromEnv::new().downloaded();
-            rt.block_on(download_to_path(
-                &mut downloader,
-                download_dir,
-                with_progress_bar,
-            ))?;
-            // FIXME: Validate downloaded package with checksum
-            // FIXME: Validate downloaded package with signature
-            install_package(release, requested_version, install_extra_packages)?;
+/// Is `version` of `implementation` already installed by hygeia? Warns if it is.
+fn is_installed(
+    installed_toolchains: &[InstalledToolchain],
+    implementation: Implementation,
+    version: &Version,
+) -> bool {
+    match find_custom_install(installed_toolchains, implementation, version) {
+        Some(matching_installed_version) => {
+            log::warn!(
+                "{} version {} already installed!",
+                implementation.label(),
+                version
+            );
+            log::warn!(
+                "Compatible version found: {} (in {})",
+                matching_installed_version.version,
+                matching_installed_version.location.display(),
+            );
+            true
         }
+        None => false,
     }
+}
 
+/// Write the installed versions to `.python-version` if required, and tell the user.
+fn report_installed(versions: &[(Implementation, Version)], select: bool) -> Result<()> {
     // Write .python-version file, if required
     if select {
         log::info!("Writing configuration to file {:?}", TOOLCHAIN_FILE);
 
-        let version = format!("={}", requested_version.version);
+        // Several versions are listed in the requested order, the first one being preferred
         let mut output = File::create(&TOOLCHAIN_FILE)?;
-        output.write_all(version.as_bytes())?;
-        output.write_all(b"\n")?;
+        for (implementation, version) in versions {
+            output.write_all(implementation.pin(version).as_bytes())?;
+            output.write_all(b"\n")?;
+        }
     }
 
-    println!(
-        "🐍 Python {} successfully installed!",
-        requested_version.version
-    );
-    if select {
+    for (implementation, version) in versions {
         println!(
+            "🐍 {} {} successfully installed!",
+            implementation.label(),
+            version
+        );
+    }
+    // Other implementations than CPython are named
+    let described: Vec<String> = versions
+        .iter()
+        .map(|(implementation, version)| match implementation {
+            Implementation::CPython => version.to_string(),
+            _ => format!("{} {}", implementation.label(), version),
+        })
+        .collect();
+    let (implementation, version) = &versions[0];
+    match (select, versions.len()) {
+        (true, 1) => println!(
             "   Version {} is selected and will be used in current directory.",
-            requested_version.version
+            described[0]
+        ),
+        (true, _) => println!(
+            "   Versions {} are selected, {} will be used in current directory.",
+            described.join(", "),
+            described[0]
+        ),
+        (false, 1) => {
+            println!(
+                "   Version {} was installed but is not selected. Select it with:",
+                described[0]
+            );
+            println!(
+                "      {} select {}",
+                EXECUTABLE_NAME,
+                implementation.pin(version)
+            );
+        }
+        (false, _) => {
+            println!("   Versions were installed but are not selected. Select one with:");
+            println!(
+                "      {} select {}",
+                EXECUTABLE_NAME,
+                implementation.pin(version)
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Build and install from a source archive, source directory or URL (see `source`).
+#[cfg_attr(windows, allow(unused_variables))]
+fn install_from_source<P>(
+    from_source: &str,
+    force_install: bool,
+    options: &InstallOptions,
+    paths_provider: &PycorsPathsProvider<P>,
+    mirrors: &Mirrors,
+) -> Result<Version>
+where
+    P: PycorsHomeProviderTrait,
+{
+    #[cfg(windows)]
+    {
+        Err(InstallError::FromSourceUnsupportedPlatform.into())
+    }
+    #[cfg(not(windows))]
+    {
+        let source_path = source::fetch(from_source, mirrors, paths_provider)?;
+        let source_tree = source::prepare(&source_path, from_source, paths_provider)?;
+        log::info!(
+            "Installing Python {} (from {})",
+            source_tree.version,
+            from_source
         );
+
+        let installed_toolchains = find_installed_toolchains(paths_provider)?;
+        if force_install
+            || !is_installed(
+                &installed_toolchains,
+                Implementation::CPython,
+                &source_tree.version,
+            )
+        {
+            unix::compile_source(
+                options.release,
+                &options.profile,
+                &source_tree,
+                options.ignore_missing_deps,
+                options.install_extra_packages.as_ref(),
+            )
+            .with_context(|| "Failed to compile source")?;
+        }
+
+        Ok(source_tree.version)
+    }
+}
+
+fn parse_version_req(requested_version: &str) -> Result<(Implementation, VersionReq)> {
+    log::debug!("Parsing string {:?} as VersionReq", requested_version);
+    Ok(Implementation::parse_requirement(
+        if requested_version == "latest" {
+            "*"
+        } else {
+            requested_version
+        },
+    )?)
+}
+
+/// Version requirement to install when none is given: the first one of
+/// `.python-version`, or else asked to the user.
+fn version_req_from_file() -> Result<(Implementation, VersionReq)> {
+    match ToolchainFile::load()? {
+        None => Ok(selected_version_from_user_input()?),
+        Some(toolchain_file) => {
+            // Install the first version listed (paths and 'system' cannot be installed)
+            toolchain_file
+                .entries
+                .into_iter()
+                .find_map(|entry| match entry {
+                    ToolchainFileEntry::VersionReq(version_req) => {
+                        Some((Implementation::CPython, version_req))
+                    }
+                    ToolchainFileEntry::Implementation(implementation, version_req) => {
+                        Some((implementation, version_req))
+                    }
+                    ToolchainFileEntry::Path(_) | ToolchainFileEntry::System => None,
+                })
+                .ok_or_else(|| {
+                    log::error!(
+                        "Cannot install toolchain from file containing only paths or 'system'"
+                    );
+                    InstallError::ToolchainFileWithoutVersion.into()
+                })
+        }
+    }
+}
+
+/// Download (and verify) the package of a version, reporting progress on `pb`.
+async fn download(
+    toolchain: &AvailableToolchain,
+    options: &InstallOptions,
+    mirrors: &Mirrors,
+    pb: &ProgressBar,
+) -> Result<()> {
+    #[cfg(windows)]
+    let (download_url, published_digest) = (
+        toolchain.windows_pre_built_url().ok_or_else(|| {
+            anyhow::anyhow!("Requested version should have a pre-built package url")
+        })?,
+        toolchain.win_pre_built_digest.clone(),
+    );
+    #[cfg(not(windows))]
+    let (download_url, published_digest) = match &toolchain.standalone_pre_built {
+        Some(pre_built) if options.prebuilt => (
+            pre_built.url.clone(),
+            if options.skip_verify {
+                None
+            } else {
+                standalone_digest(pre_built, mirrors).await?
+            },
+        ),
+        _ => (toolchain.source_url(), toolchain.source_digest.clone()),
+    };
+
+    let mut downloader = mirrors.downloader(&download_url)?;
+    let expected_digest = if options.skip_verify {
+        log::warn!("Skipping checksum verification of downloaded package");
+        None
     } else {
-        println!(
-            "   Version {} was installed but is not selected. Select it with:",
-            requested_version.version
-        );
-        println!(
-            "      {} select ={}",
-            EXECUTABLE_NAME, requested_version.version
-        );
+        match published_digest.as_ref() {
+            Some(published_digest) => Some(published_digest),
+            None => return Err(InstallError::MissingChecksum(downloader.filename()?).into()),
+        }
+    };
+    let download_dir = PycorsPathsProviderFromEnv::new().downloaded();
+    download_to_path_with_progress_bar(&mut downloader, download_dir, pb, expected_digest).await
+}
+
+/// Build (or unpack) and install a downloaded version.
+fn build<P>(
+    toolchain: &AvailableToolchain,
+    options: &InstallOptions,
+    #[cfg_attr(windows, allow(unused_variables))] paths_provider: &PycorsPathsProvider<P>,
+    #[cfg_attr(windows, allow(unused_variables))] mirrors: &Mirrors,
+) -> Result<()>
+where
+    P: PycorsHomeProviderTrait,
+{
+    if options.prebuilt {
+        standalone::install_package(toolchain, options.install_extra_packages.as_ref())
+    } else {
+        #[cfg(not(windows))]
+        if !options.skip_verify && !options.skip_signature {
+            verify_source_signature(toolchain, paths_provider, mirrors)?;
+        }
+        install_package(
+            options.release,
+            &options.profile,
+            toolchain,
+            options.ignore_missing_deps,
+            options.install_extra_packages.as_ref(),
+        )
     }
+}
 
-    Ok(())
+/// Install several versions at once, each with its own progress bar.
+///
+/// All the packages are downloaded concurrently, then up to `parallel_builds` versions
+/// are built at the same time. A failure does not stop the other installations.
+///
+/// Returns the versions that failed to install.
+fn install_concurrently<P>(
+    rt: &tokio::runtime::Runtime,
+    toolchains: &[&AvailableToolchain],
+    options: &InstallOptions,
+    paths_provider: &PycorsPathsProvider<P>,
+    mirrors: &Mirrors,
+    parallel_builds: usize,
+) -> Vec<Version>
+where
+    P: PycorsHomeProviderTrait + Clone + Send + Sync + 'static,
+{
+    let multi_progress = MultiProgress::new();
+    let bars: Vec<ProgressBar> = toolchains
+        .iter()
+        .map(|toolchain| {
+            let pb = multi_progress.add(ProgressBar::new_spinner());
+            pb.set_style(ProgressStyle::default_spinner().template("{spinner} {prefix}{msg}"));
+            pb.set_prefix(format!("Python {}: ", toolchain.version));
+            pb.set_message("Waiting...");
+            pb
+        })
+        .collect();
+    // Bars are only drawn while joined, which returns once they are all finished
+    let drawing = std::thread::spawn(move || multi_progress.join());
+
+    log::info!(
+        "Installing Python {}",
+        toolchains
+            .iter()
+            .map(|toolchain| toolchain.version.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    let downloads = rt.block_on(join_all(
+        toolchains
+            .iter()
+            .zip(&bars)
+            .map(|(toolchain, pb)| download(toolchain, options, mirrors, pb)),
+    ));
+
+    let queue: Arc<Mutex<VecDeque<_>>> = Arc::new(Mutex::new(
+        toolchains
+            .iter()
+            .zip(&bars)
+            .zip(downloads)
+            .map(|((toolchain, pb), downloaded)| ((*toolchain).clone(), pb.clone(), downloaded))
+            .collect(),
+    ));
+    let failed = Arc::new(Mutex::new(Vec::new()));
+    let shared = Arc::new((options.clone(), paths_provider.clone(), mirrors.clone()));
+    let builders: Vec<_> = (0..parallel_builds.min(toolchains.len()))
+        .map(|_| {
+            let queue = Arc::clone(&queue);
+            let failed = Arc::clone(&failed);
+            let shared = Arc::clone(&shared);
+            std::thread::spawn(move || loop {
+                let (options, paths_provider, mirrors) = &*shared;
+                let next = queue.lock().expect("Build queue poisoned").pop_front();
+                let (toolchain, pb, downloaded) = match next {
+                    Some(next) => next,
+                    None => break,
+                };
+                pb.set_style(ProgressStyle::default_spinner().template("{spinner} {prefix}{msg}"));
+                let result = downloaded.and_then(|()| {
+                    utils::with_progress_bar(&pb, || {
+                        build(&toolchain, options, paths_provider, mirrors)
+                    })
+                });
+                match result {
+                    Ok(()) => pb.finish_with_message("successfully installed"),
+                    Err(e) => {
+                        log::error!("Failed to install Python {}: {:?}", toolchain.version, e);
+                        pb.finish_with_message(format!("failed: {}", e));
+                        failed
+                            .lock()
+                            .expect("Failed versions poisoned")
+                            .push(toolchain.version.clone());
+                    }
+                }
+            })
+        })
+        .collect();
+    for builder in builders {
+        if let Err(e) = builder.join() {
+            log::error!("Build thread panicked: {:?}", e);
+        }
+    }
+    if let Err(e) = drawing.join() {
+        log::error!("Failed to draw progress bars: {:?}", e);
+    }
+
+    let failed = failed.lock().expect("Failed versions poisoned");
+    failed.clone()
 }
 
 fn install_package(
     #[cfg_attr(windows, allow(unused_variables))] release: bool,
+    #[cfg_attr(windows, allow(unused_variables))] profile: &BuildProfile,
     available_toolchain: &AvailableToolchain,
+    #[cfg_attr(windows, allow(unused_variables))] ignore_missing_deps: bool,
     install_extra_packages: Option<&commands::InstallExtraPackagesOptions>,
 ) -> Result<()> {
     #[cfg(not(target_os = "windows"))]
     {
-        unix::install_package(release, available_toolchain, install_extra_packages)?;
+        unix::install_package(
+            release,
+            profile,
+            available_toolchain,
+            ignore_missing_deps,
+            install_extra_packages,
+        )?;
     }
     #[cfg(target_os = "windows")]
     {
@@ -180,7 +671,58 @@ fn install_package(
     Ok(())
 }
 
-fn selected_version_from_user_input() -> Result<VersionReq> {
+/// Published checksum of a python-build-standalone archive.
+#[cfg_attr(windows, allow(dead_code))]
+async fn standalone_digest(
+    pre_built: &StandalonePreBuilt,
+    mirrors: &Mirrors,
+) -> Result<Option<Digest>> {
+    let digest_url = match &pre_built.digest_url {
+        Some(digest_url) => digest_url,
+        None => return Ok(None),
+    };
+    let mut downloader = mirrors.downloader(digest_url)?;
+    // Checksums are too small to bother with a progress bar
+    let with_progress_bar = false;
+    let content = download_to_string(&mut downloader, with_progress_bar).await?;
+
+    Ok(parse_sha256_file(&content, &pre_built.name))
+}
+
+/// Verify the downloaded source archive against its detached OpenPGP signature.
+///
+/// The archive is deleted if the verification fails, so it will never be built.
+#[cfg_attr(windows, allow(dead_code))]
+fn verify_source_signature<P>(
+    available_toolchain: &AvailableToolchain,
+    paths_provider: &PycorsPathsProvider<P>,
+    mirrors: &Mirrors,
+) -> Result<()>
+where
+    P: PycorsHomeProviderTrait,
+{
+    let keyring = Keyring::load(paths_provider)
+        .with_context(|| "Failed to load the keyring, use '--skip-signature' to install anyway")?;
+
+    let mut downloader = mirrors.downloader(&available_toolchain.source_signature_url())?;
+    // Signature is too small to bother with a progress bar
+    let with_progress_bar = false;
+    let rt = tokio::runtime::Runtime::new()?;
+    let armored_signature = rt.block_on(download_to_string(&mut downloader, with_progress_bar))?;
+
+    let archive = paths_provider
+        .downloaded()
+        .join(&available_toolchain.source_tar_gz);
+    if let Err(e) = keyring.verify_file(&archive, &armored_signature) {
+        log::error!("Deleting {:?}: {}", archive, e);
+        remove_file(&archive)?;
+        return Err(e);
+    }
+
+    Ok(())
+}
+
+fn selected_version_from_user_input() -> Result<(Implementation, VersionReq)> {
     log::debug!("Reading configuration from stdin");
 
     let stdin = io::stdin();
@@ -191,13 +733,13 @@ fn selected_version_from_user_input() -> Result<VersionReq> {
     };
     log::debug!("Given: {}", line);
 
-    let version: VersionReq = line.trim().parse()?;
+    let (implementation, version) = Implementation::parse_requirement(line.trim())?;
 
     if line.is_empty() {
         log::error!("Empty line given as input.");
         Err(anyhow!("Empty line provided"))
     } else {
-        log::debug!("Parsed version: {}", version);
-        Ok(version)
+        log::debug!("Parsed version: {} {}", implementation, version);
+        Ok((implementation, version))
     }
 }
@@ -0,0 +1,636 @@
+//! Checks that the tools and libraries CPython needs are available before building it.
+
+use std::{
+    env, fs,
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use thiserror::Error;
+
+use crate::Result;
+
+/// Free space needed to build and install CPython (sources, objects and installation).
+const MIN_FREE_SPACE_MB: u64 = 1024;
+
+#[derive(Debug, Error)]
+pub enum DependencyError {
+    #[error("Missing build dependencies: {0} (use '--ignore-missing-deps' to build anyway)")]
+    Missing(String),
+}
+
+/// Distributions whose package names we know, as identified by `/etc/os-release`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Distribution {
+    Debian,
+    Fedora,
+    RedHat,
+    Arch,
+    Suse,
+    Alpine,
+    MacOs,
+}
+
+impl Distribution {
+    /// Distribution of the running system.
+    pub fn detect() -> Option<Distribution> {
+        if cfg!(target_os = "macos") {
+            return Some(Distribution::MacOs);
+        }
+        let os_release = fs::read_to_string("/etc/os-release")
+            .or_else(|_| fs::read_to_string("/usr/lib/os-release"))
+            .ok()?;
+        Distribution::from_os_release(&os_release)
+    }
+
+    /// Distribution from the content of `/etc/os-release`: its `ID`, or else the first
+    /// known one of its `ID_LIKE`.
+    pub fn from_os_release(os_release: &str) -> Option<Distribution> {
+        let value = |key: &str| -> String {
+            os_release
+                .lines()
+                .filter_map(|line| line.trim().strip_prefix(key)?.strip_prefix('='))
+                .map(|value| value.trim_matches(|c| c == '"' || c == '\'').to_string())
+                .next()
+                .unwrap_or_default()
+        };
+        let id = value("ID");
+        let id_like = value("ID_LIKE");
+
+        std::iter::once(id.as_str())
+            .chain(id_like.split_whitespace())
+            .find_map(|id| match id {
+                "debian" | "ubuntu" => Some(Distribution::Debian),
+                "fedora" => Some(Distribution::Fedora),
+                "rhel" | "centos" | "amzn" => Some(Distribution::RedHat),
+                "arch" => Some(Distribution::Arch),
+                "suse" | "opensuse" => Some(Distribution::Suse),
+                "alpine" => Some(Distribution::Alpine),
+                _ => None,
+            })
+    }
+
+    /// Command installing `packages`.
+    pub fn install_command(self, packages: &[&str]) -> String {
+        let command = match self {
+            Distribution::Debian => "sudo apt-get install --no-install-recommends",
+            Distribution::Fedora => "sudo dnf install",
+            Distribution::RedHat => "sudo yum install",
+            Distribution::Arch => "sudo pacman -S --needed",
+            Distribution::Suse => "sudo zypper install",
+            Distribution::Alpine => "sudo apk add",
+            Distribution::MacOs => "brew install",
+        };
+        format!("{} {}", command, packages.join(" "))
+    }
+}
+
+/// How to find out if a dependency is available.
+#[derive(Debug)]
+enum Probe {
+    /// A tool, found if `<command> --version` runs
+    Tool(&'static str),
+    /// A library, found by `pkg-config` or else by compiling and linking a program
+    /// including its headers
+    Library {
+        pkg_config: &'static str,
+        headers: &'static [&'static str],
+        libraries: &'static [&'static str],
+    },
+}
+
+/// Something the build of CPython needs.
+#[derive(Debug)]
+pub struct Dependency {
+    pub name: &'static str,
+    /// What is missing without it
+    pub needed_for: &'static str,
+    probe: Probe,
+    /// Packages providing it, on each distribution
+    packages: &'static [(Distribution, &'static str)],
+}
+
+impl Dependency {
+    /// Package providing the dependency on `distribution`, if it needs one.
+    pub fn package(&self, distribution: Distribution) -> Option<&'static str> {
+        self.packages
+            .iter()
+            .find(|(packaged_for, _)| *packaged_for == distribution)
+            .map(|(_, package)| *package)
+    }
+}
+
+/// The C compiler, found through the `CC` environment variable like `configure` does.
+const COMPILER: &str = "cc";
+
+pub const DEPENDENCIES: &[Dependency] = &[
+    Dependency {
+        name: "C compiler",
+        needed_for: "the build",
+        probe: Probe::Tool(COMPILER),
+        packages: &[
+            (Distribution::Debian, "build-essential"),
+            (Distribution::Fedora, "gcc"),
+            (Distribution::RedHat, "gcc"),
+            (Distribution::Arch, "base-devel"),
+            (Distribution::Suse, "gcc"),
+            (Distribution::Alpine, "build-base"),
+        ],
+    },
+    Dependency {
+        name: "make",
+        needed_for: "the build",
+        probe: Probe::Tool("make"),
+        packages: &[
+            (Distribution::Debian, "make"),
+            (Distribution::Fedora, "make"),
+            (Distribution::RedHat, "make"),
+            (Distribution::Arch, "make"),
+            (Distribution::Suse, "make"),
+            (Distribution::Alpine, "make"),
+            (Distribution::MacOs, "make"),
+        ],
+    },
+    Dependency {
+        name: "zlib",
+        needed_for: "the zlib module (and pip)",
+        probe: Probe::Library {
+            pkg_config: "zlib",
+            headers: &["zlib.h"],
+            libraries: &["z"],
+        },
+        packages: &[
+            (Distribution::Debian, "zlib1g-dev"),
+            (Distribution::Fedora, "zlib-devel"),
+            (Distribution::RedHat, "zlib-devel"),
+            (Distribution::Arch, "zlib"),
+            (Distribution::Suse, "zlib-devel"),
+            (Distribution::Alpine, "zlib-dev"),
+            (Distribution::MacOs, "zlib"),
+        ],
+    },
+    Dependency {
+        name: "OpenSSL",
+        needed_for: "the ssl and hashlib modules (and pip)",
+        probe: Probe::Library {
+            pkg_config: "openssl",
+            headers: &["openssl/ssl.h"],
+            libraries: &["ssl", "crypto"],
+        },
+        packages: &[
+            (Distribution::Debian, "libssl-dev"),
+            (Distribution::Fedora, "openssl-devel"),
+            (Distribution::RedHat, "openssl-devel"),
+            (Distribution::Arch, "openssl"),
+            (Distribution::Suse, "libopenssl-devel"),
+            (Distribution::Alpine, "openssl-dev"),
+            (Distribution::MacOs, "openssl"),
+        ],
+    },
+    Dependency {
+        name: "libffi",
+        needed_for: "the ctypes module",
+        probe: Probe::Library {
+            pkg_config: "libffi",
+            headers: &["ffi.h"],
+            libraries: &["ffi"],
+        },
+        packages: &[
+            (Distribution::Debian, "libffi-dev"),
+            (Distribution::Fedora, "libffi-devel"),
+            (Distribution::RedHat, "libffi-devel"),
+            (Distribution::Arch, "libffi"),
+            (Distribution::Suse, "libffi-devel"),
+            (Distribution::Alpine, "libffi-dev"),
+            (Distribution::MacOs, "libffi"),
+        ],
+    },
+    Dependency {
+        name: "bzip2",
+        needed_for: "the bz2 module",
+        probe: Probe::Library {
+            pkg_config: "bzip2",
+            headers: &["bzlib.h"],
+            libraries: &["bz2"],
+        },
+        packages: &[
+            (Distribution::Debian, "libbz2-dev"),
+            (Distribution::Fedora, "bzip2-devel"),
+            (Distribution::RedHat, "bzip2-devel"),
+            (Distribution::Arch, "bzip2"),
+            (Distribution::Suse, "libbz2-devel"),
+            (Distribution::Alpine, "bzip2-dev"),
+            (Distribution::MacOs, "bzip2"),
+        ],
+    },
+    Dependency {
+        name: "liblzma",
+        needed_for: "the lzma module",
+        probe: Probe::Library {
+            pkg_config: "liblzma",
+            headers: &["lzma.h"],
+            libraries: &["lzma"],
+        },
+        packages: &[
+            (Distribution::Debian, "liblzma-dev"),
+            (Distribution::Fedora, "xz-devel"),
+            (Distribution::RedHat, "xz-devel"),
+            (Distribution::Arch, "xz"),
+            (Distribution::Suse, "xz-devel"),
+            (Distribution::Alpine, "xz-dev"),
+            (Distribution::MacOs, "xz"),
+        ],
+    },
+    Dependency {
+        name: "readline",
+        needed_for: "line editing in the interactive interpreter",
+        probe: Probe::Library {
+            pkg_config: "readline",
+            headers: &["stdio.h", "readline/readline.h"],
+            libraries: &["readline"],
+        },
+        packages: &[
+            (Distribution::Debian, "libreadline-dev"),
+            (Distribution::Fedora, "readline-devel"),
+            (Distribution::RedHat, "readline-devel"),
+            (Distribution::Arch, "readline"),
+            (Distribution::Suse, "readline-devel"),
+            (Distribution::Alpine, "readline-dev"),
+            (Distribution::MacOs, "readline"),
+        ],
+    },
+    Dependency {
+        name: "SQLite",
+        needed_for: "the sqlite3 module",
+        probe: Probe::Library {
+            pkg_config: "sqlite3",
+            headers: &["sqlite3.h"],
+            libraries: &["sqlite3"],
+        },
+        packages: &[
+            (Distribution::Debian, "libsqlite3-dev"),
+            (Distribution::Fedora, "sqlite-devel"),
+            (Distribution::RedHat, "sqlite-devel"),
+            (Distribution::Arch, "sqlite"),
+            (Distribution::Suse, "sqlite3-devel"),
+            (Distribution::Alpine, "sqlite-dev"),
+            (Distribution::MacOs, "sqlite"),
+        ],
+    },
+    Dependency {
+        name: "Tk",
+        needed_for: "the tkinter module",
+        probe: Probe::Library {
+            pkg_config: "tk",
+            headers: &["tk.h"],
+            libraries: &["tk"],
+        },
+        packages: &[
+            (Distribution::Debian, "tk-dev"),
+            (Distribution::Fedora, "tk-devel"),
+            (Distribution::RedHat, "tk-devel"),
+            (Distribution::Arch, "tk"),
+            (Distribution::Suse, "tk-devel"),
+            (Distribution::Alpine, "tk-dev"),
+            (Distribution::MacOs, "tcl-tk"),
+        ],
+    },
+];
+
+/// Probes dependencies with the environment of the build.
+pub struct DependencyChecker<'a> {
+    environment: &'a [(String, String)],
+    /// Flags of the test compilations not set in the environment (like the ones
+    /// given to `configure` as arguments)
+    extra_flags: Vec<String>,
+}
+
+impl<'a> DependencyChecker<'a> {
+    pub fn new(environment: &'a [(String, String)]) -> DependencyChecker<'a> {
+        DependencyChecker {
+            environment,
+            extra_flags: Vec::new(),
+        }
+    }
+
+    pub fn extra_flags(mut self, flags: Vec<String>) -> Self {
+        self.extra_flags = flags;
+        self
+    }
+
+    /// Variable of the build environment, or else of ours.
+    fn variable(&self, name: &str) -> Option<String> {
+        self.environment
+            .iter()
+            .find(|(set, _)| set == name)
+            .map(|(_, value)| value.clone())
+            .or_else(|| env::var(name).ok())
+            .filter(|value| !value.is_empty())
+    }
+
+    /// Dependencies that could not be found.
+    pub fn missing(&self) -> Vec<&'static Dependency> {
+        DEPENDENCIES
+            .iter()
+            .filter(|dependency| !self.is_available(dependency))
+            .collect()
+    }
+
+    fn is_available(&self, dependency: &Dependency) -> bool {
+        let available = match &dependency.probe {
+            // 'CC' can contain arguments, like 'gcc -m32'
+            Probe::Tool(COMPILER) => self
+                .compiler()
+                .split_whitespace()
+                .next()
+                .map_or(false, tool_runs),
+            Probe::Tool(tool) => tool_runs(tool),
+            Probe::Library {
+                pkg_config,
+                headers,
+                libraries,
+            } => pkg_config_finds(pkg_config) || self.compiles(headers, libraries),
+        };
+        log::debug!(
+            "Build dependency {}: {}",
+            dependency.name,
+            if available { "found" } else { "not found" }
+        );
+        available
+    }
+
+    fn compiler(&self) -> String {
+        self.variable("CC").unwrap_or_else(|| COMPILER.to_string())
+    }
+
+    /// Compile and link a program including `headers` with `libraries`.
+    fn compiles(&self, headers: &[&str], libraries: &[&str]) -> bool {
+        let cc = self.compiler();
+        let mut words = cc.split_whitespace();
+        let mut command = match words.next() {
+            Some(compiler) => Command::new(compiler),
+            None => return false,
+        };
+        command.args(words);
+        for flags in &["CPPFLAGS", "CFLAGS"] {
+            command.args(self.variable(flags).unwrap_or_default().split_whitespace());
+        }
+        command
+            .args(&self.extra_flags)
+            .args(["-x", "c", "-", "-o", "/dev/null"])
+            .args(
+                self.variable("LDFLAGS")
+                    .unwrap_or_default()
+                    .split_whitespace(),
+            )
+            .args(libraries.iter().map(|library| format!("-l{}", library)))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+
+        let program: String = headers
+            .iter()
+            .map(|header| format!("#include <{}>\n", header))
+            .chain(std::iter::once(
+                "int main(void) { return 0; }\n".to_string(),
+            ))
+            .collect();
+        let mut child = match command.spawn() {
+            Ok(child) => child,
+            Err(e) => {
+                log::debug!("Failed to run the compiler {:?}: {}", cc, e);
+                return false;
+            }
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            if stdin.write_all(program.as_bytes()).is_err() {
+                return false;
+            }
+        }
+        child.wait().map_or(false, |status| status.success())
+    }
+}
+
+fn tool_runs(tool: &str) -> bool {
+    Command::new(tool)
+        .arg("--version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_or(false, |status| status.success())
+}
+
+fn pkg_config_finds(name: &str) -> bool {
+    Command::new("pkg-config")
+        .arg("--exists")
+        .arg(name)
+        .stderr(Stdio::null())
+        .status()
+        .map_or(false, |status| status.success())
+}
+
+/// Free space (in MiB) of the file system containing `path`, or of its closest
+/// existing parent, as reported by `df`.
+pub fn free_space_mb(path: &Path) -> Option<u64> {
+    let existing = path.ancestors().find(|dir| dir.exists())?;
+    let output = Command::new("df").arg("-Pk").arg(existing).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_df_available_kb(&String::from_utf8_lossy(&output.stdout)).map(|kb| kb / 1024)
+}
+
+/// Available space of `df -P` output, in KiB.
+fn parse_df_available_kb(df_output: &str) -> Option<u64> {
+    // Filesystem 1024-blocks Used Available Capacity Mounted on
+    df_output
+        .lines()
+        .nth(1)?
+        .split_whitespace()
+        .nth(3)?
+        .parse()
+        .ok()
+}
+
+/// Make sure CPython can be built: the dependencies are installed and `dirs` have
+/// enough free space.
+///
+/// Problems are reported with the command installing the missing packages. Unless
+/// `ignore_missing`, they fail the installation.
+pub fn check(checker: &DependencyChecker, dirs: &[&Path], ignore_missing: bool) -> Result<()> {
+    log::info!("Checking build dependencies...");
+
+    let mut problems: Vec<String> = Vec::new();
+
+    let missing = checker.missing();
+    if !missing.is_empty() {
+        for dependency in &missing {
+            log::error!(
+                "{} not found, needed for {}",
+                dependency.name,
+                dependency.needed_for
+            );
+        }
+        match Distribution::detect() {
+            Some(distribution) => {
+                let compiler_missing = missing
+                    .iter()
+                    .any(|dependency| matches!(dependency.probe, Probe::Tool(COMPILER)));
+                if distribution == Distribution::MacOs && compiler_missing {
+                    log::error!("Install the command line tools with: xcode-select --install");
+                }
+                let packages: Vec<&str> = missing
+                    .iter()
+                    .filter_map(|dependency| dependency.package(distribution))
+                    .collect();
+                if !packages.is_empty() {
+                    log::error!(
+                        "Install them with: {}",
+                        distribution.install_command(&packages)
+                    );
+                }
+            }
+            None => {
+                log::error!("Install them with your package manager (their development packages)")
+            }
+        }
+        problems.extend(missing.iter().map(|dependency| dependency.name.to_string()));
+    }
+
+    let mut checked: Vec<PathBuf> = Vec::new();
+    for dir in dirs {
+        if checked.iter().any(|checked| checked == dir) {
+            continue;
+        }
+        checked.push(dir.to_path_buf());
+        match free_space_mb(dir) {
+            Some(free) if free < MIN_FREE_SPACE_MB => {
+                log::error!(
+                    "Only {} MiB free for {:?}, building Python needs about {} MiB",
+                    free,
+                    dir,
+                    MIN_FREE_SPACE_MB
+                );
+                problems.push(format!("disk space in {:?}", dir));
+            }
+            Some(free) => log::debug!("{} MiB free for {:?}", free, dir),
+            None => log::debug!("Could not find the free space for {:?}", dir),
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else if ignore_missing {
+        log::warn!(
+            "Building anyway, without: {} (some modules will be missing)",
+            problems.join(", ")
+        );
+        Ok(())
+    } else {
+        Err(DependencyError::Missing(problems.join(", ")).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use indoc::indoc;
+
+    #[test]
+    fn distribution_from_os_release() {
+        let debian = indoc!(
+            r#"
+            PRETTY_NAME="Debian GNU/Linux 12 (bookworm)"
+            NAME="Debian GNU/Linux"
+            VERSION_ID="12"
+            ID=debian
+            "#
+        );
+        assert_eq!(
+            Distribution::from_os_release(debian),
+            Some(Distribution::Debian)
+        );
+
+        let mint = "ID=linuxmint\nID_LIKE=\"ubuntu debian\"\n";
+        assert_eq!(
+            Distribution::from_os_release(mint),
+            Some(Distribution::Debian)
+        );
+
+        let rocky = "ID=\"rocky\"\nID_LIKE=\"rhel centos fedora\"\n";
+        assert_eq!(
+            Distribution::from_os_release(rocky),
+            Some(Distribution::RedHat)
+        );
+
+        let tumbleweed = "ID=\"opensuse-tumbleweed\"\nID_LIKE=\"opensuse suse\"\n";
+        assert_eq!(
+            Distribution::from_os_release(tumbleweed),
+            Some(Distribution::Suse)
+        );
+
+        assert_eq!(Distribution::from_os_release("ID=nixos\n"), None);
+        assert_eq!(Distribution::from_os_release(""), None);
+    }
+
+    #[test]
+    fn install_command() {
+        let missing: Vec<&str> = DEPENDENCIES
+            .iter()
+            .filter(|dependency| ["OpenSSL", "libffi"].contains(&dependency.name))
+            .filter_map(|dependency| dependency.package(Distribution::Debian))
+            .collect();
+        assert_eq!(
+            Distribution::Debian.install_command(&missing),
+            "sudo apt-get install --no-install-recommends libssl-dev libffi-dev"
+        );
+
+        // Every dependency is packaged on Linux distributions
+        for distribution in &[
+            Distribution::Debian,
+            Distribution::Fedora,
+            Distribution::RedHat,
+            Distribution::Arch,
+            Distribution::Suse,
+            Distribution::Alpine,
+        ] {
+            for dependency in DEPENDENCIES {
+                assert!(
+                    dependency.package(*distribution).is_some(),
+                    "{} on {:?}",
+                    dependency.name,
+                    distribution
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn df_output() {
+        let output = indoc!(
+            "
+            Filesystem     1024-blocks     Used Available Capacity Mounted on
+            /dev/vda         264212084 24750132  76052144      25% /
+            "
+        );
+        assert_eq!(parse_df_available_kb(output), Some(76052144));
+        assert_eq!(parse_df_available_kb("df: /nowhere: No such file\n"), None);
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn missing_compiler() {
+        let environment = vec![("CC".to_string(), "false".to_string())];
+        let missing = DependencyChecker::new(&environment).missing();
+        let names: Vec<&str> = missing.iter().map(|dependency| dependency.name).collect();
+        assert!(names.contains(&"C compiler"));
+
+        let err = check(&DependencyChecker::new(&environment), &[], false).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("Missing build dependencies: C compiler"));
+        assert!(check(&DependencyChecker::new(&environment), &[], true).is_ok());
+    }
+}
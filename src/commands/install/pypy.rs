@@ -0,0 +1,163 @@
+use std::{
+    fs::{self, File},
+    io::BufReader,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use bzip2::read::BzDecoder;
+use indicatif::ProgressBar;
+use semver::Version;
+use thiserror::Error;
+
+use crate::{
+    cache::{parse_pypy_checksum, AvailablePyPy},
+    checksum::Digest,
+    constants::EXECUTABLE_NAME,
+    download::{download_to_path_with_progress_bar, download_to_string},
+    mirrors::Mirrors,
+    toolchain::implementation::Implementation,
+    utils::{self, directory::PycorsPathsProviderFromEnv, SpinnerMessage},
+    Result,
+};
+
+use super::{standalone::extract_installation, InstallError};
+
+#[derive(Debug, Error)]
+pub enum PyPyError {
+    #[error("No pre-built PyPy releases are available for this platform")]
+    UnsupportedPlatform,
+    #[error("Unsupported archive {0:?}: expected a '.tar.bz2' file")]
+    UnsupportedFormat(String),
+    #[error("Archive {0:?} does not contain a PyPy installation")]
+    NoInstallation(PathBuf),
+}
+
+/// Download (and verify) a PyPy archive, reporting progress on `pb`.
+pub async fn download(
+    pypy: &AvailablePyPy,
+    skip_verify: bool,
+    mirrors: &Mirrors,
+    pb: &ProgressBar,
+) -> Result<()> {
+    let mut downloader = mirrors.downloader(&pypy.url)?;
+    let expected_digest = if skip_verify {
+        log::warn!("Skipping checksum verification of downloaded package");
+        None
+    } else {
+        match digest(pypy, mirrors).await? {
+            Some(digest) => Some(digest),
+            None => return Err(InstallError::MissingChecksum(pypy.name.clone()).into()),
+        }
+    };
+    let download_dir = PycorsPathsProviderFromEnv::new().downloaded();
+    download_to_path_with_progress_bar(&mut downloader, download_dir, pb, expected_digest.as_ref())
+        .await
+}
+
+/// Published checksum of a PyPy archive.
+async fn digest(pypy: &AvailablePyPy, mirrors: &Mirrors) -> Result<Option<Digest>> {
+    let mut downloader = mirrors.downloader(&mirrors.pypy_checksums)?;
+    // Checksums are too small to bother with a progress bar
+    let with_progress_bar = false;
+    let content = download_to_string(&mut downloader, with_progress_bar).await?;
+
+    Ok(parse_pypy_checksum(&content, &pypy.name))
+}
+
+/// Install a downloaded PyPy archive.
+pub fn install_package(pypy: &AvailablePyPy) -> Result<()> {
+    let version = &pypy.version;
+    // The archive contains a single directory named like itself
+    let prefix = pypy
+        .name
+        .strip_suffix(".tar.bz2")
+        .ok_or_else(|| PyPyError::UnsupportedFormat(pypy.name.clone()))?;
+    let paths_provider = PycorsPathsProviderFromEnv::new();
+    let archive_path = paths_provider.downloaded().join(pypy.filename());
+    let install_dir = paths_provider.implementation_install_dir(Implementation::PyPy, version);
+
+    let line_header = "[2/2] Extract";
+    let message = format!("{}ing {:?}...", line_header, archive_path);
+    let (tx, child) = utils::spinner_in_thread(message);
+
+    // The spinner is stopped whether the extraction succeeds or not
+    let extracted = (|| -> Result<()> {
+        let file = BufReader::new(
+            File::open(&archive_path)
+                .with_context(|| format!("Failed to open archive {:?}", archive_path))?,
+        );
+        let nb_extracted = extract_installation(BzDecoder::new(file), prefix, &install_dir)
+            .with_context(|| format!("Failed to unpack archive {:?}", archive_path))?;
+        if nb_extracted == 0 {
+            return Err(PyPyError::NoInstallation(archive_path.clone()).into());
+        }
+        Ok(())
+    })();
+
+    let message = match extracted {
+        Ok(()) => format!("{}ion of {:?} done.", line_header, archive_path),
+        Err(_) => format!("{}ion of {:?} failed.", line_header, archive_path),
+    };
+    tx.send(SpinnerMessage::Message(message))?;
+    tx.send(SpinnerMessage::Stop)?;
+    child
+        .join()
+        .map_err(|e| anyhow::anyhow!("Failed to join threads: {:?}", e))?;
+    extracted?;
+
+    // Create a file in install directory to detect if we installed it ourselves
+    utils::create_info_file(&install_dir, version)?;
+    utils::append_to_info_file(&install_dir, &format!("PyPy {}", pypy.pypy_version))?;
+
+    let bin_dir = paths_provider.implementation_bin_dir(Implementation::PyPy, version);
+    create_python_links(&bin_dir, version)?;
+    create_pypy_shims(&bin_dir)
+}
+
+/// Make sure `python`, `python3` and `python3.X` run PyPy (older releases only provide
+/// the `pypy` executables), so the shims select it like any other toolchain.
+fn create_python_links(bin_dir: &Path, version: &Version) -> Result<()> {
+    let ver_maj_min = format!("{}.{}", version.major, version.minor);
+    let ver_maj = format!("{}", version.major);
+    let pypy = [
+        format!("pypy{}", ver_maj_min),
+        format!("pypy{}", ver_maj),
+        "pypy".to_string(),
+    ]
+    .iter()
+    .map(|name| bin_dir.join(name))
+    .find(|path| path.exists())
+    .ok_or_else(|| PyPyError::NoInstallation(bin_dir.to_path_buf()))?;
+
+    for suffix in &["", ver_maj.as_str(), ver_maj_min.as_str()] {
+        let python = bin_dir.join(format!("python{}", suffix));
+        if !python.exists() {
+            utils::create_hard_link(&pypy, &python)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Create the shims of the `pypy` executables (the `python` ones are created by `setup`).
+fn create_pypy_shims(bin_dir: &Path) -> Result<()> {
+    let shim_dir = PycorsPathsProviderFromEnv::new().shims();
+    let executable_path = shim_dir.join(EXECUTABLE_NAME);
+    if !executable_path.exists() {
+        log::debug!("No shims found in {:?}, run setup to create them", shim_dir);
+        return Ok(());
+    }
+
+    for entry in fs::read_dir(bin_dir)?.flatten() {
+        let filename = entry.file_name();
+        if filename
+            .to_string_lossy()
+            .starts_with(Implementation::PyPy.name())
+        {
+            utils::create_hard_link(&executable_path, shim_dir.join(&filename))?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,353 @@
+//! Sources not listed in the python.org index: a source archive (local or to
+//! download), or a source directory like a git checkout.
+
+use std::{
+    fs::{self, File},
+    io::{BufReader, Read},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use flate2::read::GzDecoder;
+use lazy_static::lazy_static;
+use regex::Regex;
+use semver::Version;
+use tar::Archive;
+use thiserror::Error;
+use url::Url;
+use xz2::read::XzDecoder;
+
+use crate::{
+    commands::install::unix::SourceTree,
+    download::{download_to_path, Downloader},
+    mirrors::Mirrors,
+    utils::{
+        self,
+        directory::{PycorsHomeProviderTrait, PycorsPathsProvider},
+        SpinnerMessage,
+    },
+    Result,
+};
+
+#[derive(Debug, Error)]
+pub enum SourceError {
+    #[error("Source {0:?} does not exist")]
+    NotFound(PathBuf),
+    #[error("Unsupported source archive {0:?}: expected a '.tgz', '.tar.gz' or '.tar.xz' file")]
+    UnsupportedFormat(PathBuf),
+    #[error("No CPython sources found in {0:?} (missing 'Include/patchlevel.h')")]
+    NoSourceTree(PathBuf),
+    #[error("Failed to find the Python version in {0:?}")]
+    UnknownVersion(PathBuf),
+}
+
+/// Local path of `from_source`, downloading it first if it is a URL.
+///
+/// Nothing is published to verify an arbitrary archive against, so it is
+/// downloaded again every time.
+#[cfg_attr(windows, allow(dead_code))]
+pub fn fetch<P>(
+    from_source: &str,
+    mirrors: &Mirrors,
+    paths_provider: &PycorsPathsProvider<P>,
+) -> Result<PathBuf>
+where
+    P: PycorsHomeProviderTrait,
+{
+    let url = match Url::parse(from_source) {
+        Ok(url) if url.scheme() == "http" || url.scheme() == "https" => url,
+        Ok(url) if url.scheme() == "file" => {
+            let path = url
+                .to_file_path()
+                .map_err(|()| anyhow::anyhow!("Invalid file URL {}", url))?;
+            return existing(path);
+        }
+        _ => return existing(PathBuf::from(from_source)),
+    };
+
+    log::warn!("No published checksum to verify {} against", url);
+    let mut downloader = mirrors.downloader(&url)?;
+    let download_dir = paths_provider.downloaded();
+    let file_path = download_dir.join(downloader.filename()?);
+    if file_path.exists() {
+        fs::remove_file(&file_path)
+            .with_context(|| format!("Failed to delete previous download {:?}", file_path))?;
+    }
+    let with_progress_bar = true;
+    let rt = tokio::runtime::Runtime::new()?;
+    rt.block_on(download_to_path(
+        &mut downloader,
+        &download_dir,
+        with_progress_bar,
+        None,
+    ))?;
+
+    Ok(file_path)
+}
+
+fn existing(path: PathBuf) -> Result<PathBuf> {
+    if path.exists() {
+        Ok(path)
+    } else {
+        Err(SourceError::NotFound(path).into())
+    }
+}
+
+/// Sources to build from `path`, an archive (extracted first) or a directory.
+///
+/// Directories are built out of tree, leaving them untouched.
+#[cfg_attr(windows, allow(dead_code))]
+pub fn prepare<P>(
+    path: &Path,
+    origin: &str,
+    paths_provider: &PycorsPathsProvider<P>,
+) -> Result<SourceTree>
+where
+    P: PycorsHomeProviderTrait,
+{
+    let path = path
+        .canonicalize()
+        .with_context(|| format!("Failed to get the absolute path of {:?}", path))?;
+
+    let (source_dir, build_dir) = if path.is_dir() {
+        if !path.join("Include").join("patchlevel.h").exists() {
+            return Err(SourceError::NoSourceTree(path).into());
+        }
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let build_dir = paths_provider
+            .extracted()
+            .join("from-source")
+            .join(format!("{}-build", name));
+        recreate_dir(&build_dir)?;
+        (path, build_dir)
+    } else {
+        let name = archive_stem(&path)?;
+        let extract_dir = paths_provider.extracted().join("from-source").join(name);
+        recreate_dir(&extract_dir)?;
+        extract(&path, &extract_dir)?;
+        let source_dir = find_source_dir(&extract_dir).ok_or(SourceError::NoSourceTree(path))?;
+        (source_dir.clone(), source_dir)
+    };
+
+    Ok(SourceTree {
+        version: detect_version(&source_dir)?,
+        source_dir,
+        build_dir,
+        origin: Some(origin.to_string()),
+    })
+}
+
+fn recreate_dir(dir: &Path) -> Result<()> {
+    if dir.exists() {
+        log::debug!("Deleting previous directory {:?}", dir);
+        fs::remove_dir_all(dir).with_context(|| format!("Failed to delete {:?}", dir))?;
+    }
+    fs::create_dir_all(dir).with_context(|| format!("Failed to create directory {:?}", dir))?;
+    Ok(())
+}
+
+/// Name of an archive without its extension.
+fn archive_stem(path: &Path) -> Result<String> {
+    let filename = path
+        .file_name()
+        .map(|filename| filename.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    [".tar.gz", ".tgz", ".tar.xz", ".txz"]
+        .iter()
+        .find_map(|extension| filename.strip_suffix(extension))
+        .map(String::from)
+        .ok_or_else(|| SourceError::UnsupportedFormat(path.to_path_buf()).into())
+}
+
+fn extract(archive_path: &Path, extract_dir: &Path) -> Result<()> {
+    let line_header = "[2/15] Extract";
+    let message = format!("{}ing {:?}...", line_header, archive_path);
+
+    let file = BufReader::new(
+        File::open(archive_path)
+            .with_context(|| format!("Failed to open archive {:?}", archive_path))?,
+    );
+    let decoder: Box<dyn Read> = match archive_path.extension().and_then(|ext| ext.to_str()) {
+        Some("gz") | Some("tgz") => Box::new(GzDecoder::new(file)),
+        Some("xz") | Some("txz") => Box::new(XzDecoder::new(file)),
+        _ => return Err(SourceError::UnsupportedFormat(archive_path.to_path_buf()).into()),
+    };
+
+    let (tx, child) = utils::spinner_in_thread(message);
+    Archive::new(decoder)
+        .unpack(extract_dir)
+        .with_context(|| format!("Failed to unpack archive {:?}", archive_path))?;
+
+    let message = format!("{}ion of {:?} done.", line_header, archive_path);
+    tx.send(SpinnerMessage::Message(message))?;
+    tx.send(SpinnerMessage::Stop)?;
+    child
+        .join()
+        .map_err(|e| anyhow::anyhow!("Failed to join threads: {:?}", e))?;
+
+    Ok(())
+}
+
+/// The sources are either at the root of the archive, or in its single top directory.
+fn find_source_dir(extract_dir: &Path) -> Option<PathBuf> {
+    let is_source_dir = |dir: &Path| dir.join("Include").join("patchlevel.h").is_file();
+    if is_source_dir(extract_dir) {
+        return Some(extract_dir.to_path_buf());
+    }
+    fs::read_dir(extract_dir)
+        .ok()?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .find(|dir| is_source_dir(dir))
+}
+
+/// Version of the sources in `source_dir`, from `PY_VERSION` in `Include/patchlevel.h`.
+fn detect_version(source_dir: &Path) -> Result<Version> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r#"(?m)^#define\s+PY_VERSION\s+"([^"]+)""#).unwrap();
+    }
+
+    let patchlevel = source_dir.join("Include").join("patchlevel.h");
+    let content = fs::read_to_string(&patchlevel)
+        .with_context(|| format!("Failed to read {:?}", patchlevel))?;
+    RE.captures(&content)
+        .and_then(|captures| parse_py_version(&captures[1]))
+        .ok_or_else(|| SourceError::UnknownVersion(patchlevel).into())
+}
+
+/// Parse a CPython version (`3.12.0`, `3.13.0a1`, `3.12.0rc1+`) into a semantic version.
+///
+/// The `+` of development builds is dropped.
+fn parse_py_version(py_version: &str) -> Option<Version> {
+    lazy_static! {
+        static ref RE: Regex =
+            Regex::new(r"^(\d+)\.(\d+)(?:\.(\d+))?((?:a|b|rc)\d+)?\+?$").unwrap();
+    }
+
+    let captures = RE.captures(py_version.trim())?;
+    let version = format!(
+        "{}.{}.{}",
+        &captures[1],
+        &captures[2],
+        captures.get(3).map_or("0", |micro| micro.as_str())
+    );
+    let version = match captures.get(4) {
+        Some(pre) => format!("{}-{}", version, pre.as_str()),
+        None => version,
+    };
+    Version::parse(&version).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use flate2::{write::GzEncoder, Compression};
+
+    use super::*;
+    use crate::utils::directory::MockPycorsHomeProviderTrait;
+
+    use hygeia_test_helpers::create_test_temp_dir;
+
+    const PATCHLEVEL_H: &str = "/* Version parsed into components */\n\
+        #define PY_MAJOR_VERSION        3\n\
+        #define PY_MINOR_VERSION        12\n\
+        \n\
+        /* Version as a string */\n\
+        #define PY_VERSION              \"3.12.0rc1+\"\n";
+
+    fn paths_provider(project_home: PathBuf) -> PycorsPathsProvider<MockPycorsHomeProviderTrait> {
+        let mut mock = MockPycorsHomeProviderTrait::new();
+        mock.expect_project_home().return_const(Some(project_home));
+        mock.expect_home().times(0).return_const(None);
+        PycorsPathsProvider::from(mock)
+    }
+
+    fn source_tree(dir: &Path) {
+        fs::create_dir_all(dir.join("Include")).unwrap();
+        fs::write(dir.join("Include").join("patchlevel.h"), PATCHLEVEL_H).unwrap();
+        fs::write(dir.join("configure"), "#!/bin/sh\n").unwrap();
+    }
+
+    #[test]
+    fn py_versions() {
+        for (py_version, expected) in &[
+            ("3.8.1", "3.8.1"),
+            ("3.13.0a1", "3.13.0-a1"),
+            ("3.12.0b4", "3.12.0-b4"),
+            ("3.12.0rc1+", "3.12.0-rc1"),
+            ("2.7", "2.7.0"),
+        ] {
+            assert_eq!(
+                parse_py_version(py_version),
+                Some(Version::parse(expected).unwrap()),
+                "{}",
+                py_version
+            );
+        }
+        assert_eq!(parse_py_version("3.x"), None);
+    }
+
+    #[test]
+    fn prepare_directory() {
+        let home = create_test_temp_dir!();
+        let checkout = home.join("cpython");
+        source_tree(&checkout);
+
+        let origin = checkout.to_string_lossy().into_owned();
+        let paths_provider = paths_provider(home.join(".hygeia"));
+        let source_tree = prepare(&checkout, &origin, &paths_provider).unwrap();
+        assert_eq!(source_tree.version, Version::parse("3.12.0-rc1").unwrap());
+        assert_eq!(source_tree.source_dir, checkout.canonicalize().unwrap());
+        assert_eq!(
+            source_tree.build_dir,
+            paths_provider
+                .extracted()
+                .join("from-source")
+                .join("cpython-build")
+        );
+        assert!(source_tree.build_dir.is_dir());
+        assert_eq!(source_tree.origin, Some(origin));
+
+        let err = prepare(&home.join(".hygeia"), "", &paths_provider).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<SourceError>(),
+            Some(SourceError::NoSourceTree(_))
+        ));
+    }
+
+    #[test]
+    fn prepare_archive() {
+        let home = create_test_temp_dir!();
+        source_tree(&home.join("Python-3.12.0rc1"));
+        let archive_path = home.join("Python-3.12.0rc1.tgz");
+        let mut builder = tar::Builder::new(GzEncoder::new(
+            File::create(&archive_path).unwrap(),
+            Compression::default(),
+        ));
+        builder
+            .append_dir_all("Python-3.12.0rc1", home.join("Python-3.12.0rc1"))
+            .unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let paths_provider = paths_provider(home.join(".hygeia"));
+        let source_tree = prepare(&archive_path, "Python-3.12.0rc1.tgz", &paths_provider).unwrap();
+        let expected_dir = paths_provider
+            .extracted()
+            .join("from-source")
+            .join("Python-3.12.0rc1")
+            .join("Python-3.12.0rc1");
+        assert_eq!(source_tree.version, Version::parse("3.12.0-rc1").unwrap());
+        assert_eq!(source_tree.source_dir, expected_dir);
+        assert_eq!(source_tree.build_dir, expected_dir);
+        assert!(expected_dir.join("configure").is_file());
+
+        let zip_path = home.join("Python-3.12.0rc1.zip");
+        fs::write(&zip_path, "").unwrap();
+        let err = prepare(&zip_path, "", &paths_provider).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<SourceError>(),
+            Some(SourceError::UnsupportedFormat(_))
+        ));
+    }
+}
@@ -0,0 +1,209 @@
+use std::{
+    fs::{self, File},
+    io::{BufReader, Read},
+    path::{Component, Path, PathBuf},
+};
+
+use anyhow::Context;
+use flate2::read::GzDecoder;
+use tar::{Archive, EntryType};
+use thiserror::Error;
+
+use crate::{
+    cache::AvailableToolchain,
+    commands::{self, install::pip::install_extra_pip_packages},
+    utils::{self, directory::PycorsPathsProviderFromEnv, SpinnerMessage},
+    Result,
+};
+
+#[derive(Debug, Error)]
+pub enum StandaloneError {
+    #[error("Python {0} has no python-build-standalone archive")]
+    NotAvailable(semver::Version),
+    #[error("Unsupported archive {0:?}: expected a '.tar.gz' or '.tar.zst' file")]
+    UnsupportedFormat(String),
+    #[error("Archive {0:?} does not contain a Python installation")]
+    NoInstallation(PathBuf),
+}
+
+/// Install a downloaded python-build-standalone archive.
+#[cfg_attr(windows, allow(dead_code))]
+pub fn install_package(
+    available_toolchain: &AvailableToolchain,
+    install_extra_packages: Option<&commands::InstallExtraPackagesOptions>,
+) -> Result<()> {
+    let version = &available_toolchain.version;
+    let pre_built = available_toolchain
+        .standalone_pre_built
+        .as_ref()
+        .ok_or_else(|| StandaloneError::NotAvailable(version.clone()))?;
+    let paths_provider = PycorsPathsProviderFromEnv::new();
+    let archive_path = paths_provider.downloaded().join(pre_built.filename());
+    let install_dir = paths_provider.install_dir(version);
+
+    let line_header = "[2/3] Extract";
+    let message = format!("{}ing {:?}...", line_header, archive_path);
+    let (tx, child) = utils::spinner_in_thread(message);
+
+    // The spinner is stopped whether the extraction succeeds or not
+    let extracted = (|| -> Result<()> {
+        let file = BufReader::new(
+            File::open(&archive_path)
+                .with_context(|| format!("Failed to open archive {:?}", archive_path))?,
+        );
+        let decoder: Box<dyn Read> = if pre_built.name.ends_with(".tar.gz") {
+            Box::new(GzDecoder::new(file))
+        } else if pre_built.name.ends_with(".tar.zst") {
+            Box::new(zstd::Decoder::new(file)?)
+        } else {
+            return Err(StandaloneError::UnsupportedFormat(pre_built.name.clone()).into());
+        };
+        let prefix = install_prefix(&pre_built.name);
+        let nb_extracted = extract_installation(decoder, prefix, &install_dir)
+            .with_context(|| format!("Failed to unpack archive {:?}", archive_path))?;
+        if nb_extracted == 0 {
+            return Err(StandaloneError::NoInstallation(archive_path.clone()).into());
+        }
+        Ok(())
+    })();
+
+    let message = match extracted {
+        Ok(()) => format!("{}ion of {:?} done.", line_header, archive_path),
+        Err(_) => format!("{}ion of {:?} failed.", line_header, archive_path),
+    };
+    tx.send(SpinnerMessage::Message(message))?;
+    tx.send(SpinnerMessage::Stop)?;
+    child
+        .join()
+        .map_err(|e| anyhow::anyhow!("Failed to join threads: {:?}", e))?;
+    extracted?;
+
+    // Create a file in install directory to detect if we installed it ourselves
+    utils::create_info_file(&install_dir, version)?;
+
+    if let Some(install_extra_packages) = install_extra_packages {
+        install_extra_pip_packages(version, install_extra_packages)
+            .with_context(|| "Failed to install extra pip packages")?;
+    }
+
+    super::unix::create_unversioned_links(version)
+}
+
+/// Directory of the installation inside an archive: `install_only` archives contain
+/// it directly while `full` ones also contain build artifacts.
+fn install_prefix(archive_name: &str) -> &'static str {
+    if archive_name.contains("-install_only") {
+        "python"
+    } else {
+        "python/install"
+    }
+}
+
+/// Extract the entries of a tar archive found under `prefix` into `install_dir`.
+///
+/// Returns the number of entries extracted.
+pub fn extract_installation<R>(reader: R, prefix: &str, install_dir: &Path) -> Result<usize>
+where
+    R: Read,
+{
+    let mut archive = Archive::new(reader);
+    let mut nb_extracted = 0;
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+        let relative = match path.strip_prefix(prefix) {
+            Ok(relative) if !relative.as_os_str().is_empty() => relative,
+            _ => continue,
+        };
+        if relative
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_)))
+        {
+            log::warn!("Skipping suspicious archive entry {:?}", path);
+            continue;
+        }
+
+        let destination = install_dir.join(relative);
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory {:?}", parent))?;
+        }
+        if entry.header().entry_type() == EntryType::Link {
+            // Hard links are relative to the archive's root
+            let target = entry
+                .link_name()?
+                .ok_or_else(|| anyhow::anyhow!("Hard link {:?} without target", path))?
+                .into_owned();
+            utils::create_hard_link(install_dir.join(target.strip_prefix(prefix)?), &destination)?;
+        } else {
+            entry
+                .unpack(&destination)
+                .with_context(|| format!("Failed to extract {:?}", path))?;
+        }
+        nb_extracted += 1;
+    }
+
+    Ok(nb_extracted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use hygeia_test_helpers::create_test_temp_dir;
+
+    fn tar_with(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (path, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o755);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, path, content.as_bytes())
+                .unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    #[test]
+    fn extract_install_only() {
+        let install_dir = create_test_temp_dir!();
+        let archive = tar_with(&[
+            ("python/bin/python3.11", "python"),
+            ("python/lib/python3.11/os.py", "os"),
+        ]);
+
+        let prefix =
+            install_prefix("cpython-3.11.7+20240107-x86_64-unknown-linux-gnu-install_only.tar.gz");
+        let nb_extracted = extract_installation(&archive[..], prefix, &install_dir).unwrap();
+
+        assert_eq!(nb_extracted, 2);
+        assert!(install_dir.join("bin").join("python3.11").is_file());
+        assert!(install_dir
+            .join("lib")
+            .join("python3.11")
+            .join("os.py")
+            .is_file());
+    }
+
+    #[test]
+    fn extract_full() {
+        let install_dir = create_test_temp_dir!();
+        let archive = tar_with(&[
+            ("python/PYTHON.json", "{}"),
+            ("python/build/lib/libpython3.11.a", "static"),
+            ("python/install/bin/python3.11", "python"),
+        ]);
+
+        let prefix =
+            install_prefix("cpython-3.11.7+20240107-x86_64-unknown-linux-gnu-pgo+lto-full.tar.zst");
+        let nb_extracted = extract_installation(&archive[..], prefix, &install_dir).unwrap();
+
+        assert_eq!(nb_extracted, 1);
+        assert!(install_dir.join("bin").join("python3.11").is_file());
+        assert!(!install_dir.join("PYTHON.json").exists());
+        assert!(!install_dir.join("build").exists());
+        assert!(!install_dir.join("install").exists());
+    }
+}
@@ -1,5 +1,4 @@
 use std::{
-    env,
     fs::{self, File},
     io,
     path::{Path, PathBuf},
@@ -12,7 +11,16 @@ use tar::Archive;
 
 use crate::{
     cache::AvailableToolchain,
-    commands::{self, install::pip::install_extra_pip_packages},
+    check,
+    commands::{
+        self,
+        install::{
+            dependencies::{self, DependencyChecker},
+            pip::install_extra_pip_packages,
+        },
+    },
+    config::Config,
+    profile::BuildProfile,
     utils::{self, directory::PycorsPathsProviderFromEnv, SpinnerMessage},
     Result,
 };
@@ -20,15 +28,47 @@ use crate::{
 #[cfg_attr(windows, allow(dead_code))]
 pub fn install_package(
     release: bool,
+    profile: &BuildProfile,
     available_toolchain: &AvailableToolchain,
+    ignore_missing_deps: bool,
     install_extra_packages: Option<&commands::InstallExtraPackagesOptions>,
 ) -> Result<()> {
     extract_source(available_toolchain).with_context(|| "Failed to extract source")?;
-    compile_source(release, available_toolchain, install_extra_packages)
-        .with_context(|| "Failed to compile source")?;
+
+    let basename = Path::new(&available_toolchain.source_tar_gz).with_extension("");
+    let extract_dir = PycorsPathsProviderFromEnv::new()
+        .extracted()
+        .join(&basename);
+    let source_tree = SourceTree {
+        version: available_toolchain.version.clone(),
+        source_dir: extract_dir.clone(),
+        build_dir: extract_dir,
+        origin: None,
+    };
+    compile_source(
+        release,
+        profile,
+        &source_tree,
+        ignore_missing_deps,
+        install_extra_packages,
+    )
+    .with_context(|| "Failed to compile source")?;
     Ok(())
 }
 
+/// CPython sources to build.
+#[derive(Debug)]
+pub struct SourceTree {
+    pub version: Version,
+    pub source_dir: PathBuf,
+    /// Where to run `configure` and `make`: the source directory itself, or
+    /// another directory to build out of tree.
+    pub build_dir: PathBuf,
+    /// Where the sources come from, if not the python.org index, recorded in
+    /// the info file
+    pub origin: Option<String>,
+}
+
 #[cfg_attr(windows, allow(dead_code))]
 pub fn extract_source(available_toolchain: &AvailableToolchain) -> Result<()> {
     let download_dir = PycorsPathsProviderFromEnv::new().downloaded();
@@ -66,12 +106,14 @@ pub fn extract_source(available_toolchain: &AvailableToolchain) -> Result<()> {
 #[cfg_attr(windows, allow(dead_code))]
 pub fn compile_source(
     release: bool,
-    available_toolchain: &AvailableToolchain,
+    profile: &BuildProfile,
+    source_tree: &SourceTree,
+    ignore_missing_deps: bool,
     install_extra_packages: Option<&commands::InstallExtraPackagesOptions>,
 ) -> Result<()> {
     // Compilation
 
-    let version = &available_toolchain.version;
+    let version = &source_tree.version;
 
     let install_dir = PycorsPathsProviderFromEnv::new().install_dir(version);
 
@@ -84,7 +126,9 @@ pub fn compile_source(
             })?
             .to_string(),
     ];
-    if *version >= Version::new(3, 3, 0) {
+    if !profile.shared {
+        log::debug!("Building without shared library support");
+    } else if *version >= Version::new(3, 3, 0) {
         configure_args.push("--enable-shared".to_string());
     } else {
         log::warn!("Python <3.3.0 seems to have issue compiling with '--enable-shared'.");
@@ -96,7 +140,11 @@ pub fn compile_source(
             "and 'libpython{}.{}{}.{}' will not be available.",
             version.major,
             version.minor,
-            if release { "" } else { "m" },
+            if release || profile.optimizations {
+                ""
+            } else {
+                "m"
+            },
             if cfg!(target_os = "macos") {
                 "dylib"
             } else {
@@ -105,7 +153,7 @@ pub fn compile_source(
         );
         log::warn!("See https://github.com/nbigaouette/hygeia/issues/122 for more information.")
     }
-    if release {
+    if release || profile.optimizations {
         configure_args.push("--enable-optimizations".to_string());
     }
 
@@ -115,6 +163,9 @@ pub fn compile_source(
     let mut cppflags: Vec<String> = Vec::new();
     #[cfg_attr(not(macos), allow(unused_mut))]
     let mut ldflags: Vec<String> = Vec::new();
+    // Flags only given to `configure` as arguments, needed to find the dependencies
+    #[cfg_attr(not(target_os = "macos"), allow(unused_mut))]
+    let mut probe_flags: Vec<String> = Vec::new();
 
     // See https://devguide.python.org/setup/#macos-and-os-x
     #[cfg(target_os = "macos")]
@@ -124,6 +175,8 @@ pub fn compile_source(
         if *version >= Version::new(3, 7, 0) {
             let ssl_arg = format!("--with-openssl={}", openssl_prefix);
             configure_args.push(ssl_arg);
+            probe_flags.push(format!("-I{}/include", openssl_prefix));
+            probe_flags.push(format!("-L{}/lib", openssl_prefix));
         } else {
             cppflags.push(format!("-I{}/include", openssl_prefix));
             ldflags.push(format!("-L{}/lib", openssl_prefix));
@@ -144,7 +197,9 @@ pub fn compile_source(
         cppflags.push("-I/opt/X11/include".into());
     }
 
-    let environment_variables = vec![
+    configure_args.extend(profile.configure_args.iter().cloned());
+
+    let environment_variables = profile.environment(vec![
         ("CFLAGS", cflags.join(" ")),
         ("CPPFLAGS", cppflags.join(" ")),
         ("LDFLAGS", ldflags.join(" ")),
@@ -153,38 +208,50 @@ pub fn compile_source(
         // See https://stackoverflow.com/questions/37757314/problems-installing-python-3-with-enable-shared
         #[cfg(target_os = "linux")]
         ("LD_RUN_PATH", format!("{}/lib", install_dir.display())),
-    ];
+    ]);
 
-    let basename = Path::new(&available_toolchain.source_tar_gz).with_extension("");
-    let extract_dir = PycorsPathsProviderFromEnv::new()
-        .extracted()
-        .join(&basename);
+    let build_dir = &source_tree.build_dir;
+
+    dependencies::check(
+        &DependencyChecker::new(&environment_variables).extra_flags(probe_flags),
+        &[build_dir, &install_dir],
+        ignore_missing_deps,
+    )?;
+    let configure = if source_tree.source_dir == *build_dir {
+        "./configure".to_string()
+    } else {
+        source_tree
+            .source_dir
+            .join("configure")
+            .to_string_lossy()
+            .into_owned()
+    };
 
     utils::run_cmd_template(
         version,
         "[3/15] Configure",
-        "./configure",
+        &configure,
         &configure_args,
         &environment_variables,
-        &extract_dir,
+        build_dir,
     )
-    .with_context(|| format!("Failed to run command ./configure {:?}", configure_args))?;
-    utils::run_cmd_template::<&str, &PathBuf, _, _>(
+    .with_context(|| format!("Failed to run command {} {:?}", configure, configure_args))?;
+    utils::run_cmd_template(
         version,
         "[4/15] Make",
         "make",
-        &[],
+        &profile.make_targets,
         &environment_variables,
-        &extract_dir,
+        build_dir,
     )
-    .with_context(|| "Failed to run command 'make'")?;
+    .with_context(|| format!("Failed to run command 'make' {:?}", profile.make_targets))?;
     utils::run_cmd_template(
         version,
         "[5/15] Make install",
         "make",
         &["install"],
         &environment_variables,
-        &extract_dir,
+        build_dir,
     )
     .with_context(|| "Failed to run command 'make install'")?;
 
@@ -195,13 +262,31 @@ pub fn compile_source(
             version, install_dir
         )
     })?;
+    if let Some(origin) = &source_tree.origin {
+        utils::append_to_info_file(&install_dir, &format!("Built from source {}", origin))?;
+    }
+
+    // `make` succeeds even when optional extension modules could not be built
+    let paths_provider = PycorsPathsProviderFromEnv::new();
+    check::verify_installation(
+        &Config::load(&paths_provider)?.check,
+        &install_dir,
+        &paths_provider.bin_dir(version),
+        version,
+    )?;
 
     if let Some(install_extra_packages) = install_extra_packages {
         install_extra_pip_packages(version, install_extra_packages)
             .with_context(|| "Failed to install extra pip packages")?;
     }
 
-    // Create symbolic links from binaries with `3` suffix
+    create_unversioned_links(version)
+}
+
+/// Create hard links to the binaries without their version suffix (for example
+/// `python3` and `python` pointing to `python3.8`).
+#[cfg_attr(windows, allow(dead_code))]
+pub fn create_unversioned_links(version: &Version) -> Result<()> {
     let bin_dir = PycorsPathsProviderFromEnv::new().bin_dir(version);
     let basenames_to_link = &[
         "easy_install-###",
@@ -215,15 +300,13 @@ pub fn compile_source(
     ];
     let ver_maj_min = format!("{}.{}", version.major, version.minor);
     let ver_maj = format!("{}", version.major);
-    let original_current_dir =
-        env::current_dir().with_context(|| "Failed to get current working directory")?;
-    env::set_current_dir(&bin_dir)
-        .with_context(|| format!("Failed to set current working directory to {:?}", bin_dir))?;
+    // Work with full paths: several versions can be installed concurrently, so the
+    // process-wide current directory must not be changed.
     for basename_to_link in basenames_to_link {
-        let basename_src = basename_to_link.replace("###", &ver_maj_min);
+        let basename_src = bin_dir.join(basename_to_link.replace("###", &ver_maj_min));
         // Create a hard link to the file containing the version (major.minor)
-        let basename_dest = basename_to_link.replace("-###", "").replace("###", "");
-        if Path::new(&basename_dest).exists() {
+        let basename_dest = bin_dir.join(basename_to_link.replace("-###", "").replace("###", ""));
+        if basename_dest.exists() {
             fs::remove_file(&basename_dest).with_context(|| {
                 format!("Failed to delete previous hard link {:?}", basename_dest)
             })?;
@@ -244,9 +327,11 @@ pub fn compile_source(
             },
         }
         // Create a hard link to the file containing the major version only
-        let basename_dest = basename_to_link
-            .replace("-###", &ver_maj)
-            .replace("###", &ver_maj);
+        let basename_dest = bin_dir.join(
+            basename_to_link
+                .replace("-###", &ver_maj)
+                .replace("###", &ver_maj),
+        );
         utils::create_hard_link(&basename_src, &basename_dest).with_context(|| {
             format!(
                 "Failed to create hard link {:?} pointing to {:?}",
@@ -255,16 +340,5 @@ pub fn compile_source(
         })?;
     }
 
-    log::debug!(
-        "Changing back current directory to {:?}",
-        original_current_dir
-    );
-    env::set_current_dir(&original_current_dir).with_context(|| {
-        format!(
-            "Failed to set current working directory back to original {:?}",
-            original_current_dir
-        )
-    })?;
-
     Ok(())
 }
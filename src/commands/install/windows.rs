@@ -8,13 +8,12 @@ use anyhow::Context;
 use crate::{
     cache::AvailableToolchain,
     commands::{self, install::pip::install_extra_pip_packages},
-    download::{download_to_path, HyperDownloader},
+    download::download_to_path,
+    mirrors::Mirrors,
     utils::{self, directory::PycorsPathsProviderFromEnv},
     Result,
 };
 
-const GET_PIP_URL: &str = "https://bootstrap.pypa.io/get-pip.py";
-
 #[cfg_attr(not(windows), allow(dead_code))]
 pub fn install_package(
     available_toolchain: &AvailableToolchain,
@@ -89,16 +88,19 @@ pub fn install_package(
     let python_exe = install_dir.join("python.exe");
 
     // Install pip
-    let cache_dir = PycorsPathsProviderFromEnv::new().cache();
+    let paths_provider = PycorsPathsProviderFromEnv::new();
+    let cache_dir = paths_provider.cache();
     let get_pip_py = cache_dir.join("get-pip.py");
     // File is too small to bother for a progress bar
     let with_progress_bar = false;
-    let mut downloader = HyperDownloader::new(GET_PIP_URL)?;
+    let mirrors = Mirrors::load(&paths_provider)?;
+    let mut downloader = mirrors.downloader(&mirrors.get_pip)?;
     let rt = tokio::runtime::Runtime::new()?;
     rt.block_on(download_to_path(
         &mut downloader,
         &cache_dir,
         with_progress_bar,
+        None,
     ))?;
 
     let env_variables: [(&str, &str); 0] = [];
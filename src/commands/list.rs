@@ -1,31 +1,49 @@
 use std::path::PathBuf;
 
-use prettytable::{cell, row, Cell, Row, Table};
+use prettytable::{cell, format::Alignment, row, Attr, Cell, Row, Table};
 use semver::VersionReq;
 
 use crate::{
+    cache::{
+        AvailablePyPy, AvailableToolchain, AvailableToolchainsCache, ToolchainsCacheFetchOnline,
+    },
+    commands::install::pypy::PyPyError,
+    config::Config,
     constants::EXECUTABLE_NAME,
+    mirrors::Mirrors,
+    os,
     toolchain::{
-        find_installed_toolchains, installed::InstalledToolchain, is_a_custom_install,
-        SelectedToolchain, ToolchainFile,
+        find_installed_toolchains, implementation::Implementation, installed::InstalledToolchain,
+        is_a_custom_install, SelectedToolchain, ToolchainFile,
     },
     utils::directory::PycorsPathsProviderFromEnv,
+    venv::Venv,
     Result,
 };
 
-pub fn run() -> Result<()> {
+pub fn run(available: bool, version: Option<String>, pre: bool) -> Result<()> {
+    if available {
+        return run_available(version, pre);
+    }
+
     let paths_provider = PycorsPathsProviderFromEnv::new();
     let installed_toolchains: Vec<InstalledToolchain> = find_installed_toolchains(&paths_provider)?;
+    let include_pre_releases = Config::load(&paths_provider)?.pre_releases;
 
-    let mut toolchains_table = ToolChainTable::new(&installed_toolchains);
+    let venvs = Venv::list(&paths_provider)?;
+
+    let mut toolchains_table = ToolChainTable::new(&installed_toolchains, &venvs);
 
     if let Some(toolchain_file) = ToolchainFile::load()? {
-        let selected_toolchain =
-            SelectedToolchain::from_toolchain_file(&toolchain_file, &installed_toolchains);
+        let selected_toolchain = SelectedToolchain::from_toolchain_file(
+            &toolchain_file,
+            &installed_toolchains,
+            include_pre_releases,
+        );
 
         // Information was loaded from .python-version. Mark the relevant installed toolchain
         // as being active. If not found, add it to the list as not-installed.
-        toolchains_table.append(&selected_toolchain, true);
+        toolchains_table.append(&selected_toolchain, true, &venvs);
     }
 
     toolchains_table.printstd();
@@ -33,32 +51,85 @@ pub fn run() -> Result<()> {
     Ok(())
 }
 
+fn run_available(version: Option<String>, pre: bool) -> Result<()> {
+    let (implementation, version_req): (Implementation, VersionReq) = match version {
+        Some(version) => {
+            log::debug!("Parsing string {:?} as VersionReq", version);
+            Implementation::parse_requirement(if version == "latest" { "*" } else { &version })?
+        }
+        None => (Implementation::CPython, VersionReq::STAR),
+    };
+
+    let paths_provider = PycorsPathsProviderFromEnv::new();
+    let include_pre_releases = pre || Config::load(&paths_provider)?.pre_releases;
+    let downloader = ToolchainsCacheFetchOnline::new(Mirrors::load(&paths_provider)?);
+    let mut cache = AvailableToolchainsCache::new(&paths_provider, &downloader)?;
+    let installed_toolchains: Vec<InstalledToolchain> = find_installed_toolchains(&paths_provider)?;
+
+    if implementation == Implementation::PyPy {
+        let platform = os::pypy_platform().ok_or(PyPyError::UnsupportedPlatform)?;
+        if cache
+            .query_all_pypy(&version_req, include_pre_releases)
+            .is_empty()
+        {
+            cache.fetch_pypy(platform, &paths_provider, &downloader)?;
+        }
+        let available_pypys = cache.query_all_pypy(&version_req, include_pre_releases);
+        AvailablePyPyTable::new(&available_pypys, &installed_toolchains).printstd();
+        return Ok(());
+    }
+
+    let available_toolchains = cache.query_all(&version_req, include_pre_releases);
+
+    AvailableToolChainTable::new(&available_toolchains, &installed_toolchains).printstd();
+
+    Ok(())
+}
+
 struct ToolChainTableLine {
     active: bool,
+    implementation: Implementation,
     version: Option<VersionReq>,
     custom_install: bool,
     location: Option<PathBuf>,
     installed: bool,
+    /// Number of virtual environments created from this toolchain
+    venvs: usize,
+}
+
+/// Number of virtual environments created from the installed toolchain.
+fn dependent_venvs(toolchain: &InstalledToolchain, venvs: &[Venv]) -> usize {
+    venvs
+        .iter()
+        .filter(|venv| venv.is_based_on(&toolchain.location))
+        // Different versions can share the same location (for example '/usr/bin')
+        .filter(|venv| match &venv.version {
+            Some(version) => *version == toolchain.version,
+            None => true,
+        })
+        .count()
 }
 
 struct ToolChainTable(Vec<ToolChainTableLine>);
 
 impl ToolChainTable {
-    fn new(installed_toolchains: &[InstalledToolchain]) -> ToolChainTable {
+    fn new(installed_toolchains: &[InstalledToolchain], venvs: &[Venv]) -> ToolChainTable {
         let list: Vec<ToolChainTableLine> = installed_toolchains
             .iter()
             .map(|t| ToolChainTableLine {
                 active: false,
+                implementation: t.implementation,
                 version: Some(format!("={}", t.version).parse().unwrap()),
                 custom_install: t.is_custom_install(),
                 location: Some(t.location.clone()),
                 installed: true,
+                venvs: dependent_venvs(t, venvs),
             })
             .collect();
         ToolChainTable(list)
     }
 
-    fn append(&mut self, toolchain: &SelectedToolchain, active: bool) {
+    fn append(&mut self, toolchain: &SelectedToolchain, active: bool, venvs: &[Venv]) {
         match self.0.iter_mut().find(|t| match (&t.version, &t.location) {
             (None, _) => false,
             (_, None) => false,
@@ -75,13 +146,16 @@ impl ToolChainTable {
                 let line: ToolChainTableLine = match toolchain {
                     SelectedToolchain::InstalledToolchain(t) => ToolChainTableLine {
                         active,
+                        implementation: t.implementation,
                         version: Some(format!("={}", t.version).parse().unwrap()),
                         custom_install: is_a_custom_install(&t.location),
                         location: Some(t.location.clone()),
                         installed: true,
+                        venvs: dependent_venvs(t, venvs),
                     },
                     SelectedToolchain::NotInstalledToolchain(t) => ToolChainTableLine {
                         active,
+                        implementation: t.implementation,
                         version: t.version.clone(),
                         custom_install: t
                             .location
@@ -90,6 +164,7 @@ impl ToolChainTable {
                             .unwrap_or(false),
                         location: t.location.clone(),
                         installed: false,
+                        venvs: 0,
                     },
                 };
                 // Insert at the top of the list
@@ -101,67 +176,213 @@ impl ToolChainTable {
 
 impl ToolChainTable {
     fn printstd(&self) {
-        let mut table = Table::new();
-        // ╭──────────┬───────────┬───────────────────────┬────────────╮
-        // │ Active   │ Version   │ Installed by hygeia   │ Location   │
-        // ╰──────────┴───────────┴───────────────────────┴────────────╯
-        // Header
-        table.add_row(row![
+        // ╭──────────┬───────────┬───────────────────────┬─────────┬────────────╮
+        // │ Active   │ Version   │ Installed by hygeia   │ Venvs   │ Location   │
+        // ╰──────────┴───────────┴───────────────────────┴─────────┴────────────╯
+        let header = row![
             "Active",
             "Version",
             &format!("Installed by {}", EXECUTABLE_NAME),
+            "Venvs",
             "Location"
-        ]);
+        ];
 
-        let green = prettytable::Attr::ForegroundColor(prettytable::color::GREEN);
-        let red = prettytable::Attr::ForegroundColor(prettytable::color::RED);
-        let bold = prettytable::Attr::Bold;
+        let green = Attr::ForegroundColor(prettytable::color::GREEN);
+        let red = Attr::ForegroundColor(prettytable::color::RED);
+        let bold = Attr::Bold;
 
-        self.0.iter().for_each(|t: &ToolChainTableLine| {
+        let lines = self.0.iter().map(|t: &ToolChainTableLine| {
             let (active_char, line_color, line_style) = match (t.active, t.installed) {
                 (true, true) => ("✓", Some(green), Some(bold)),
                 (true, false) => ("✗", Some(red), Some(bold)),
                 (false, _) => ("", None, None),
             };
             let custom_char = if t.custom_install { "✓" } else { "" };
+            let venvs = if t.venvs > 0 {
+                t.venvs.to_string()
+            } else {
+                String::new()
+            };
 
-            let mut col_1 = Cell::new_align(active_char, prettytable::format::Alignment::CENTER);
-
-            let mut col_2 = Cell::new_align(
-                &t.version
-                    .as_ref()
-                    .map(|t| format!("{}", t).replace('=', ""))
-                    .unwrap_or_default(),
-                prettytable::format::Alignment::CENTER,
-            );
-
-            let mut col_3 = Cell::new_align(custom_char, prettytable::format::Alignment::CENTER);
-
-            let mut col_4 = Cell::new_align(
-                &t.location
-                    .as_ref()
-                    .map(|t| format!("{}", t.display()))
-                    .unwrap_or_default(),
-                prettytable::format::Alignment::LEFT,
-            );
-
-            if let Some(c) = line_color {
-                col_1.style(c);
-                col_2.style(c);
-                col_3.style(c);
-                col_4.style(c);
+            StyledLine {
+                cells: vec![
+                    (active_char.to_string(), Alignment::CENTER),
+                    (
+                        t.version
+                            .as_ref()
+                            .map(|version| match t.implementation {
+                                Implementation::CPython => format!("{}", version).replace('=', ""),
+                                implementation => format!(
+                                    "{} {}",
+                                    implementation.label(),
+                                    version.to_string().replace('=', "")
+                                ),
+                            })
+                            .unwrap_or_default(),
+                        Alignment::CENTER,
+                    ),
+                    (custom_char.to_string(), Alignment::CENTER),
+                    (venvs, Alignment::CENTER),
+                    (
+                        t.location
+                            .as_ref()
+                            .map(|t| format!("{}", t.display()))
+                            .unwrap_or_default(),
+                        Alignment::LEFT,
+                    ),
+                ],
+                color: line_color,
+                style: line_style,
             }
+        });
+
+        printstd(header, lines);
+    }
+}
 
-            if let Some(c) = line_style {
-                col_1.style(c);
-                col_2.style(c);
-                col_3.style(c);
-                col_4.style(c);
+struct AvailableToolChainTableLine {
+    version: String,
+    windows_pre_built: bool,
+    installed: bool,
+}
+
+struct AvailableToolChainTable(Vec<AvailableToolChainTableLine>);
+
+impl AvailableToolChainTable {
+    fn new(
+        available_toolchains: &[&AvailableToolchain],
+        installed_toolchains: &[InstalledToolchain],
+    ) -> AvailableToolChainTable {
+        let list: Vec<AvailableToolChainTableLine> = available_toolchains
+            .iter()
+            .map(|t| AvailableToolChainTableLine {
+                version: format!("{}", t.version),
+                windows_pre_built: t.win_pre_built.is_some(),
+                // Only our own installations: a PyPy or an interpreter found in `PATH`
+                // of the same version does not make this one installed.
+                installed: installed_toolchains.iter().any(|installed| {
+                    installed.implementation == Implementation::CPython
+                        && installed.version == t.version
+                        && installed.is_custom_install()
+                }),
+            })
+            .collect();
+        AvailableToolChainTable(list)
+    }
+
+    fn printstd(&self) {
+        // ╭───────────┬─────────────────────┬─────────────╮
+        // │ Version   │ Windows pre-built   │ Installed   │
+        // ╰───────────┴─────────────────────┴─────────────╯
+        let header = row!["Version", "Windows pre-built", "Installed"];
+
+        let green = Attr::ForegroundColor(prettytable::color::GREEN);
+
+        let lines = self.0.iter().map(|t: &AvailableToolChainTableLine| {
+            let windows_pre_built_char = if t.windows_pre_built { "✓" } else { "" };
+            let installed_char = if t.installed { "✓" } else { "" };
+
+            StyledLine {
+                cells: vec![
+                    (t.version.clone(), Alignment::CENTER),
+                    (windows_pre_built_char.to_string(), Alignment::CENTER),
+                    (installed_char.to_string(), Alignment::CENTER),
+                ],
+                color: if t.installed { Some(green) } else { None },
+                style: None,
             }
+        });
+
+        printstd(header, lines);
+    }
+}
 
-            table.add_row(Row::new(vec![col_1, col_2, col_3, col_4]));
+struct AvailablePyPyTableLine {
+    version: String,
+    pypy_version: String,
+    installed: bool,
+}
+
+struct AvailablePyPyTable(Vec<AvailablePyPyTableLine>);
+
+impl AvailablePyPyTable {
+    fn new(
+        available_pypys: &[&AvailablePyPy],
+        installed_toolchains: &[InstalledToolchain],
+    ) -> AvailablePyPyTable {
+        let list: Vec<AvailablePyPyTableLine> = available_pypys
+            .iter()
+            .map(|t| AvailablePyPyTableLine {
+                version: format!("{}", t.version),
+                pypy_version: format!("{}", t.pypy_version),
+                installed: installed_toolchains.iter().any(|installed| {
+                    installed.implementation == Implementation::PyPy
+                        && installed.version == t.version
+                        && installed.is_custom_install()
+                }),
+            })
+            .collect();
+        AvailablePyPyTable(list)
+    }
+
+    fn printstd(&self) {
+        // ╭───────────┬──────────┬─────────────╮
+        // │ Version   │ PyPy     │ Installed   │
+        // ╰───────────┴──────────┴─────────────╯
+        let header = row!["Version", "PyPy", "Installed"];
+
+        let green = Attr::ForegroundColor(prettytable::color::GREEN);
+
+        let lines = self.0.iter().map(|t: &AvailablePyPyTableLine| {
+            let installed_char = if t.installed { "✓" } else { "" };
+
+            StyledLine {
+                cells: vec![
+                    (t.version.clone(), Alignment::CENTER),
+                    (t.pypy_version.clone(), Alignment::CENTER),
+                    (installed_char.to_string(), Alignment::CENTER),
+                ],
+                color: if t.installed { Some(green) } else { None },
+                style: None,
+            }
         });
 
-        table.printstd();
+        printstd(header, lines);
     }
 }
+
+/// Line of a table where all cells share the same color and style.
+struct StyledLine {
+    cells: Vec<(String, Alignment)>,
+    color: Option<Attr>,
+    style: Option<Attr>,
+}
+
+fn printstd<I>(header: Row, lines: I)
+where
+    I: Iterator<Item = StyledLine>,
+{
+    let mut table = Table::new();
+    table.add_row(header);
+
+    lines.for_each(|line: StyledLine| {
+        let cells: Vec<Cell> = line
+            .cells
+            .iter()
+            .map(|(content, alignment)| {
+                let mut cell = Cell::new_align(content, *alignment);
+                if let Some(c) = line.color {
+                    cell.style(c);
+                }
+                if let Some(c) = line.style {
+                    cell.style(c);
+                }
+                cell
+            })
+            .collect();
+
+        table.add_row(Row::new(cells));
+    });
+
+    table.printstd();
+}
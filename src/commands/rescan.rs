@@ -0,0 +1,31 @@
+use std::fs;
+
+use anyhow::{Context, Result};
+
+use crate::{toolchain::find_installed_toolchains, utils::directory::PycorsPathsProviderFromEnv};
+
+pub fn run() -> Result<()> {
+    let paths_provider = PycorsPathsProviderFromEnv::new();
+
+    let index_file = paths_provider.interpreter_index_cache_file();
+    if index_file.exists() {
+        log::debug!("Removing interpreter index {:?}", index_file);
+        fs::remove_file(&index_file)
+            .with_context(|| format!("Failed to remove file {:?}", index_file))?;
+    }
+
+    // Finding the toolchains probes all interpreters in PATH and saves a new index.
+    let system_toolchains: Vec<_> = find_installed_toolchains(&paths_provider)?
+        .into_iter()
+        .filter(|toolchain| !toolchain.is_custom_install())
+        .collect();
+    for toolchain in &system_toolchains {
+        log::info!("Found {}", toolchain);
+    }
+    println!(
+        "Found {} Python interpreter(s) in PATH",
+        system_toolchains.len()
+    );
+
+    Ok(())
+}
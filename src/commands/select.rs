@@ -2,10 +2,13 @@ use anyhow::{anyhow, Result};
 
 use crate::{
     commands,
+    config::Config,
     toolchain::{
-        find_installed_toolchains, installed::InstalledToolchain, selected::VersionOrPath,
+        find_compatible_toolchain_of, find_installed_toolchains, installed::InstalledToolchain,
+        selected::VersionOrPath,
     },
     utils::{self, directory::PycorsPathsProviderFromEnv},
+    venv::Venv,
 };
 
 pub fn run(requested_version_or_path: commands::VersionOrPath) -> Result<()> {
@@ -13,12 +16,42 @@ pub fn run(requested_version_or_path: commands::VersionOrPath) -> Result<()> {
 
     let paths_provider = PycorsPathsProviderFromEnv::new();
     let installed_toolchains = find_installed_toolchains(&paths_provider)?;
+    let include_pre_releases =
+        requested_version_or_path.pre || Config::load(&paths_provider)?.pre_releases;
 
-    let version_or_path: VersionOrPath = requested_version_or_path.version_or_path.parse()?;
+    let version_or_path: VersionOrPath = match (
+        &requested_version_or_path.venv,
+        &requested_version_or_path.version_or_path,
+    ) {
+        (Some(venv_name), _) => {
+            // A virtual environment is selected through its executables' directory
+            VersionOrPath::Path(Venv::load(venv_name, &paths_provider)?.bin_dir())
+        }
+        (None, Some(version_or_path)) => version_or_path.parse()?,
+        (None, None) => unreachable!("structopt requires a version, a path or a venv"),
+    };
 
     let python_to_use: InstalledToolchain = match version_or_path {
         VersionOrPath::VersionReq(version_req) => {
-            match utils::active_version(&version_req, &installed_toolchains) {
+            match utils::active_version(&version_req, &installed_toolchains, include_pre_releases) {
+                Some(python_to_use) => {
+                    // Write to `.python-version`
+                    python_to_use.save_version()?;
+
+                    python_to_use.clone()
+                }
+                None => {
+                    return Err(anyhow!("Python version {} not found!", version_req));
+                }
+            }
+        }
+        VersionOrPath::Implementation(implementation, version_req) => {
+            match find_compatible_toolchain_of(
+                implementation,
+                &version_req,
+                &installed_toolchains,
+                include_pre_releases,
+            ) {
                 Some(python_to_use) => {
                     // Write to `.python-version`
                     python_to_use.save_version()?;
@@ -27,8 +60,9 @@ pub fn run(requested_version_or_path: commands::VersionOrPath) -> Result<()> {
                 }
                 None => {
                     return Err(anyhow!(
-                        "Python version {} not found!",
-                        requested_version_or_path.version_or_path
+                        "{} version {} not found!",
+                        implementation.label(),
+                        version_req
                     ));
                 }
             }
@@ -81,30 +81,9 @@ pub fn run(shell: Shell) -> Result<()> {
     log::debug!("Copying {:?} into {:?}...", copy_from, copy_to);
     utils::copy_file(&copy_from, &copy_to)?;
 
-    #[cfg(windows)]
-    let bin_extension = ".exe";
-    #[cfg(not(windows))]
-    let bin_extension = "";
-
     // Once the shim is in place, create hard links to it.
-    let hardlinks_version_suffix = &[
-        format!("python###{}", bin_extension),
-        format!("idle###{}", bin_extension),
-        format!("pip###{}", bin_extension),
-        format!("pydoc###{}", bin_extension),
-        // Internals
-        format!("python###-config{}", bin_extension),
-        format!("python###dm-config{}", bin_extension),
-        // Extras
-        format!("pipenv###{}", bin_extension),
-        format!("poetry###{}", bin_extension),
-        format!("pytest###{}", bin_extension),
-    ];
-    let hardlinks_dash_version_suffix = &[
-        format!("2to3###{}", bin_extension),
-        format!("easy_install###{}", bin_extension),
-        format!("pyvenv###{}", bin_extension),
-    ];
+    let hardlinks_version_suffix = &hardlinks_version_suffix();
+    let hardlinks_dash_version_suffix = &hardlinks_dash_version_suffix();
 
     // Create simple hardlinks: `EXECUTABLE_NAME` --> `bin`
     utils::create_hard_links(&copy_to, hardlinks_version_suffix, &shims_dir, "")?;
@@ -159,3 +138,57 @@ pub fn run(shell: Shell) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(windows)]
+const BIN_EXTENSION: &str = ".exe";
+#[cfg(not(windows))]
+const BIN_EXTENSION: &str = "";
+
+/// Shims created by `setup`, where `###` is replaced by nothing, `2` or `3`.
+fn hardlinks_version_suffix() -> Vec<String> {
+    vec![
+        format!("python###{}", BIN_EXTENSION),
+        format!("idle###{}", BIN_EXTENSION),
+        format!("pip###{}", BIN_EXTENSION),
+        format!("pydoc###{}", BIN_EXTENSION),
+        // Internals
+        format!("python###-config{}", BIN_EXTENSION),
+        format!("python###dm-config{}", BIN_EXTENSION),
+        // Extras
+        format!("pipenv###{}", BIN_EXTENSION),
+        format!("poetry###{}", BIN_EXTENSION),
+        format!("pytest###{}", BIN_EXTENSION),
+    ]
+}
+
+/// Shims created by `setup`, where `###` is replaced by nothing, `-2` or `-3`.
+fn hardlinks_dash_version_suffix() -> Vec<String> {
+    vec![
+        format!("2to3###{}", BIN_EXTENSION),
+        format!("easy_install###{}", BIN_EXTENSION),
+        format!("pyvenv###{}", BIN_EXTENSION),
+    ]
+}
+
+/// Filenames of all the shims created by `setup`.
+///
+/// These are always kept in the shims directory, even when no installed
+/// toolchain provides them.
+pub fn default_shims() -> Vec<String> {
+    let mut shims = Vec::new();
+    for replace_sharps_with in &["", "2", "3"] {
+        shims.extend(
+            hardlinks_version_suffix()
+                .iter()
+                .map(|name| name.replace("###", replace_sharps_with)),
+        );
+    }
+    for replace_sharps_with in &["", "-2", "-3"] {
+        shims.extend(
+            hardlinks_dash_version_suffix()
+                .iter()
+                .map(|name| name.replace("###", replace_sharps_with)),
+        );
+    }
+    shims
+}
@@ -0,0 +1,166 @@
+use std::{collections::HashSet, fs, path::PathBuf};
+
+use anyhow::{Context, Result};
+use semver::{Version, VersionReq};
+use thiserror::Error;
+
+use crate::{
+    commands::setup::default_shims,
+    config::Config,
+    constants::{EXECUTABLE_NAME, TOOLCHAIN_FILE},
+    os,
+    toolchain::{
+        find_compatible_toolchain_of, find_installed_toolchains, implementation::Implementation,
+        installed::InstalledToolchain, SelectedToolchain, ToolchainFile,
+    },
+    utils::directory::PycorsPathsProviderFromEnv,
+    venv::Venv,
+};
+
+#[derive(Debug, Error)]
+pub enum UninstallError {
+    #[error("Python version {0} not found!")]
+    NotInstalled(VersionReq),
+    #[error(
+        "Python {0} ({1:?}) was not installed by {}, refusing to uninstall it",
+        EXECUTABLE_NAME
+    )]
+    NotACustomInstall(Version, PathBuf),
+}
+
+pub fn run(requested_version: String) -> Result<()> {
+    log::debug!("Parsing string {:?} as VersionReq", requested_version);
+    let (implementation, version_req): (Implementation, VersionReq) =
+        Implementation::parse_requirement(if requested_version == "latest" {
+            "*"
+        } else {
+            &requested_version
+        })?;
+
+    let paths_provider = PycorsPathsProviderFromEnv::new();
+    let installed_toolchains = find_installed_toolchains(&paths_provider)?;
+    let include_pre_releases = Config::load(&paths_provider)?.pre_releases;
+
+    // Only toolchains we installed ourselves can be uninstalled.
+    let custom_toolchains: Vec<InstalledToolchain> = installed_toolchains
+        .iter()
+        .filter(|t| t.is_custom_install())
+        .cloned()
+        .collect();
+    let to_uninstall: InstalledToolchain = match find_compatible_toolchain_of(
+        implementation,
+        &version_req,
+        &custom_toolchains,
+        include_pre_releases,
+    ) {
+        Some(toolchain) => toolchain.clone(),
+        None => match find_compatible_toolchain_of(
+            implementation,
+            &version_req,
+            &installed_toolchains,
+            include_pre_releases,
+        ) {
+            Some(system_toolchain) => {
+                return Err(UninstallError::NotACustomInstall(
+                    system_toolchain.version.clone(),
+                    system_toolchain.location.clone(),
+                )
+                .into())
+            }
+            None => return Err(UninstallError::NotInstalled(version_req).into()),
+        },
+    };
+
+    if let Some(toolchain_file) = ToolchainFile::load()? {
+        if let SelectedToolchain::InstalledToolchain(selected) =
+            SelectedToolchain::from_toolchain_file(
+                &toolchain_file,
+                &installed_toolchains,
+                include_pre_releases,
+            )
+        {
+            if selected == to_uninstall {
+                log::warn!(
+                    "{} {} is selected by {:?}; it will not be available anymore.",
+                    to_uninstall.implementation.label(),
+                    to_uninstall.version,
+                    TOOLCHAIN_FILE
+                );
+            }
+        }
+    }
+
+    let install_dir = paths_provider
+        .implementation_install_dir(to_uninstall.implementation, &to_uninstall.version);
+    for venv in Venv::list(&paths_provider)? {
+        if venv.is_based_on(&install_dir) {
+            log::warn!(
+                "Virtual environment {:?} is based on Python {}; it will be broken.",
+                venv.name,
+                to_uninstall.version
+            );
+        }
+    }
+
+    // Collect the binaries provided by the toolchain before deleting it.
+    let provided_binaries = binaries_in(&os::paths_to_prepends(
+        to_uninstall.implementation,
+        &to_uninstall.version,
+    )?);
+
+    log::info!("Removing directory {:?}...", install_dir);
+    fs::remove_dir_all(&install_dir)
+        .with_context(|| format!("Failed to remove directory {:?}", install_dir))?;
+
+    // Prune the shims that are not provided anymore by any remaining toolchain.
+    let remaining_toolchains: Vec<&InstalledToolchain> = installed_toolchains
+        .iter()
+        .filter(|t| **t != to_uninstall)
+        .collect();
+    let mut still_provided: HashSet<String> = HashSet::new();
+    for toolchain in remaining_toolchains {
+        let mut dirs = vec![toolchain.location.clone()];
+        if toolchain.is_custom_install() {
+            dirs.extend(os::paths_to_prepends(
+                toolchain.implementation,
+                &toolchain.version,
+            )?);
+        }
+        still_provided.extend(binaries_in(&dirs));
+    }
+    let default_shims: HashSet<String> = default_shims().into_iter().collect();
+
+    let shims_dir = paths_provider.shims();
+    let mut nb_pruned_shims = 0;
+    for binary in provided_binaries
+        .difference(&still_provided)
+        .filter(|binary| !default_shims.contains(*binary))
+    {
+        let shim = shims_dir.join(binary);
+        if shim.exists() {
+            log::debug!("Removing stale shim {:?}", shim);
+            fs::remove_file(&shim)
+                .with_context(|| format!("Failed to remove stale shim {:?}", shim))?;
+            nb_pruned_shims += 1;
+        }
+    }
+    log::info!("Removed {} stale shim(s)", nb_pruned_shims);
+
+    println!(
+        "🐍 {} {} successfully uninstalled!",
+        to_uninstall.implementation.label(),
+        to_uninstall.version
+    );
+
+    Ok(())
+}
+
+/// Filenames of all the files found in the given directories.
+fn binaries_in(dirs: &[PathBuf]) -> HashSet<String> {
+    dirs.iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.flatten())
+        .filter(|entry| entry.path().is_file())
+        .filter_map(|entry| entry.file_name().to_str().map(String::from))
+        .collect()
+}
@@ -0,0 +1,25 @@
+use anyhow::Result;
+
+use crate::{
+    constants::EXECUTABLE_NAME, proxy::Proxies, utils::directory::PycorsPathsProviderFromEnv,
+};
+
+pub fn run() -> Result<()> {
+    // self_update only looks at the environment for proxies
+    Proxies::load(&PycorsPathsProviderFromEnv::new())?.export_to_env();
+
+    let status = self_update::backends::github::Update::configure()
+        .repo_owner("nbigaouette")
+        .repo_name(EXECUTABLE_NAME)
+        .bin_name(&format!(
+            "{}{}",
+            EXECUTABLE_NAME,
+            std::env::consts::EXE_SUFFIX
+        ))
+        .show_download_progress(true)
+        .current_version(self_update::cargo_crate_version!())
+        .build()?
+        .update()?;
+    println!("Update status: `{}`!", status.version());
+    Ok(())
+}
@@ -0,0 +1,106 @@
+use std::fs;
+
+use anyhow::{Context, Result};
+
+use crate::{
+    commands::VenvCommand,
+    shim,
+    toolchain::CompatibleToolchainBuilder,
+    utils::directory::PycorsPathsProviderFromEnv,
+    venv::{Venv, VenvError},
+};
+
+pub fn run(command: VenvCommand) -> Result<()> {
+    match command {
+        VenvCommand::Create { name, version } => create(name, version),
+        VenvCommand::List => list(),
+        VenvCommand::Remove { name } => remove(name),
+    }
+}
+
+fn create(name: String, version: Option<String>) -> Result<()> {
+    let paths_provider = PycorsPathsProviderFromEnv::new();
+    let location = Venv::location(&name, &paths_provider)?;
+    if location.exists() {
+        return Err(VenvError::AlreadyExists(name).into());
+    }
+
+    let compatible_toolchain_builder = match version {
+        Some(version) => CompatibleToolchainBuilder::new().load_from_string(&version),
+        None => CompatibleToolchainBuilder::new()
+            .load_from_file()
+            .pick_latest_if_none_found(),
+    };
+    let toolchain = match compatible_toolchain_builder.compatible_version(paths_provider)? {
+        Some(toolchain) => toolchain,
+        None => return Err(VenvError::MissingInterpreter(name).into()),
+    };
+    let python = match shim::find_command(&toolchain, "python3")? {
+        Some(python) => python,
+        None => shim::find_command(&toolchain, "python")?
+            .ok_or_else(|| VenvError::MissingInterpreter(name.clone()))?,
+    };
+
+    log::info!("Creating virtual environment {:?} with {}", name, toolchain);
+    let paths_provider = PycorsPathsProviderFromEnv::new();
+    fs::create_dir_all(paths_provider.venvs())?;
+    let status = std::process::Command::new(&python)
+        .arg("-m")
+        .arg("venv")
+        .arg(&location)
+        .status()
+        .with_context(|| format!("Failed to execute {:?}", python))?;
+    if !status.success() {
+        return Err(VenvError::CreationFailed(name, status.code()).into());
+    }
+
+    println!(
+        "🐍 Virtual environment {:?} created with Python {} in {}",
+        name,
+        toolchain.version,
+        location.display()
+    );
+
+    Ok(())
+}
+
+fn list() -> Result<()> {
+    let paths_provider = PycorsPathsProviderFromEnv::new();
+    for venv in Venv::list(&paths_provider)? {
+        let version = venv
+            .version
+            .as_ref()
+            .map(ToString::to_string)
+            .unwrap_or_else(|| String::from("?"));
+        let status = if venv.is_broken() {
+            match &venv.home {
+                Some(home) => format!(" (broken: {} not found)", home.display()),
+                None => String::from(" (broken: no base interpreter)"),
+            }
+        } else {
+            String::new()
+        };
+        println!(
+            "{:<20} {:<8} {}{}",
+            venv.name,
+            version,
+            venv.location.display(),
+            status
+        );
+    }
+
+    Ok(())
+}
+
+fn remove(name: String) -> Result<()> {
+    let paths_provider = PycorsPathsProviderFromEnv::new();
+    let venv = Venv::load(&name, &paths_provider)?;
+
+    log::info!("Removing directory {:?}...", venv.location);
+    fs::remove_dir_all(&venv.location)
+        .with_context(|| format!("Failed to remove directory {:?}", venv.location))?;
+
+    println!("🐍 Virtual environment {:?} successfully removed!", name);
+
+    Ok(())
+}
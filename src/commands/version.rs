@@ -1,11 +1,24 @@
 use crate::{
-    toolchain::CompatibleToolchainBuilder, utils::directory::PycorsPathsProviderFromEnv, Result,
+    constants::{version_env_variable, TOOLCHAIN_FILE},
+    toolchain::{version_override, CompatibleToolchainBuilder},
+    utils::directory::PycorsPathsProviderFromEnv,
+    Result,
 };
 
 pub fn run(version: Option<String>) -> Result<()> {
     let compatible_toolchain_builder = match version {
         Some(version) => CompatibleToolchainBuilder::new().load_from_string(&version),
-        None => CompatibleToolchainBuilder::new().load_from_file(),
+        None => {
+            if let Some(version_override) = version_override() {
+                log::info!(
+                    "Using {}={:?} (overriding {:?})",
+                    version_env_variable(),
+                    version_override,
+                    TOOLCHAIN_FILE
+                );
+            }
+            CompatibleToolchainBuilder::new().load_from_file()
+        }
     };
     let compatible_toolchain = compatible_toolchain_builder
         .pick_latest_if_none_found()
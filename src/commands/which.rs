@@ -0,0 +1,51 @@
+use anyhow::Result;
+use semver::Version;
+use thiserror::Error;
+
+use crate::{
+    shim::{self, ShimError},
+    toolchain::find_installed_toolchains,
+    utils::directory::PycorsPathsProviderFromEnv,
+};
+
+#[derive(Debug, Error)]
+pub enum WhichError {
+    #[error("Command {0:?} is not provided by Python {1}{2}")]
+    NotProvided(String, Version, String),
+}
+
+pub fn run(command: String) -> Result<()> {
+    let toolchain = match shim::compatible_toolchain(&command)? {
+        Some(toolchain) => toolchain,
+        None => {
+            log::error!("No Python interpreter found at all. Please install at least one!");
+            return Err(ShimError::MissingInterpreter(command).into());
+        }
+    };
+    log::debug!("Toolchain: {}", toolchain);
+
+    if let Some(path) = shim::find_command(&toolchain, &command)? {
+        println!("{}", path.display());
+        return Ok(());
+    }
+
+    // Help finding the right toolchain by listing the ones providing the command.
+    let paths_provider = PycorsPathsProviderFromEnv::new();
+    let mut providers = Vec::new();
+    for installed_toolchain in find_installed_toolchains(&paths_provider)? {
+        if let Some(path) = shim::find_command(&installed_toolchain, &command)? {
+            providers.push(format!(
+                "\n    {} ({})",
+                installed_toolchain.version,
+                path.display()
+            ));
+        }
+    }
+    let providers = if providers.is_empty() {
+        String::from(" (nor by any other installed toolchain)")
+    } else {
+        format!("; it is provided by:{}", providers.concat())
+    };
+
+    Err(WhichError::NotProvided(command, toolchain.version, providers).into())
+}
@@ -0,0 +1,227 @@
+use std::{collections::BTreeMap, fs::read_to_string};
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    check::CheckConfig,
+    mirrors::MirrorsConfig,
+    profile::BuildProfile,
+    proxy::ProxyConfig,
+    tls::TlsConfig,
+    utils::directory::{PycorsHomeProviderTrait, PycorsPathsProvider},
+    Result,
+};
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("Failed to parse configuration file {0:?}: {1}")]
+    Parse(std::path::PathBuf, toml::de::Error),
+}
+
+/// User configuration, read from `${HYGEIA_HOME}/config.toml`.
+///
+/// All settings are optional; a missing file is the same as an empty one.
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Consider pre-releases (alpha, beta and release candidates) as if `--pre`
+    /// was always given.
+    pub pre_releases: bool,
+    /// Read the requested version from `pyproject.toml`, `.tool-versions` and
+    /// `runtime.txt` when no `.python-version` is found.
+    pub other_version_files: bool,
+    /// Number of versions built at the same time when installing several at once,
+    /// as if `--jobs` was always given.
+    pub parallel_builds: usize,
+    /// Locations of the Python index and archives (see `Mirrors`).
+    pub mirrors: MirrorsConfig,
+    /// Proxies, when not given by the environment (see `Proxies`).
+    pub proxy: ProxyConfig,
+    /// Certificate authorities to trust (see `CaBundle`).
+    pub tls: TlsConfig,
+    /// Named build profiles, selected with `install --profile` (see `BuildProfile`).
+    pub profiles: BTreeMap<String, BuildProfile>,
+    /// Modules verified after building from source (see `CheckConfig`).
+    pub check: CheckConfig,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            pre_releases: false,
+            other_version_files: true,
+            parallel_builds: 1,
+            mirrors: MirrorsConfig::default(),
+            proxy: ProxyConfig::default(),
+            tls: TlsConfig::default(),
+            profiles: BTreeMap::new(),
+            check: CheckConfig::default(),
+        }
+    }
+}
+
+impl Config {
+    pub fn load<P>(paths_provider: &PycorsPathsProvider<P>) -> Result<Config>
+    where
+        P: PycorsHomeProviderTrait,
+    {
+        let config_file = paths_provider.config_file();
+        if !config_file.exists() {
+            log::debug!("No configuration file {:?}, using defaults", config_file);
+            return Ok(Config::default());
+        }
+
+        log::debug!("Reading configuration file {:?}", config_file);
+        let content = read_to_string(&config_file)?;
+        let config: Config =
+            toml::from_str(&content).map_err(|e| ConfigError::Parse(config_file, e))?;
+        log::debug!("Configuration: {:?}", config);
+
+        Ok(config)
+    }
+
+    /// Like `load()`, but falling back to the defaults if the file cannot be read.
+    ///
+    /// Used when selecting the interpreter to run, so that a mistake in the
+    /// configuration does not break every shim; the subcommands report it instead.
+    pub fn load_or_default<P>(paths_provider: &PycorsPathsProvider<P>) -> Config
+    where
+        P: PycorsHomeProviderTrait,
+    {
+        Config::load(paths_provider).unwrap_or_else(|e| {
+            log::warn!("{:#}. Using the default configuration.", e);
+            Config::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::utils::directory::MockPycorsHomeProviderTrait;
+
+    use hygeia_test_helpers::create_test_temp_dir;
+
+    fn paths_provider(
+        project_home: std::path::PathBuf,
+    ) -> PycorsPathsProvider<MockPycorsHomeProviderTrait> {
+        let mut mock = MockPycorsHomeProviderTrait::new();
+        mock.expect_project_home().return_const(Some(project_home));
+        mock.expect_home().times(0).return_const(None);
+        PycorsPathsProvider::from(mock)
+    }
+
+    #[test]
+    fn load_missing_file() {
+        let project_home = create_test_temp_dir!().join(".hygeia");
+        let config = Config::load(&paths_provider(project_home)).unwrap();
+        assert_eq!(config, Config::default());
+        assert!(!config.pre_releases);
+    }
+
+    #[test]
+    fn load_pre_releases() {
+        let project_home = create_test_temp_dir!().join(".hygeia");
+        fs::create_dir_all(&project_home).unwrap();
+        fs::write(project_home.join("config.toml"), "pre_releases = true\n").unwrap();
+        let config = Config::load(&paths_provider(project_home)).unwrap();
+        assert!(config.pre_releases);
+        assert!(config.other_version_files);
+    }
+
+    #[test]
+    fn load_disable_other_version_files() {
+        let project_home = create_test_temp_dir!().join(".hygeia");
+        fs::create_dir_all(&project_home).unwrap();
+        fs::write(
+            project_home.join("config.toml"),
+            "other_version_files = false\n",
+        )
+        .unwrap();
+        let config = Config::load(&paths_provider(project_home)).unwrap();
+        assert!(!config.other_version_files);
+        assert!(!config.pre_releases);
+    }
+
+    #[test]
+    fn load_parallel_builds() {
+        let project_home = create_test_temp_dir!().join(".hygeia");
+        fs::create_dir_all(&project_home).unwrap();
+        fs::write(project_home.join("config.toml"), "parallel_builds = 4\n").unwrap();
+        let config = Config::load(&paths_provider(project_home)).unwrap();
+        assert_eq!(config.parallel_builds, 4);
+        assert_eq!(Config::default().parallel_builds, 1);
+    }
+
+    #[test]
+    fn load_profiles() {
+        let project_home = create_test_temp_dir!().join(".hygeia");
+        fs::create_dir_all(&project_home).unwrap();
+        fs::write(
+            project_home.join("config.toml"),
+            "[profiles.debug]\nconfigure_args = [\"--with-pydebug\"]\nenv = { CFLAGS = \"-O0\" }\n",
+        )
+        .unwrap();
+        let config = Config::load(&paths_provider(project_home)).unwrap();
+        let debug = &config.profiles["debug"];
+        assert_eq!(debug.configure_args, vec!["--with-pydebug"]);
+        assert_eq!(debug.env["CFLAGS"], "-O0");
+        assert!(debug.shared);
+        assert!(debug.make_targets.is_empty());
+    }
+
+    #[test]
+    fn load_check() {
+        let project_home = create_test_temp_dir!().join(".hygeia");
+        fs::create_dir_all(&project_home).unwrap();
+        fs::write(
+            project_home.join("config.toml"),
+            "[check]\nmodules = [\"_ssl\", \"_tkinter\"]\nfail_on_missing = true\n",
+        )
+        .unwrap();
+        let config = Config::load(&paths_provider(project_home)).unwrap();
+        assert_eq!(
+            config.check.modules,
+            Some(vec!["_ssl".to_string(), "_tkinter".to_string()])
+        );
+        assert!(config.check.fail_on_missing);
+        assert!(!Config::default().check.fail_on_missing);
+    }
+
+    #[test]
+    fn load_mirrors() {
+        let project_home = create_test_temp_dir!().join(".hygeia");
+        fs::create_dir_all(&project_home).unwrap();
+        fs::write(
+            project_home.join("config.toml"),
+            "[mirrors]\nftp = \"http://mirror.internal/python/\"\ntrusted_hosts = [\"mirror.internal\"]\n",
+        )
+        .unwrap();
+        let config = Config::load(&paths_provider(project_home)).unwrap();
+        assert_eq!(
+            config.mirrors.ftp.unwrap().as_str(),
+            "http://mirror.internal/python/"
+        );
+        assert_eq!(config.mirrors.trusted_hosts, vec!["mirror.internal"]);
+        assert_eq!(config.mirrors.source_index, None);
+    }
+
+    #[test]
+    fn load_unknown_key() {
+        let project_home = create_test_temp_dir!().join(".hygeia");
+        fs::create_dir_all(&project_home).unwrap();
+        fs::write(project_home.join("config.toml"), "pre = true\n").unwrap();
+        let err = Config::load(&paths_provider(project_home.clone())).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ConfigError>(),
+            Some(ConfigError::Parse(..))
+        ));
+        assert_eq!(
+            Config::load_or_default(&paths_provider(project_home)),
+            Config::default()
+        );
+    }
+}
@@ -49,6 +49,15 @@ pub fn document_overwrite_env_variable() -> &'static str {
     &DOCUMENT_OVERWRITE_ENV_VARIABLE
 }
 
+/// Return the environment variable used to overwrite the requested toolchain
+pub fn version_env_variable() -> &'static str {
+    lazy_static! {
+        static ref VERSION_ENV_VARIABLE: String =
+            format!("{}_VERSION", executable_name_from_env!().to_uppercase());
+    }
+    &VERSION_ENV_VARIABLE
+}
+
 /// Filename describing which version of this project installed a toolchain.
 pub const INFO_FILE: &str = concat!("installed_by_", executable_name_from_env!(), ".txt");
 
@@ -58,14 +67,55 @@ pub const EXTRA_PACKAGES_FILENAME: &str = "extra-packages-to-install.txt";
 /// Content of file listing extra `pip` packages to install, copied when setting-up shim.
 pub const EXTRA_PACKAGES_FILENAME_CONTENT: &str = include_str!("../extra-packages-to-install.txt");
 
+/// Filename of the configuration file, in `${HYGEIA_HOME}`.
+pub const CONFIG_FILENAME: &str = "config.toml";
+
+/// Filename of the keyring used to verify the signature of source tarballs.
+pub const KEYRING_FILENAME: &str = "cpython-release-managers.asc";
+
+/// Content of the keyring bundled with the executable.
+pub const KEYRING_FILENAME_CONTENT: &str = include_str!("../cpython-release-managers.asc");
+
 pub const TOOLCHAIN_FILE: &str = ".python-version";
 
+/// Filename of the global toolchain file, in `${HYGEIA_HOME}`, used when no toolchain
+/// file is found.
+pub const GLOBAL_TOOLCHAIN_FILE: &str = "global-python-version";
+
+/// Files of other tools from which the requested version can be read, after `TOOLCHAIN_FILE`.
+pub const PYPROJECT_FILE: &str = "pyproject.toml";
+pub const TOOL_VERSIONS_FILE: &str = ".tool-versions";
+pub const RUNTIME_FILE: &str = "runtime.txt";
+
 // Note: Trailing '/' is required for proper parsing
 pub const PYTHON_SOURCE_INDEX_URL: &str = "https://www.python.org/downloads/source/";
 
 // Note: Trailing '/' is required for proper parsing
 pub const PYTHON_WINDOWS_INDEX_URL: &str = "https://www.python.org/downloads/windows/";
 
+// Note: Trailing '/' is required for proper parsing
+pub const PYTHON_FTP_URL: &str = "https://www.python.org/ftp/python/";
+
+pub const GET_PIP_URL: &str = "https://bootstrap.pypa.io/get-pip.py";
+
+// Note: Trailing '/' is required for proper parsing
+pub const PYTHON_RELEASE_PAGE_URL: &str = "https://www.python.org/downloads/release/";
+
+/// GitHub API listing the most recent releases of relocatable pre-built interpreters.
+pub const PYTHON_BUILD_STANDALONE_RELEASES_URL: &str =
+    "https://api.github.com/repos/astral-sh/python-build-standalone/releases?per_page=10";
+
+/// List of the PyPy releases and their pre-built archives.
+pub const PYPY_VERSIONS_URL: &str = "https://downloads.python.org/pypy/versions.json";
+
+/// Page publishing the checksums of the PyPy archives.
+pub const PYPY_CHECKSUMS_URL: &str = "https://www.pypy.org/checksums.html";
+
 pub const AVAILABLE_TOOLCHAIN_CACHE: &str = "available_toolchains.json";
 
+pub const INTERPRETER_INDEX_CACHE: &str = "interpreters.json";
+
+/// Certificates of a CA bundle directory, concatenated for pip.
+pub const CA_BUNDLE_CACHE: &str = "ca_bundle.pem";
+
 pub const SHIMS_DIRECTORY_IDENTIFIER_FILE: &str = "this_is_a_shims_directory.txt";
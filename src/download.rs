@@ -1,22 +1,79 @@
 use std::{
-    fs::{create_dir_all, File},
-    io::{BufWriter, Write},
+    fs::{self, create_dir_all, remove_file, File, OpenOptions},
+    io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write},
     path::{Path, PathBuf},
+    time::Duration,
 };
 
 use anyhow::Context;
 use async_trait::async_trait;
 use bytes::Bytes;
-use hyper::{body::HttpBody as _, client::Client, Uri};
+use hyper::{
+    body::HttpBody as _,
+    client::{Client, HttpConnector},
+    Body, Request, StatusCode, Uri,
+};
+use hyper_proxy::ProxyConnector;
 use hyper_tls::HttpsConnector;
 use indicatif::{ProgressBar, ProgressStyle};
-use url::Url;
+use thiserror::Error;
+use url::{Position, Url};
+
+use crate::{
+    checksum::{Digest, Hasher, HashingWriter},
+    proxy::Proxies,
+    tls::CaBundle,
+    utils, Result,
+};
+
+/// Maximum number of redirections followed for a single download.
+const MAX_REDIRECTS: usize = 10;
+
+/// Timeout connecting to a server.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Timeout waiting for a response, or for the next chunk of its body.
+const READ_TIMEOUT: Duration = Duration::from_secs(60);
 
-use crate::{utils, Result};
+/// Number of attempts of a request failing with a transient error.
+const MAX_ATTEMPTS: u32 = 4;
+
+/// Delay before retrying a failed request, doubled after every attempt.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+const USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+#[derive(Debug, Error)]
+pub enum DownloadError {
+    #[error("Too many redirections downloading {0}")]
+    TooManyRedirects(Url),
+    #[error("Redirection without location downloading {0}")]
+    MissingLocation(Url),
+    #[error("Failed to download {0}: {1}")]
+    HttpStatus(Url, StatusCode),
+    #[error("Refusing to download {0} over plain HTTP: its host is not trusted")]
+    UntrustedHttp(Url),
+    #[error("Unsupported URL scheme: {0}")]
+    UnsupportedScheme(Url),
+    #[error("Invalid file URL: {0}")]
+    InvalidFileUrl(Url),
+    #[error("Incomplete download {0:?}: got {1} bytes instead of {2}")]
+    Incomplete(PathBuf, u64, u64),
+    #[error("Timed out downloading {0}")]
+    Timeout(Url),
+}
 
 #[async_trait]
-pub trait Downloader {
+pub trait Downloader: Send {
     async fn get(&mut self) -> Result<()>;
+    /// Start downloading from byte `offset`, to resume a partial download.
+    ///
+    /// Returns `false` if the whole file is downloaded instead, for example when
+    /// the server does not support range requests.
+    async fn get_from(&mut self, _offset: u64) -> Result<bool> {
+        self.get().await?;
+        Ok(false)
+    }
     async fn next_chunk(&mut self) -> Option<Result<Bytes>>;
     fn content_length(&self) -> Option<u64>;
     fn url(&self) -> Url;
@@ -35,24 +92,41 @@ pub trait Downloader {
     }
 }
 
-async fn to_writer<D, W>(d: &mut D, w: &mut W, with_progress_bar: bool) -> Result<()>
+#[async_trait]
+impl Downloader for Box<dyn Downloader + Send> {
+    async fn get(&mut self) -> Result<()> {
+        (**self).get().await
+    }
+    async fn get_from(&mut self, offset: u64) -> Result<bool> {
+        (**self).get_from(offset).await
+    }
+    async fn next_chunk(&mut self) -> Option<Result<Bytes>> {
+        (**self).next_chunk().await
+    }
+    fn content_length(&self) -> Option<u64> {
+        (**self).content_length()
+    }
+    fn url(&self) -> Url {
+        (**self).url()
+    }
+}
+
+async fn to_writer<D, W>(d: &mut D, w: &mut W, pb: Option<&ProgressBar>) -> Result<()>
 where
     D: Downloader,
     W: Write,
 {
-    let pb = if with_progress_bar {
+    if let Some(pb) = pb {
         let message = format!(
             "Downloading {:?}...",
             d.filename().unwrap_or_else(|_| String::new())
         );
-        Some(create_download_progress_bar(&message, d.content_length()))
-    } else {
-        None
-    };
+        style_download_progress_bar(pb, &message, d.content_length());
+    }
 
     while let Some(next) = d.next_chunk().await {
         let chunk = next?;
-        if let Some(pb) = pb.as_ref() {
+        if let Some(pb) = pb {
             pb.inc(chunk.len() as u64)
         }
         w.write_all(&chunk[..])?;
@@ -60,9 +134,21 @@ where
     Ok(())
 }
 
+type HyperConnector = ProxyConnector<HttpsConnector<HttpConnector>>;
+
 pub struct HyperDownloader {
     url: Url,
-    client: hyper::client::Client<hyper_tls::HttpsConnector<hyper::client::HttpConnector>>,
+    /// `url` after following the redirections, once requested
+    final_url: Option<Url>,
+    https_only: bool,
+    proxies: Proxies,
+    ca_bundle: Option<CaBundle>,
+    connect_timeout: Duration,
+    read_timeout: Duration,
+    max_attempts: u32,
+    initial_backoff: Duration,
+    connector: HyperConnector,
+    client: Client<HyperConnector>,
     response: Option<hyper::Response<hyper::Body>>,
     content_length: Option<u64>,
 }
@@ -72,36 +158,229 @@ impl HyperDownloader {
     where
         S: AsRef<str>,
     {
-        let https = {
-            let mut connector = HttpsConnector::new();
-            connector.https_only(true);
-            connector
-        };
-        let client = Client::builder().build::<_, hyper::Body>(https);
-        let url = url.as_ref();
+        HyperDownloader::build(url.as_ref(), true)
+    }
+
+    /// Same as `new()`, but also allowing plain HTTP (for trusted mirrors).
+    pub fn new_allowing_http<S>(url: S) -> Result<HyperDownloader>
+    where
+        S: AsRef<str>,
+    {
+        HyperDownloader::build(url.as_ref(), false)
+    }
+
+    fn build(url: &str, https_only: bool) -> Result<HyperDownloader> {
         let url: Url = url
             .parse()
             .with_context(|| format!("Failed to parse url: {}", url))?;
+        let proxies = Proxies::default();
+        let connector = connector(&proxies, None, CONNECT_TIMEOUT)?;
         Ok(HyperDownloader {
             url,
-            client,
+            final_url: None,
+            https_only,
+            proxies,
+            ca_bundle: None,
+            connect_timeout: CONNECT_TIMEOUT,
+            read_timeout: READ_TIMEOUT,
+            max_attempts: MAX_ATTEMPTS,
+            initial_backoff: INITIAL_BACKOFF,
+            client: Client::builder().build(connector.clone()),
+            connector,
             response: None,
             content_length: None,
         })
     }
+
+    /// Go through `proxies` (which can be reached over plain HTTP).
+    pub fn with_proxies(mut self, proxies: &Proxies) -> Result<HyperDownloader> {
+        if let Some(proxy_url) = proxies.for_url(&self.url) {
+            log::debug!(
+                "Using proxy {}:{} for {}",
+                proxy_url.host_str().unwrap_or_default(),
+                proxy_url.port_or_known_default().unwrap_or_default(),
+                self.url
+            );
+        }
+        self.proxies = proxies.clone();
+        self.reconnect()?;
+        Ok(self)
+    }
+
+    /// Trust the certificates of `ca_bundle` in addition to the system's.
+    pub fn with_ca_bundle(mut self, ca_bundle: &CaBundle) -> Result<HyperDownloader> {
+        self.ca_bundle = Some(ca_bundle.clone());
+        self.reconnect()?;
+        Ok(self)
+    }
+
+    fn reconnect(&mut self) -> Result<()> {
+        self.connector = connector(&self.proxies, self.ca_bundle.as_ref(), self.connect_timeout)?;
+        self.client = Client::builder().build(self.connector.clone());
+        Ok(())
+    }
+}
+
+fn connector(
+    proxies: &Proxies,
+    ca_bundle: Option<&CaBundle>,
+    connect_timeout: Duration,
+) -> Result<HyperConnector> {
+    let mut tls = native_tls::TlsConnector::builder();
+    if let Some(ca_bundle) = ca_bundle {
+        ca_bundle.add_to(&mut tls)?;
+    }
+    let tls = tls.build()?;
+
+    let mut http = HttpConnector::new();
+    http.enforce_http(false);
+    http.set_connect_timeout(Some(connect_timeout));
+    let https = HttpsConnector::from((http, tls.clone().into()));
+
+    let mut connector = proxies.connector(https)?;
+    // Used when tunneling through a proxy
+    connector.set_tls(Some(tls));
+    Ok(connector)
 }
 
 #[async_trait]
 impl Downloader for HyperDownloader {
     async fn get(&mut self) -> Result<()> {
-        log::info!("Downloading {}...", self.url);
+        self.request(None).await?;
+        Ok(())
+    }
+
+    async fn get_from(&mut self, offset: u64) -> Result<bool> {
+        self.request(Some(offset)).await
+    }
 
-        let uri = Uri::builder()
-            .scheme(self.url.scheme())
-            .authority(self.url.host_str().unwrap())
-            .path_and_query(self.url.path())
-            .build()?;
-        let response = self.client.get(uri).await?;
+    async fn next_chunk(&mut self) -> Option<Result<Bytes>> {
+        let response = self.response.as_mut()?;
+        match tokio::time::timeout(self.read_timeout, response.data()).await {
+            Ok(chunk) => chunk.map(|v| v.with_context(|| "Failed to get next chunk")),
+            Err(_) => Some(Err(
+                DownloadError::Timeout(self.current_url().clone()).into()
+            )),
+        }
+    }
+
+    fn content_length(&self) -> Option<u64> {
+        self.content_length
+    }
+
+    fn url(&self) -> Url {
+        self.url.clone()
+    }
+}
+
+impl HyperDownloader {
+    /// URL requested: the final one once the redirections were followed, so resuming
+    /// does not go through them again.
+    fn current_url(&self) -> &Url {
+        self.final_url.as_ref().unwrap_or(&self.url)
+    }
+
+    /// Send the request, starting at byte `range_start` if given and supported
+    /// by the server (in which case `true` is returned).
+    async fn request(&mut self, range_start: Option<u64>) -> Result<bool> {
+        log::info!("Downloading {}...", self.current_url());
+
+        let mut attempt = 1;
+        let mut backoff = self.initial_backoff;
+        loop {
+            match self.try_request(range_start).await {
+                Err(e) if attempt < self.max_attempts && is_transient(&e) => {
+                    log::warn!(
+                        "Failed to download {} (attempt {}/{}): {}. Retrying in {:?}...",
+                        self.current_url(),
+                        attempt,
+                        self.max_attempts,
+                        e,
+                        backoff
+                    );
+                    tokio::time::sleep(backoff).await;
+                    attempt += 1;
+                    backoff *= 2;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    async fn try_request(&mut self, mut range_start: Option<u64>) -> Result<bool> {
+        // GitHub serves release archives through a redirection
+        let mut url = self.current_url().clone();
+        let mut nb_redirects = 0;
+        let response = loop {
+            if self.https_only && url.scheme() != "https" {
+                return Err(DownloadError::UntrustedHttp(url).into());
+            }
+            // Everything but the fragment (and credentials, unsupported by `Uri`)
+            let uri: Uri = format!(
+                "{}://{}{}",
+                url.scheme(),
+                &url[Position::BeforeHost..Position::AfterPort],
+                &url[Position::BeforePath..Position::AfterQuery]
+            )
+            .parse()?;
+            let mut request = Request::get(uri)
+                // Required by the GitHub API
+                .header(hyper::header::USER_AGENT, USER_AGENT)
+                .body(Body::empty())?;
+            if let Some(range_start) = range_start {
+                request.headers_mut().insert(
+                    hyper::header::RANGE,
+                    format!("bytes={}-", range_start).parse()?,
+                );
+            }
+            // Plain HTTP requests carry the proxy's credentials themselves
+            if let Some(headers) = self.connector.http_headers(request.uri()) {
+                request.headers_mut().extend(headers.clone());
+            }
+            let response = tokio::time::timeout(self.read_timeout, self.client.request(request))
+                .await
+                .map_err(|_| DownloadError::Timeout(url.clone()))??;
+            if response.status() == StatusCode::RANGE_NOT_SATISFIABLE && range_start.is_some() {
+                log::debug!("Cannot resume downloading {}, starting over", url);
+                range_start = None;
+                continue;
+            }
+            if let Some(start) = range_start {
+                // Appending anything but the requested range would corrupt the file
+                if response.status() == StatusCode::PARTIAL_CONTENT
+                    && content_range_start(response.headers()) != Some(start)
+                {
+                    log::debug!("Unexpected range downloading {}, starting over", url);
+                    range_start = None;
+                    continue;
+                }
+            }
+            if !response.status().is_redirection() {
+                break response;
+            }
+
+            let location = response
+                .headers()
+                .get(hyper::header::LOCATION)
+                .ok_or_else(|| DownloadError::MissingLocation(url.clone()))?
+                .to_str()?;
+            url = url.join(location)?;
+            nb_redirects += 1;
+            if nb_redirects > MAX_REDIRECTS {
+                return Err(DownloadError::TooManyRedirects(self.current_url().clone()).into());
+            }
+            log::debug!("Redirected to {}", url);
+        };
+        if nb_redirects > 0 {
+            self.final_url = Some(url.clone());
+        }
+        if !response.status().is_success() {
+            return Err(DownloadError::HttpStatus(url, response.status()).into());
+        }
+        let resumed = range_start.is_some() && response.status() == StatusCode::PARTIAL_CONTENT;
+        if let (Some(range_start), true) = (range_start, resumed) {
+            log::debug!("Resuming download from byte {}", range_start);
+        }
 
         let headers = response.headers().clone();
         let content_length = match headers.get(hyper::header::CONTENT_LENGTH).cloned() {
@@ -119,16 +398,132 @@ impl Downloader for HyperDownloader {
         self.response = Some(response);
         self.content_length = content_length;
 
+        Ok(resumed)
+    }
+}
+
+/// First byte of the response, from a `Content-Range: bytes <first>-<last>/<length>`
+/// header.
+fn content_range_start(headers: &hyper::HeaderMap) -> Option<u64> {
+    let content_range = headers.get(hyper::header::CONTENT_RANGE)?.to_str().ok()?;
+    let range = content_range.trim().strip_prefix("bytes ")?;
+    range.split('-').next()?.trim().parse().ok()
+}
+
+/// Is the error worth retrying the request?
+fn is_transient(error: &anyhow::Error) -> bool {
+    if let Some(error) = error.downcast_ref::<DownloadError>() {
+        return match error {
+            DownloadError::Timeout(_) => true,
+            DownloadError::HttpStatus(_, status) => {
+                status.is_server_error()
+                    || *status == StatusCode::REQUEST_TIMEOUT
+                    || *status == StatusCode::TOO_MANY_REQUESTS
+            }
+            _ => false,
+        };
+    }
+    match error.downcast_ref::<hyper::Error>() {
+        // An untrusted certificate will not become trusted by retrying
+        Some(error) if is_tls_error(error) => false,
+        Some(error) => {
+            error.is_connect()
+                || error.is_incomplete_message()
+                || error.is_closed()
+                // Connection lost while reading the body
+                || matches!(
+                    std::error::Error::source(error),
+                    Some(source) if source.is::<std::io::Error>()
+                )
+        }
+        None => false,
+    }
+}
+
+/// Is the error, or one of its causes, a TLS error (for example a failed certificate
+/// verification)?
+fn is_tls_error(error: &(dyn std::error::Error + 'static)) -> bool {
+    let mut cause = Some(error);
+    while let Some(error) = cause {
+        if error.is::<native_tls::Error>() {
+            return true;
+        }
+        // `io::Error::source()` skips the error it wraps
+        if let Some(inner) = error
+            .downcast_ref::<std::io::Error>()
+            .and_then(std::io::Error::get_ref)
+        {
+            if is_tls_error(inner) {
+                return true;
+            }
+        }
+        cause = error.source();
+    }
+    false
+}
+
+/// Size of the chunks read by `FileDownloader`.
+const FILE_CHUNK_SIZE: usize = 64 * 1024;
+
+/// "Downloads" from a local `file://` URL, for example a mirror on a shared drive.
+///
+/// A URL pointing to a directory reads the `index.html` file it contains.
+pub struct FileDownloader {
+    url: Url,
+    path: PathBuf,
+    file: Option<File>,
+    content_length: Option<u64>,
+}
+
+impl FileDownloader {
+    pub fn new(url: Url) -> Result<FileDownloader> {
+        let path = url
+            .to_file_path()
+            .map_err(|_| DownloadError::InvalidFileUrl(url.clone()))?;
+        Ok(FileDownloader {
+            url,
+            path,
+            file: None,
+            content_length: None,
+        })
+    }
+}
+
+#[async_trait]
+impl Downloader for FileDownloader {
+    async fn get(&mut self) -> Result<()> {
+        self.get_from(0).await?;
         Ok(())
     }
 
+    async fn get_from(&mut self, offset: u64) -> Result<bool> {
+        let path = if self.path.is_dir() {
+            self.path.join("index.html")
+        } else {
+            self.path.clone()
+        };
+        log::info!("Reading {:?}...", path);
+        let mut file = File::open(&path).with_context(|| format!("Failed to open {:?}", path))?;
+        let length = file.metadata()?.len();
+        let offset = if offset <= length { offset } else { 0 };
+        file.seek(SeekFrom::Start(offset))?;
+        self.content_length = Some(length - offset);
+        self.file = Some(file);
+        Ok(offset > 0)
+    }
+
     async fn next_chunk(&mut self) -> Option<Result<Bytes>> {
-        match &mut self.response {
-            None => None,
-            Some(response) => response
-                .data()
-                .await
-                .map(|v| v.with_context(|| "Failed to get next chunk")),
+        let file = self.file.as_mut()?;
+        let mut buffer = vec![0; FILE_CHUNK_SIZE];
+        match file.read(&mut buffer) {
+            Ok(0) => None,
+            Ok(nb_bytes) => {
+                buffer.truncate(nb_bytes);
+                Some(Ok(Bytes::from(buffer)))
+            }
+            Err(e) => Some(Err(
+                anyhow::Error::new(e).context(format!("Failed to read {:?}", self.path))
+            )),
         }
     }
 
@@ -147,26 +542,67 @@ where
 {
     downloader.get().await?;
     let mut writer: Vec<u8> = Vec::new();
-    to_writer(downloader, &mut writer, with_progress_bar).await?;
+    let pb = new_progress_bar(with_progress_bar);
+    to_writer(downloader, &mut writer, pb.as_ref()).await?;
+    if let Some(pb) = pb {
+        pb.finish();
+    }
     Ok(String::from_utf8(writer)?)
 }
 
+/// Download to the `download_to` directory.
+///
+/// The file is written to a `.part` file, renamed once complete. An interrupted
+/// download is resumed if the server supports it.
+///
+/// When `expected_digest` is given, the downloaded file is hashed while being
+/// written and deleted if it does not match. A file already present is only
+/// reused if it matches the expected digest, or else its expected size.
 pub async fn download_to_path<D, P>(
     downloader: &mut D,
     download_to: P,
     with_progress_bar: bool,
+    expected_digest: Option<&Digest>,
 ) -> Result<()>
 where
     P: AsRef<Path>,
     D: Downloader,
 {
-    _download_to_path(downloader, download_to.as_ref(), with_progress_bar).await
+    let pb = new_progress_bar(with_progress_bar);
+    _download_to_path(
+        downloader,
+        download_to.as_ref(),
+        pb.as_ref(),
+        expected_digest,
+    )
+    .await?;
+    if let Some(pb) = pb {
+        pb.finish();
+    }
+    Ok(())
 }
 
-pub async fn _download_to_path<D>(
+/// Same as `download_to_path()`, reporting progress on an existing progress bar.
+///
+/// The bar is restyled for the download but not finished, its owner can reuse it.
+pub async fn download_to_path_with_progress_bar<D, P>(
+    downloader: &mut D,
+    download_to: P,
+    pb: &ProgressBar,
+    expected_digest: Option<&Digest>,
+) -> Result<()>
+where
+    P: AsRef<Path>,
+    D: Downloader,
+{
+    _download_to_path(downloader, download_to.as_ref(), Some(pb), expected_digest).await
+}
+
+async fn _download_to_path<D>(
     downloader: &mut D,
     download_to: &Path,
-    with_progress_bar: bool,
+    pb: Option<&ProgressBar>,
+    expected_digest: Option<&Digest>,
 ) -> Result<()>
 where
     D: Downloader,
@@ -181,38 +617,197 @@ where
     let mut file_path = PathBuf::new();
     file_path.push(download_to);
     file_path.push(&filename);
+    let part_path = download_to.join(format!("{}.part", filename));
 
+    let mut started = false;
     if file_path.exists() {
-        log::warn!("skipped: file {} already downloaded.", filename);
-        Ok(())
-    } else {
-        let mut output = BufWriter::new(File::create(&file_path)?);
+        match expected_digest {
+            None => {
+                // Without a checksum, only the size tells if the file is complete
+                downloader.get().await?;
+                started = true;
+                let file_size = fs::metadata(&file_path)?.len();
+                match downloader.content_length() {
+                    Some(expected_size) if expected_size != file_size => {
+                        log::warn!(
+                            "File {} already downloaded but its size ({} bytes) does not match ({} bytes). Downloading again.",
+                            filename,
+                            file_size,
+                            expected_size
+                        );
+                        remove_file(&file_path)?;
+                    }
+                    _ => {
+                        log::info!("skipped: file {} already downloaded.", filename);
+                        return Ok(());
+                    }
+                }
+            }
+            Some(expected_digest) => {
+                if expected_digest.verify_file(&file_path).is_ok() {
+                    log::info!(
+                        "skipped: file {} already downloaded (checksum verified).",
+                        filename
+                    );
+                    return Ok(());
+                }
+                log::warn!(
+                    "File {} already downloaded but its checksum does not match. Downloading again.",
+                    filename
+                );
+                remove_file(&file_path)?;
+            }
+        }
+    }
 
+    let partial_size = match fs::metadata(&part_path) {
+        Ok(metadata) if !started => metadata.len(),
+        _ => 0,
+    };
+    let mut resumed = if started {
+        false
+    } else if partial_size > 0 {
+        downloader.get_from(partial_size).await?
+    } else {
         downloader.get().await?;
+        false
+    };
+
+    // The request itself is retried by the downloader; a connection lost while
+    // receiving the body resumes from what was written so far.
+    let mut attempt = 1;
+    let (expected_size, actual_digest) = loop {
+        let downloaded_size = if resumed {
+            let downloaded_size = fs::metadata(&part_path)?.len();
+            log::info!(
+                "Resuming download of {} after {} bytes",
+                filename,
+                downloaded_size
+            );
+            downloaded_size
+        } else {
+            0
+        };
+        let expected_size = downloader
+            .content_length()
+            .map(|length| downloaded_size + length);
 
-        to_writer(downloader, &mut output, with_progress_bar).await?;
+        match write_part(downloader, &part_path, resumed, pb, expected_digest).await {
+            Err(e) if attempt < MAX_ATTEMPTS && is_transient(&e) => {
+                let partial_size = fs::metadata(&part_path)?.len();
+                log::warn!(
+                    "Download of {} interrupted after {} bytes (attempt {}/{}): {}. Resuming...",
+                    filename,
+                    partial_size,
+                    attempt,
+                    MAX_ATTEMPTS,
+                    e
+                );
+                attempt += 1;
+                resumed = downloader.get_from(partial_size).await?;
+            }
+            result => break (expected_size, result?),
+        }
+    };
 
-        Ok(())
+    // An incomplete file is kept to be resumed later
+    let size = fs::metadata(&part_path)?.len();
+    if let Some(expected_size) = expected_size {
+        if size != expected_size {
+            return Err(DownloadError::Incomplete(part_path, size, expected_size).into());
+        }
     }
+
+    if let (Some(expected_digest), Some(actual_digest)) = (expected_digest, actual_digest) {
+        if let Err(e) = expected_digest.verify(&file_path, actual_digest) {
+            log::error!("Deleting {:?}: {}", part_path, e);
+            remove_file(&part_path)?;
+            return Err(e);
+        }
+        log::debug!("Checksum of {} verified ({})", filename, expected_digest);
+    }
+
+    fs::rename(&part_path, &file_path)
+        .with_context(|| format!("Failed to rename {:?} to {:?}", part_path, file_path))?;
+
+    Ok(())
 }
 
-fn create_download_progress_bar(msg: &str, length: Option<u64>) -> ProgressBar {
-    let pb = match length {
-        Some(len) => ProgressBar::new(len),
-        None => ProgressBar::new_spinner(),
+/// Write the body of the response to `part_path`, appended to its content if
+/// `resumed`. Returns the digest of the whole file if `expected_digest` is given.
+async fn write_part<D>(
+    downloader: &mut D,
+    part_path: &Path,
+    resumed: bool,
+    pb: Option<&ProgressBar>,
+    expected_digest: Option<&Digest>,
+) -> Result<Option<Digest>>
+where
+    D: Downloader,
+{
+    let output = if resumed {
+        BufWriter::new(OpenOptions::new().append(true).open(part_path)?)
+    } else {
+        BufWriter::new(File::create(part_path)?)
     };
 
+    match expected_digest {
+        None => {
+            let mut output = output;
+            let result = to_writer(downloader, &mut output, pb).await;
+            // Keep what was received to resume from it
+            output.flush()?;
+            result?;
+            Ok(None)
+        }
+        Some(expected_digest) => {
+            let mut hasher = expected_digest.hasher();
+            if resumed {
+                hash_file(&mut hasher, part_path)?;
+            }
+            let mut output = HashingWriter::new(output, hasher);
+            let result = to_writer(downloader, &mut output, pb).await;
+            if let Err(e) = result {
+                output.flush()?;
+                return Err(e);
+            }
+            Ok(Some(output.finalize()?))
+        }
+    }
+}
+
+/// Feed the content of a file to `hasher`.
+fn hash_file(hasher: &mut Hasher, path: &Path) -> Result<()> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut buffer = vec![0; FILE_CHUNK_SIZE];
+    loop {
+        match reader.read(&mut buffer)? {
+            0 => return Ok(()),
+            nb_bytes => hasher.update(&buffer[..nb_bytes]),
+        }
+    }
+}
+
+fn new_progress_bar(with_progress_bar: bool) -> Option<ProgressBar> {
+    if with_progress_bar {
+        Some(ProgressBar::new_spinner())
+    } else {
+        None
+    }
+}
+
+fn style_download_progress_bar(pb: &ProgressBar, msg: &str, length: Option<u64>) {
     pb.set_message(msg.to_owned());
-    if length.is_some() {
+    pb.set_position(0);
+    if let Some(len) = length {
+        pb.set_length(len);
         pb
             .set_style(ProgressStyle::default_bar()
-                .template("{spinner:.green} {msg} [{elapsed_precise}] [{wide_bar:.cyan/blue}] {bytes}/{total_bytes} eta: {eta}")
+                .template("{spinner:.green} {prefix}{msg} [{elapsed_precise}] [{wide_bar:.cyan/blue}] {bytes}/{total_bytes} eta: {eta}")
                 .progress_chars("=> "));
     } else {
-        pb.set_style(ProgressStyle::default_spinner());
+        pb.set_style(ProgressStyle::default_spinner().template("{spinner} {prefix}{msg}"));
     }
-
-    pb
 }
 
 #[cfg(test)]
@@ -220,11 +815,13 @@ mod tests {
     use std::fs;
 
     use super::*;
+    use crate::checksum::ChecksumError;
 
     use hygeia_test_helpers::create_test_temp_dir;
 
     struct MockDownloader {
         chunks: Vec<Result<Bytes>>,
+        content_length: Option<u64>,
     }
 
     impl MockDownloader {
@@ -232,8 +829,14 @@ mod tests {
             // We use Vec::pop() to yields elements, so revert the vector here
             MockDownloader {
                 chunks: chunks.into_iter().rev().collect(),
+                content_length: None,
             }
         }
+
+        fn with_content_length(mut self, content_length: u64) -> MockDownloader {
+            self.content_length = Some(content_length);
+            self
+        }
     }
 
     #[async_trait]
@@ -246,7 +849,7 @@ mod tests {
             o
         }
         fn content_length(&self) -> Option<u64> {
-            None
+            self.content_length
         }
         fn url(&self) -> Url {
             Url::parse("https://example.com/python.tar.gz").unwrap()
@@ -279,7 +882,7 @@ mod tests {
         {
             let mut downloader = HyperDownloader::new("https://example.com/").unwrap();
             rt.block_on(async {
-                download_to_path(&mut downloader, &download_dir, with_progress_bar).await
+                download_to_path(&mut downloader, &download_dir, with_progress_bar, None).await
             })
             .unwrap();
             assert_eq!(
@@ -288,13 +891,17 @@ mod tests {
             );
         }
         // Downloading a second time should skip download since already downloaded
+        // (only its size is compared)
         {
             let mut downloader = HyperDownloader::new("https://example.com/").unwrap();
             rt.block_on(async {
-                download_to_path(&mut downloader, &download_dir, with_progress_bar).await
+                download_to_path(&mut downloader, &download_dir, with_progress_bar, None).await
             })
             .unwrap();
-            assert_eq!(downloader.content_length(), None);
+            assert_eq!(
+                downloader.content_length().unwrap(),
+                fs::metadata(&downloaded_file).unwrap().len()
+            );
         }
     }
 
@@ -329,7 +936,7 @@ mod tests {
 
         futures::executor::block_on(async {
             mock_downloader.get().await.unwrap();
-            to_writer(&mut mock_downloader, &mut writer, false)
+            to_writer(&mut mock_downloader, &mut writer, None)
                 .await
                 .unwrap();
         });
@@ -347,4 +954,525 @@ mod tests {
             futures::executor::block_on(download_to_string(&mut mock_downloader, false)).unwrap();
         assert_eq!(downloaded, "Hello world");
     }
+
+    #[test]
+    fn file_downloader_directory_index() {
+        let mirror_dir = create_test_temp_dir!();
+        fs::create_dir_all(&mirror_dir).unwrap();
+        fs::write(mirror_dir.join("index.html"), "Hello world").unwrap();
+
+        let mut downloader =
+            FileDownloader::new(Url::from_directory_path(&mirror_dir).unwrap()).unwrap();
+        let downloaded =
+            futures::executor::block_on(download_to_string(&mut downloader, false)).unwrap();
+
+        assert_eq!(downloaded, "Hello world");
+        assert_eq!(downloader.content_length(), Some(11));
+        assert_eq!(downloader.filename().unwrap(), "index.html");
+    }
+
+    /// Local HTTP server accepting one connection per response (`None` never
+    /// answering), returning the lines (request line and headers) of each request.
+    fn local_http_server(
+        responses: Vec<Option<&'static str>>,
+    ) -> (
+        std::net::SocketAddr,
+        std::thread::JoinHandle<Vec<Vec<String>>>,
+    ) {
+        use std::io::{BufRead, BufReader};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            let mut requests = Vec::new();
+            let mut unanswered = Vec::new();
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request = Vec::new();
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    request.push(line.trim().to_string());
+                }
+                requests.push(request);
+                match response {
+                    Some(response) => stream.write_all(response.as_bytes()).unwrap(),
+                    None => unanswered.push(stream),
+                }
+            }
+            // Let the client time out instead of seeing the connections closed
+            if !unanswered.is_empty() {
+                std::thread::sleep(Duration::from_secs(3));
+            }
+            requests
+        });
+        (address, server)
+    }
+
+    const HELLO_WORLD_RESPONSE: &str =
+        "HTTP/1.1 200 OK\r\nConnection: close\r\nContent-Length: 11\r\n\r\nHello world";
+
+    #[test]
+    fn hyper_downloader_through_proxy() {
+        let (proxy_address, proxy) = local_http_server(vec![Some(HELLO_WORLD_RESPONSE)]);
+
+        let proxies = Proxies {
+            http: Some(
+                format!("http://user:secret@{}", proxy_address)
+                    .parse()
+                    .unwrap(),
+            ),
+            https: None,
+            no_proxy: Vec::new(),
+        };
+        let mut downloader =
+            HyperDownloader::new_allowing_http("http://mirror.invalid/python/index.html")
+                .unwrap()
+                .with_proxies(&proxies)
+                .unwrap();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let downloaded = rt
+            .block_on(download_to_string(&mut downloader, false))
+            .unwrap();
+        assert_eq!(downloaded, "Hello world");
+
+        let request = &proxy.join().unwrap()[0];
+        assert_eq!(
+            request[0],
+            "GET http://mirror.invalid/python/index.html HTTP/1.1"
+        );
+        assert!(request.iter().any(|header| {
+            // base64 of "user:secret"
+            header.eq_ignore_ascii_case("proxy-authorization: Basic dXNlcjpzZWNyZXQ=")
+        }));
+    }
+
+    // macOS refuses server certificates valid for more than 825 days
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    fn hyper_downloader_with_ca_bundle() {
+        use std::{net::TcpListener, path::Path, thread};
+
+        let fixtures_dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures")
+            .join("tls");
+        let identity = native_tls::Identity::from_pkcs8(
+            &fs::read(fixtures_dir.join("localhost.pem")).unwrap(),
+            &fs::read(fixtures_dir.join("localhost-key.pem")).unwrap(),
+        )
+        .unwrap();
+        let acceptor = native_tls::TlsAcceptor::new(identity).unwrap();
+
+        // Local server signed by the test CA, answering until its certificate is accepted
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!(
+            "https://localhost:{}/",
+            listener.local_addr().unwrap().port()
+        );
+        let server = thread::spawn(move || loop {
+            let (stream, _) = listener.accept().unwrap();
+            if let Ok(mut stream) = acceptor.accept(stream) {
+                let mut buffer = [0; 4096];
+                let _request_length = stream.read(&mut buffer).unwrap();
+                stream
+                    .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 11\r\n\r\nHello world")
+                    .unwrap();
+                break;
+            }
+        });
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        // Not retried
+        let mut downloader = HyperDownloader::new(&url).unwrap();
+        let err = rt.block_on(downloader.get()).unwrap_err();
+        assert!(!is_transient(&err));
+
+        let ca_bundle = CaBundle::new(
+            &crate::tls::TlsConfig {
+                ca_bundle: Some(fixtures_dir.join("ca.pem")),
+                export_ca_bundle: false,
+            },
+            |_| None,
+        )
+        .unwrap()
+        .unwrap();
+        let mut downloader = HyperDownloader::new(&url)
+            .unwrap()
+            .with_ca_bundle(&ca_bundle)
+            .unwrap();
+        let downloaded = rt
+            .block_on(download_to_string(&mut downloader, false))
+            .unwrap();
+        assert_eq!(downloaded, "Hello world");
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn hyper_downloader_range_request() {
+        let (address, server) = local_http_server(vec![Some(
+            "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes 6-10/11\r\n\
+            Content-Length: 5\r\n\r\nworld",
+        )]);
+
+        let url = format!("http://{}/python.tar.gz", address);
+        let mut downloader = HyperDownloader::new_allowing_http(&url).unwrap();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        assert!(rt.block_on(downloader.get_from(6)).unwrap());
+        assert_eq!(downloader.content_length(), Some(5));
+        let mut downloaded = Vec::new();
+        rt.block_on(to_writer(&mut downloader, &mut downloaded, None))
+            .unwrap();
+        assert_eq!(downloaded, b"world");
+        assert!(server.join().unwrap()[0]
+            .iter()
+            .any(|header| header.eq_ignore_ascii_case("range: bytes=6-")));
+    }
+
+    #[test]
+    fn hyper_downloader_unexpected_range() {
+        let (address, server) = local_http_server(vec![
+            Some(
+                "HTTP/1.1 206 Partial Content\r\nConnection: close\r\n\
+                Content-Range: bytes 3-10/11\r\nContent-Length: 8\r\n\r\nlo world",
+            ),
+            Some(HELLO_WORLD_RESPONSE),
+        ]);
+
+        let url = format!("http://{}/python.tar.gz", address);
+        let mut downloader = HyperDownloader::new_allowing_http(&url).unwrap();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        // Not the requested range: downloaded again from the start
+        assert!(!rt.block_on(downloader.get_from(6)).unwrap());
+        let mut downloaded = Vec::new();
+        rt.block_on(to_writer(&mut downloader, &mut downloaded, None))
+            .unwrap();
+        assert_eq!(downloaded, b"Hello world");
+
+        let requests = server.join().unwrap();
+        assert!(requests[0]
+            .iter()
+            .any(|header| header.eq_ignore_ascii_case("range: bytes=6-")));
+        assert!(!requests[1]
+            .iter()
+            .any(|header| header.to_lowercase().starts_with("range:")));
+    }
+
+    #[test]
+    fn hyper_downloader_follows_redirects() {
+        let (address, server) = local_http_server(vec![
+            Some("HTTP/1.1 302 Found\r\nConnection: close\r\nLocation: /final?b=2\r\n\r\n"),
+            Some(HELLO_WORLD_RESPONSE),
+        ]);
+
+        let url = format!("http://{}/start?a=1#fragment", address);
+        let mut downloader = HyperDownloader::new_allowing_http(&url).unwrap();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let downloaded = rt
+            .block_on(download_to_string(&mut downloader, false))
+            .unwrap();
+        assert_eq!(downloaded, "Hello world");
+        // The file name is still the requested one
+        assert_eq!(downloader.filename().unwrap(), "start");
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests[0][0], "GET /start?a=1 HTTP/1.1");
+        assert_eq!(requests[1][0], "GET /final?b=2 HTTP/1.1");
+    }
+
+    #[test]
+    fn download_to_path_resumes_from_final_url() {
+        let (address, server) = local_http_server(vec![
+            Some("HTTP/1.1 302 Found\r\nConnection: close\r\nLocation: /final\r\n\r\n"),
+            Some("HTTP/1.1 200 OK\r\nConnection: close\r\nContent-Length: 11\r\n\r\nHello"),
+            Some(
+                "HTTP/1.1 206 Partial Content\r\nConnection: close\r\n\
+                Content-Range: bytes 5-10/11\r\nContent-Length: 6\r\n\r\n world",
+            ),
+        ]);
+
+        let download_dir = create_test_temp_dir!();
+        let url = format!("http://{}/python.tar.gz", address);
+        let mut downloader = HyperDownloader::new_allowing_http(&url).unwrap();
+        downloader.initial_backoff = Duration::from_millis(10);
+        let expected_digest = Digest::sha256(HELLO_WORLD_SHA256);
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(download_to_path(
+            &mut downloader,
+            &download_dir,
+            false,
+            Some(&expected_digest),
+        ))
+        .unwrap();
+
+        // Still saved under the requested file name
+        let downloaded = fs::read_to_string(download_dir.join("python.tar.gz")).unwrap();
+        assert_eq!(downloaded, "Hello world");
+        assert_eq!(downloader.current_url().path(), "/final");
+        // Resumed without going through the redirection again
+        let requests = server.join().unwrap();
+        assert_eq!(requests[2][0], "GET /final HTTP/1.1");
+        assert!(requests[2]
+            .iter()
+            .any(|header| header.eq_ignore_ascii_case("range: bytes=5-")));
+    }
+
+    #[test]
+    fn hyper_downloader_too_many_redirects() {
+        let (address, server) = local_http_server(vec![
+            Some(
+                "HTTP/1.1 301 Moved Permanently\r\nConnection: close\r\nLocation: /loop\r\n\r\n"
+            );
+            MAX_REDIRECTS + 1
+        ]);
+
+        let url = format!("http://{}/loop", address);
+        let mut downloader = HyperDownloader::new_allowing_http(&url).unwrap();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let err = rt.block_on(downloader.get()).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<DownloadError>(),
+            Some(DownloadError::TooManyRedirects(_))
+        ));
+        assert_eq!(server.join().unwrap().len(), MAX_REDIRECTS + 1);
+    }
+
+    #[test]
+    fn hyper_downloader_retries_transient_errors() {
+        let (address, server) = local_http_server(vec![
+            Some("HTTP/1.1 503 Service Unavailable\r\nConnection: close\r\nContent-Length: 0\r\n\r\n"),
+            None,
+            Some(HELLO_WORLD_RESPONSE),
+        ]);
+
+        let url = format!("http://{}/python.tar.gz", address);
+        let mut downloader = HyperDownloader::new_allowing_http(&url).unwrap();
+        downloader.read_timeout = Duration::from_millis(500);
+        downloader.initial_backoff = Duration::from_millis(10);
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let downloaded = rt
+            .block_on(download_to_string(&mut downloader, false))
+            .unwrap();
+        assert_eq!(downloaded, "Hello world");
+        assert_eq!(server.join().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn download_to_path_resumes_interrupted_response() {
+        let (address, server) = local_http_server(vec![
+            Some("HTTP/1.1 200 OK\r\nConnection: close\r\nContent-Length: 11\r\n\r\nHello"),
+            Some(
+                "HTTP/1.1 206 Partial Content\r\nConnection: close\r\n\
+                Content-Range: bytes 5-10/11\r\nContent-Length: 6\r\n\r\n world",
+            ),
+        ]);
+
+        let download_dir = create_test_temp_dir!();
+        let url = format!("http://{}/python.tar.gz", address);
+        let mut downloader = HyperDownloader::new_allowing_http(&url).unwrap();
+        downloader.initial_backoff = Duration::from_millis(10);
+        let expected_digest = Digest::sha256(HELLO_WORLD_SHA256);
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(download_to_path(
+            &mut downloader,
+            &download_dir,
+            false,
+            Some(&expected_digest),
+        ))
+        .unwrap();
+
+        let downloaded = fs::read_to_string(download_dir.join("python.tar.gz")).unwrap();
+        assert_eq!(downloaded, "Hello world");
+        let requests = server.join().unwrap();
+        assert!(requests[1]
+            .iter()
+            .any(|header| header.eq_ignore_ascii_case("range: bytes=5-")));
+    }
+
+    #[test]
+    fn hyper_downloader_gives_up() {
+        let (address, server) = local_http_server(vec![None, None]);
+
+        let url = format!("http://{}/python.tar.gz", address);
+        let mut downloader = HyperDownloader::new_allowing_http(&url).unwrap();
+        downloader.read_timeout = Duration::from_millis(500);
+        downloader.initial_backoff = Duration::from_millis(10);
+        downloader.max_attempts = 2;
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let err = rt.block_on(downloader.get()).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<DownloadError>(),
+            Some(DownloadError::Timeout(_))
+        ));
+        assert_eq!(server.join().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn hyper_downloader_does_not_retry_not_found() {
+        let (address, server) = local_http_server(vec![Some(
+            "HTTP/1.1 404 Not Found\r\nConnection: close\r\nContent-Length: 0\r\n\r\n",
+        )]);
+
+        let url = format!("http://{}/python.tar.gz", address);
+        let mut downloader = HyperDownloader::new_allowing_http(&url).unwrap();
+        downloader.initial_backoff = Duration::from_millis(10);
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let err = rt.block_on(downloader.get()).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<DownloadError>(),
+            Some(DownloadError::HttpStatus(_, StatusCode::NOT_FOUND))
+        ));
+        assert_eq!(server.join().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn hyper_downloader_refuses_plain_http() {
+        let mut downloader = HyperDownloader::new("http://example.com/").unwrap();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let err = rt.block_on(downloader.get()).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<DownloadError>(),
+            Some(DownloadError::UntrustedHttp(_))
+        ));
+    }
+
+    // SHA-256 of "Hello world"
+    const HELLO_WORLD_SHA256: &str =
+        "64ec88ca00b268e5ba1a35678a1b5316d212f4f366b2477232534a8aeca37f3c";
+
+    fn hello_world_downloader() -> MockDownloader {
+        MockDownloader::new(vec![Ok(Bytes::from("Hello ")), Ok(Bytes::from("world"))])
+    }
+
+    #[test]
+    fn download_to_path_checksum_match() {
+        let download_dir = create_test_temp_dir!();
+        let expected_digest = Digest::sha256(HELLO_WORLD_SHA256);
+
+        futures::executor::block_on(download_to_path(
+            &mut hello_world_downloader(),
+            &download_dir,
+            false,
+            Some(&expected_digest),
+        ))
+        .unwrap();
+
+        let downloaded = fs::read_to_string(download_dir.join("python.tar.gz")).unwrap();
+        assert_eq!(downloaded, "Hello world");
+    }
+
+    #[test]
+    fn download_to_path_checksum_mismatch() {
+        let download_dir = create_test_temp_dir!();
+        let expected_digest = Digest::md5("00000000000000000000000000000000");
+
+        let err = futures::executor::block_on(download_to_path(
+            &mut hello_world_downloader(),
+            &download_dir,
+            false,
+            Some(&expected_digest),
+        ))
+        .unwrap_err();
+
+        assert!(matches!(
+            err.downcast_ref::<ChecksumError>(),
+            Some(ChecksumError::Mismatch { .. })
+        ));
+        assert!(!download_dir.join("python.tar.gz").exists());
+    }
+
+    #[test]
+    fn download_to_path_existing_file_corrupted() {
+        let download_dir = create_test_temp_dir!();
+        fs::create_dir_all(&download_dir).unwrap();
+        fs::write(download_dir.join("python.tar.gz"), "Corrupted").unwrap();
+        let expected_digest = Digest::sha256(HELLO_WORLD_SHA256);
+
+        futures::executor::block_on(download_to_path(
+            &mut hello_world_downloader(),
+            &download_dir,
+            false,
+            Some(&expected_digest),
+        ))
+        .unwrap();
+
+        let downloaded = fs::read_to_string(download_dir.join("python.tar.gz")).unwrap();
+        assert_eq!(downloaded, "Hello world");
+    }
+
+    /// Source directory containing `python.tar.gz` ("Hello world").
+    fn hello_world_file_downloader(source_dir: &Path) -> FileDownloader {
+        fs::create_dir_all(source_dir).unwrap();
+        let source = source_dir.join("python.tar.gz");
+        fs::write(&source, "Hello world").unwrap();
+        FileDownloader::new(Url::from_file_path(&source).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn download_to_path_resumes_partial_file() {
+        let test_dir = create_test_temp_dir!();
+        let download_dir = test_dir.join("download");
+        fs::create_dir_all(&download_dir).unwrap();
+        fs::write(download_dir.join("python.tar.gz.part"), "Hello ").unwrap();
+        let expected_digest = Digest::sha256(HELLO_WORLD_SHA256);
+
+        let mut downloader = hello_world_file_downloader(&test_dir.join("source"));
+        futures::executor::block_on(download_to_path(
+            &mut downloader,
+            &download_dir,
+            false,
+            Some(&expected_digest),
+        ))
+        .unwrap();
+
+        // Only the missing part was read
+        assert_eq!(downloader.content_length(), Some(5));
+        let downloaded = fs::read_to_string(download_dir.join("python.tar.gz")).unwrap();
+        assert_eq!(downloaded, "Hello world");
+        assert!(!download_dir.join("python.tar.gz.part").exists());
+    }
+
+    #[test]
+    fn download_to_path_interrupted() {
+        let download_dir = create_test_temp_dir!();
+
+        let err = futures::executor::block_on(download_to_path(
+            &mut hello_world_downloader().with_content_length(100),
+            &download_dir,
+            false,
+            None,
+        ))
+        .unwrap_err();
+
+        assert!(matches!(
+            err.downcast_ref::<DownloadError>(),
+            Some(DownloadError::Incomplete(_, 11, 100))
+        ));
+        // Kept to be resumed, but never used as a complete download
+        assert!(!download_dir.join("python.tar.gz").exists());
+        assert!(download_dir.join("python.tar.gz.part").exists());
+    }
+
+    #[test]
+    fn download_to_path_existing_file_truncated() {
+        let test_dir = create_test_temp_dir!();
+        let download_dir = test_dir.join("download");
+        fs::create_dir_all(&download_dir).unwrap();
+        fs::write(download_dir.join("python.tar.gz"), "Hello").unwrap();
+
+        futures::executor::block_on(download_to_path(
+            &mut hello_world_file_downloader(&test_dir.join("source")),
+            &download_dir,
+            false,
+            None,
+        ))
+        .unwrap();
+
+        let downloaded = fs::read_to_string(download_dir.join("python.tar.gz")).unwrap();
+        assert_eq!(downloaded, "Hello world");
+    }
 }
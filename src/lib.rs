@@ -1,12 +1,21 @@
 mod cache;
+mod check;
+mod checksum;
 pub mod commands;
+mod config;
 pub mod constants;
 mod dir_monitor;
 mod download;
+mod mirrors;
 mod os;
+mod profile;
+mod proxy;
 pub mod shim;
+mod signature;
+mod tls;
 mod toolchain;
 pub mod utils;
+mod venv;
 
 pub use anyhow::Result;
 pub use structopt::StructOpt;
@@ -3,7 +3,6 @@
 // FIXME: Gracefully handle errors that bubble to main
 // FIXME: Add -vvv flag to control log level
 // FIXME: Increase test coverage
-// FIXME: Implement checksum/signature validation
 
 use std::{
     env,
@@ -61,53 +60,62 @@ pub fn no_shim_execution() -> Result<()> {
 
     if let Some(subcommand) = opt.subcommand {
         match subcommand {
-            Command::List => commands::list::run()?,
+            Command::List {
+                available,
+                version,
+                pre,
+            } => commands::list::run(available, version, pre)?,
             Command::Path { version } => commands::path::run(version)?,
             Command::Version { version } => commands::version::run(version)?,
             Command::Select(version_or_path) => commands::select::run(version_or_path)?,
+            Command::Global { version, unset } => commands::global::run(version, unset)?,
             Command::Install {
                 release,
-                from_version,
+                from_versions,
+                from_source,
                 force,
                 install_extra_packages,
                 select,
+                skip_verify,
+                skip_signature,
+                pre,
+                prebuilt,
+                profile,
+                jobs,
+                ignore_missing_deps,
             } => {
                 commands::install::run(
                     release,
-                    from_version,
+                    from_versions,
+                    from_source,
                     force,
                     &install_extra_packages,
                     select,
+                    skip_verify,
+                    skip_signature,
+                    pre,
+                    prebuilt,
+                    profile,
+                    jobs,
+                    ignore_missing_deps,
                 )?;
             }
+            Command::Uninstall { version } => commands::uninstall::run(version)?,
+            Command::Rescan => commands::rescan::run()?,
+            Command::Doctor => commands::doctor::run()?,
+            Command::Check { version } => commands::check::run(version)?,
+            Command::Venv(venv_command) => commands::venv::run(venv_command)?,
             Command::Run { version, command } => commands::run::run(version, &command)?,
+            Command::Which { command } => commands::which::run(command)?,
             Command::Setup { shell } => commands::setup::run(shell)?,
             #[cfg(feature = "self-update")]
-            Command::Update => update()?,
+            Command::Update => commands::update::run()?,
         }
     }
 
     Ok(())
 }
 
-#[cfg(feature = "self-update")]
-fn update() -> Result<()> {
-    let status = self_update::backends::github::Update::configure()
-        .repo_owner("nbigaouette")
-        .repo_name(EXECUTABLE_NAME)
-        .bin_name(&format!(
-            "{}{}",
-            EXECUTABLE_NAME,
-            std::env::consts::EXE_SUFFIX
-        ))
-        .show_download_progress(true)
-        .current_version(self_update::cargo_crate_version!())
-        .build()?
-        .update()?;
-    println!("Update status: `{}`!", status.version());
-    Ok(())
-}
-
 pub fn python_shim(command: &str) -> Result<()> {
     env_logger::init();
 
@@ -0,0 +1,315 @@
+//! Locations of the Python index and archives, configurable to use a (possibly
+//! local) mirror instead of python.org.
+
+use std::env;
+
+use serde::Deserialize;
+use url::Url;
+
+use crate::{
+    config::Config,
+    constants::{
+        EXECUTABLE_NAME, GET_PIP_URL, PYPY_CHECKSUMS_URL, PYPY_VERSIONS_URL,
+        PYTHON_BUILD_STANDALONE_RELEASES_URL, PYTHON_FTP_URL, PYTHON_RELEASE_PAGE_URL,
+        PYTHON_SOURCE_INDEX_URL, PYTHON_WINDOWS_INDEX_URL,
+    },
+    download::{DownloadError, Downloader, FileDownloader, HyperDownloader},
+    proxy::Proxies,
+    tls::CaBundle,
+    utils::directory::{PycorsHomeProviderTrait, PycorsPathsProvider},
+    Result,
+};
+
+/// `[mirrors]` table of the configuration file.
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MirrorsConfig {
+    /// Page listing the source archives
+    pub source_index: Option<Url>,
+    /// Page listing the Windows pre-built archives
+    pub windows_index: Option<Url>,
+    /// Directory of the release pages, publishing the archives' checksums
+    pub release_page: Option<Url>,
+    /// Directory replacing `https://www.python.org/ftp/python/` in archives URLs
+    pub ftp: Option<Url>,
+    /// The `get-pip.py` script
+    pub get_pip: Option<Url>,
+    /// List of the python-build-standalone releases
+    pub standalone_releases: Option<Url>,
+    /// List of the PyPy releases (`versions.json`)
+    pub pypy_versions: Option<Url>,
+    /// Page (or `sha256sum` output) listing the PyPy archives' checksums
+    pub pypy_checksums: Option<Url>,
+    /// Hosts (like `mirror.internal` or `mirror.internal:8080`) from which
+    /// downloading over plain HTTP is allowed
+    pub trusted_hosts: Vec<String>,
+}
+
+/// Locations to download from, the defaults being overridden by the configuration
+/// file, itself overridden by environment variables (like `HYGEIA_SOURCE_INDEX_URL`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mirrors {
+    pub source_index: Url,
+    pub windows_index: Url,
+    pub release_page: Url,
+    pub ftp: Url,
+    pub get_pip: Url,
+    pub standalone_releases: Url,
+    pub pypy_versions: Url,
+    pub pypy_checksums: Url,
+    pub trusted_hosts: Vec<String>,
+    pub proxies: Proxies,
+    pub ca_bundle: Option<CaBundle>,
+}
+
+/// Environment variable overriding a setting, for example `HYGEIA_FTP_URL`.
+pub fn env_variable(setting: &str) -> String {
+    format!("{}_{}", EXECUTABLE_NAME.to_uppercase(), setting)
+}
+
+impl Mirrors {
+    pub fn load<P>(paths_provider: &PycorsPathsProvider<P>) -> Result<Mirrors>
+    where
+        P: PycorsHomeProviderTrait,
+    {
+        let config = Config::load(paths_provider)?;
+        let env = |name: &str| env::var(name).ok();
+        let mut mirrors = Mirrors::new(&config.mirrors, env)?;
+        mirrors.proxies = Proxies::new(&config.proxy, env)?;
+        mirrors.ca_bundle = CaBundle::new(&config.tls, env)?;
+        Ok(mirrors)
+    }
+
+    /// Merge the configuration with the environment (looked up with `env`).
+    pub fn new<F>(config: &MirrorsConfig, env: F) -> Result<Mirrors>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let setting = |env_setting: &str, configured: &Option<Url>, default: &str| {
+            let url = match env(&env_variable(env_setting)).filter(|value| !value.is_empty()) {
+                Some(value) => value.parse()?,
+                None => match configured {
+                    Some(url) => url.clone(),
+                    None => default.parse()?,
+                },
+            };
+            Ok::<Url, anyhow::Error>(url)
+        };
+
+        let trusted_hosts = match env(&env_variable("TRUSTED_HOSTS")) {
+            Some(value) => value
+                .split(',')
+                .map(str::trim)
+                .filter(|host| !host.is_empty())
+                .map(String::from)
+                .collect(),
+            None => config.trusted_hosts.clone(),
+        };
+
+        let mirrors = Mirrors {
+            source_index: as_directory(setting(
+                "SOURCE_INDEX_URL",
+                &config.source_index,
+                PYTHON_SOURCE_INDEX_URL,
+            )?),
+            windows_index: as_directory(setting(
+                "WINDOWS_INDEX_URL",
+                &config.windows_index,
+                PYTHON_WINDOWS_INDEX_URL,
+            )?),
+            release_page: as_directory(setting(
+                "RELEASE_PAGE_URL",
+                &config.release_page,
+                PYTHON_RELEASE_PAGE_URL,
+            )?),
+            ftp: as_directory(setting("FTP_URL", &config.ftp, PYTHON_FTP_URL)?),
+            get_pip: setting("GET_PIP_URL", &config.get_pip, GET_PIP_URL)?,
+            standalone_releases: setting(
+                "STANDALONE_RELEASES_URL",
+                &config.standalone_releases,
+                PYTHON_BUILD_STANDALONE_RELEASES_URL,
+            )?,
+            pypy_versions: setting(
+                "PYPY_VERSIONS_URL",
+                &config.pypy_versions,
+                PYPY_VERSIONS_URL,
+            )?,
+            pypy_checksums: setting(
+                "PYPY_CHECKSUMS_URL",
+                &config.pypy_checksums,
+                PYPY_CHECKSUMS_URL,
+            )?,
+            trusted_hosts,
+            proxies: Proxies::default(),
+            ca_bundle: None,
+        };
+        log::debug!("Mirrors: {:?}", mirrors);
+
+        Ok(mirrors)
+    }
+
+    /// Replace python.org's download directory by the mirror's, if any.
+    pub fn rewrite(&self, url: &Url) -> Result<Url> {
+        match url.as_str().strip_prefix(PYTHON_FTP_URL) {
+            Some(relative) if self.ftp.as_str() != PYTHON_FTP_URL => {
+                let rewritten = self.ftp.join(relative)?;
+                log::debug!("Using mirror {} for {}", rewritten, url);
+                Ok(rewritten)
+            }
+            _ => Ok(url.clone()),
+        }
+    }
+
+    /// Use the configured proxies and certificate authorities.
+    fn configure(&self, downloader: HyperDownloader) -> Result<HyperDownloader> {
+        let downloader = downloader.with_proxies(&self.proxies)?;
+        match &self.ca_bundle {
+            Some(ca_bundle) => downloader.with_ca_bundle(ca_bundle),
+            None => Ok(downloader),
+        }
+    }
+
+    fn is_trusted(&self, url: &Url) -> bool {
+        let host = url.host_str().unwrap_or_default();
+        let host_and_port = match url.port() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host.to_string(),
+        };
+        self.trusted_hosts
+            .iter()
+            .any(|trusted| *trusted == host || *trusted == host_and_port)
+    }
+
+    /// A downloader for `url` (after rewriting it), depending on its scheme.
+    ///
+    /// Plain HTTP is only allowed for trusted hosts.
+    pub fn downloader(&self, url: &Url) -> Result<Box<dyn Downloader + Send>> {
+        let url = self.rewrite(url)?;
+        match url.scheme() {
+            "https" => Ok(Box::new(self.configure(HyperDownloader::new(&url)?)?)),
+            "http" if self.is_trusted(&url) => Ok(Box::new(
+                self.configure(HyperDownloader::new_allowing_http(&url)?)?,
+            )),
+            "http" => Err(DownloadError::UntrustedHttp(url).into()),
+            "file" => Ok(Box::new(FileDownloader::new(url)?)),
+            _ => Err(DownloadError::UnsupportedScheme(url).into()),
+        }
+    }
+}
+
+/// Make sure a URL ends with `/`, so it can be joined with relative paths.
+fn as_directory(mut url: Url) -> Url {
+    if !url.path().ends_with('/') {
+        let path = format!("{}/", url.path());
+        url.set_path(&path);
+    }
+    url
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn env_from(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(name, value)| (env_variable(name), value.to_string()))
+            .collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    #[test]
+    fn defaults() {
+        let mirrors = Mirrors::new(&MirrorsConfig::default(), env_from(&[])).unwrap();
+        assert_eq!(mirrors.source_index.as_str(), PYTHON_SOURCE_INDEX_URL);
+        assert_eq!(mirrors.ftp.as_str(), PYTHON_FTP_URL);
+        assert_eq!(mirrors.get_pip.as_str(), GET_PIP_URL);
+        assert!(mirrors.trusted_hosts.is_empty());
+
+        let url: Url = "https://www.python.org/ftp/python/3.8.1/Python-3.8.1.tgz"
+            .parse()
+            .unwrap();
+        assert_eq!(mirrors.rewrite(&url).unwrap(), url);
+    }
+
+    #[test]
+    fn environment_overrides_config() {
+        let config = MirrorsConfig {
+            ftp: Some("http://mirror.internal/python".parse().unwrap()),
+            get_pip: Some("http://mirror.internal/get-pip.py".parse().unwrap()),
+            trusted_hosts: vec!["mirror.internal".into()],
+            ..MirrorsConfig::default()
+        };
+        let mirrors = Mirrors::new(
+            &config,
+            env_from(&[("FTP_URL", "file:///srv/mirror/ftp/python/")]),
+        )
+        .unwrap();
+
+        assert_eq!(mirrors.ftp.as_str(), "file:///srv/mirror/ftp/python/");
+        assert_eq!(
+            mirrors.get_pip.as_str(),
+            "http://mirror.internal/get-pip.py"
+        );
+        let url: Url = "https://www.python.org/ftp/python/3.8.1/Python-3.8.1.tgz"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            mirrors.rewrite(&url).unwrap().as_str(),
+            "file:///srv/mirror/ftp/python/3.8.1/Python-3.8.1.tgz"
+        );
+    }
+
+    #[test]
+    fn directories_end_with_slash() {
+        let config = MirrorsConfig {
+            ftp: Some("https://mirror.internal/python".parse().unwrap()),
+            ..MirrorsConfig::default()
+        };
+        let mirrors = Mirrors::new(&config, env_from(&[])).unwrap();
+        let url: Url = "https://www.python.org/ftp/python/3.8.1/Python-3.8.1.tgz"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            mirrors.rewrite(&url).unwrap().as_str(),
+            "https://mirror.internal/python/3.8.1/Python-3.8.1.tgz"
+        );
+    }
+
+    #[test]
+    fn plain_http_only_from_trusted_hosts() {
+        let mirrors = Mirrors::new(
+            &MirrorsConfig::default(),
+            env_from(&[("TRUSTED_HOSTS", "mirror.internal, other.internal:8080")]),
+        )
+        .unwrap();
+
+        for url in &[
+            "http://mirror.internal/python/index.html",
+            "http://other.internal:8080/python/index.html",
+            "https://untrusted.example.com/index.html",
+            "file:///srv/mirror/index.html",
+        ] {
+            assert!(mirrors.downloader(&url.parse().unwrap()).is_ok(), "{}", url);
+        }
+        for url in &[
+            "http://untrusted.example.com/index.html",
+            "http://other.internal/python/index.html",
+        ] {
+            let err = mirrors.downloader(&url.parse().unwrap()).err().unwrap();
+            assert!(
+                matches!(
+                    err.downcast_ref::<DownloadError>(),
+                    Some(DownloadError::UntrustedHttp(_))
+                ),
+                "{}",
+                url
+            );
+        }
+        assert!(mirrors
+            .downloader(&"ftp://ftp.example.com/python.tgz".parse().unwrap())
+            .is_err());
+    }
+}
@@ -2,10 +2,15 @@ use std::path::PathBuf;
 
 use semver::Version;
 
-use crate::{utils::directory::PycorsPathsProviderFromEnv, Result};
+use crate::{
+    toolchain::implementation::Implementation, utils::directory::PycorsPathsProviderFromEnv, Result,
+};
 
-pub fn paths_to_prepends(version: &Version) -> Result<Vec<PathBuf>> {
-    let bin_dir = PycorsPathsProviderFromEnv::new().bin_dir(version);
+pub fn paths_to_prepends(
+    implementation: Implementation,
+    version: &Version,
+) -> Result<Vec<PathBuf>> {
+    let bin_dir = PycorsPathsProviderFromEnv::new().implementation_bin_dir(implementation, version);
 
     #[allow(clippy::redundant_clone)]
     #[allow(unused_mut)]
@@ -18,3 +23,46 @@ pub fn paths_to_prepends(version: &Version) -> Result<Vec<PathBuf>> {
 
     Ok(paths)
 }
+
+/// Target triple of the python-build-standalone archives runnable on this platform.
+pub fn standalone_target_triple() -> Option<&'static str> {
+    if cfg!(all(
+        target_os = "linux",
+        target_arch = "x86_64",
+        target_env = "gnu"
+    )) {
+        Some("x86_64-unknown-linux-gnu")
+    } else if cfg!(all(
+        target_os = "linux",
+        target_arch = "x86_64",
+        target_env = "musl"
+    )) {
+        Some("x86_64-unknown-linux-musl")
+    } else if cfg!(all(
+        target_os = "linux",
+        target_arch = "aarch64",
+        target_env = "gnu"
+    )) {
+        Some("aarch64-unknown-linux-gnu")
+    } else {
+        None
+    }
+}
+
+/// Platform and architecture (as named in PyPy's `versions.json`) of the PyPy archives
+/// runnable on this platform.
+///
+/// Windows is not supported: its archives are `.zip` files.
+pub fn pypy_platform() -> Option<(&'static str, &'static str)> {
+    if cfg!(all(target_os = "linux", target_arch = "x86_64")) {
+        Some(("linux", "x64"))
+    } else if cfg!(all(target_os = "linux", target_arch = "aarch64")) {
+        Some(("linux", "aarch64"))
+    } else if cfg!(all(target_os = "macos", target_arch = "x86_64")) {
+        Some(("darwin", "x64"))
+    } else if cfg!(all(target_os = "macos", target_arch = "aarch64")) {
+        Some(("darwin", "arm64"))
+    } else {
+        None
+    }
+}
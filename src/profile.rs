@@ -0,0 +1,183 @@
+//! Named build profiles, selecting how Python is compiled from source.
+
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{config::Config, Result};
+
+/// Name of the profile used when none is given.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Flags appended to the ones hygeia sets instead of replacing them.
+const APPENDED_FLAGS: &[&str] = &["CFLAGS", "CPPFLAGS", "LDFLAGS"];
+
+#[derive(Debug, Error)]
+pub enum ProfileError {
+    #[error("Unknown build profile {0:?} (available: {1})")]
+    Unknown(String, String),
+}
+
+/// `[profiles.<name>]` table of the configuration file: how to build from source.
+///
+/// The profile adds to what is always needed (`--prefix`, the macOS include paths,
+/// the runtime library path on Linux). Defining `[profiles.default]` replaces the
+/// default profile.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BuildProfile {
+    /// Arguments added to `./configure`, for example `--with-pydebug`
+    pub configure_args: Vec<String>,
+    /// Environment variables of the build steps. `CFLAGS`, `CPPFLAGS` and `LDFLAGS`
+    /// are appended to the flags set by hygeia, others are set as given.
+    pub env: BTreeMap<String, String>,
+    /// Targets of the `make` step, for example `profile-opt` (default: `make`'s
+    /// default target)
+    pub make_targets: Vec<String>,
+    /// Build the shared library (`--enable-shared`, Python 3.3 and later)
+    pub shared: bool,
+    /// Enable optimizations (`--enable-optimizations`), as if `--release` was given
+    pub optimizations: bool,
+}
+
+impl Default for BuildProfile {
+    fn default() -> BuildProfile {
+        BuildProfile {
+            configure_args: Vec::new(),
+            env: BTreeMap::new(),
+            make_targets: Vec::new(),
+            shared: true,
+            optimizations: false,
+        }
+    }
+}
+
+impl BuildProfile {
+    /// Profile `name` of the configuration, or the default profile.
+    pub fn from_config(config: &Config, name: Option<&str>) -> Result<BuildProfile> {
+        let name = name.unwrap_or(DEFAULT_PROFILE);
+        match config.profiles.get(name) {
+            Some(profile) => Ok(profile.clone()),
+            None if name == DEFAULT_PROFILE => Ok(BuildProfile::default()),
+            None => {
+                let mut available: Vec<&str> = config.profiles.keys().map(String::as_str).collect();
+                if !config.profiles.contains_key(DEFAULT_PROFILE) {
+                    available.insert(0, DEFAULT_PROFILE);
+                }
+                Err(ProfileError::Unknown(name.to_string(), available.join(", ")).into())
+            }
+        }
+    }
+
+    /// Environment of the build: `required` (set by hygeia) merged with the profile's.
+    pub fn environment(&self, required: Vec<(&str, String)>) -> Vec<(String, String)> {
+        let mut environment: Vec<(String, String)> = required
+            .into_iter()
+            .map(|(name, value)| match self.env.get(name) {
+                Some(extra) if APPENDED_FLAGS.contains(&name) => {
+                    let flags = [value.as_str(), extra.as_str()]
+                        .iter()
+                        .filter(|flags| !flags.is_empty())
+                        .copied()
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    (name.to_string(), flags)
+                }
+                Some(value) => (name.to_string(), value.clone()),
+                None => (name.to_string(), value),
+            })
+            .collect();
+        for (name, value) in &self.env {
+            if !environment.iter().any(|(set, _)| set == name) {
+                environment.push((name.clone(), value.clone()));
+            }
+        }
+        environment
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_with(profiles: &[(&str, BuildProfile)]) -> Config {
+        Config {
+            profiles: profiles
+                .iter()
+                .map(|(name, profile)| (name.to_string(), profile.clone()))
+                .collect(),
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn default_profile() {
+        let profile = BuildProfile::from_config(&Config::default(), None).unwrap();
+        assert_eq!(profile, BuildProfile::default());
+        assert!(profile.shared);
+        assert!(!profile.optimizations);
+
+        let custom_default = BuildProfile {
+            optimizations: true,
+            ..BuildProfile::default()
+        };
+        let config = config_with(&[(DEFAULT_PROFILE, custom_default.clone())]);
+        assert_eq!(
+            BuildProfile::from_config(&config, None).unwrap(),
+            custom_default
+        );
+    }
+
+    #[test]
+    fn named_profile() {
+        let debug = BuildProfile {
+            configure_args: vec!["--with-pydebug".into()],
+            ..BuildProfile::default()
+        };
+        let config = config_with(&[("debug", debug.clone())]);
+        assert_eq!(
+            BuildProfile::from_config(&config, Some("debug")).unwrap(),
+            debug
+        );
+
+        let err = BuildProfile::from_config(&config, Some("lto")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unknown build profile \"lto\" (available: default, debug)"
+        );
+    }
+
+    #[test]
+    fn environment() {
+        let profile = BuildProfile {
+            env: vec![
+                ("CFLAGS", "-O0 -g"),
+                ("LDFLAGS", "-L/opt/openssl/lib"),
+                ("LD_RUN_PATH", "/opt/lib"),
+                ("MAKEFLAGS", "-j8"),
+            ]
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect(),
+            ..BuildProfile::default()
+        };
+        let environment = profile.environment(vec![
+            ("CFLAGS", "-I/usr/include".to_string()),
+            ("CPPFLAGS", String::new()),
+            ("LDFLAGS", String::new()),
+            ("LD_RUN_PATH", "/home/user/.hygeia/lib".to_string()),
+        ]);
+        let expected: Vec<(String, String)> = vec![
+            ("CFLAGS", "-I/usr/include -O0 -g"),
+            ("CPPFLAGS", ""),
+            ("LDFLAGS", "-L/opt/openssl/lib"),
+            ("LD_RUN_PATH", "/opt/lib"),
+            ("MAKEFLAGS", "-j8"),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+        assert_eq!(environment, expected);
+    }
+}
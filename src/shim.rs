@@ -9,9 +9,12 @@ use crate::{
     constants::EXECUTABLE_NAME,
     dir_monitor::DirectoryMonitor,
     os,
-    toolchain::{installed::InstalledToolchain, CompatibleToolchainBuilder},
+    toolchain::{
+        implementation::Implementation, installed::InstalledToolchain, CompatibleToolchainBuilder,
+    },
     utils,
     utils::directory::PycorsPathsProviderFromEnv,
+    venv,
 };
 
 #[derive(Debug, Error)]
@@ -24,23 +27,56 @@ pub fn run<S>(command: &str, arguments: &[S]) -> Result<()>
 where
     S: AsRef<str> + std::convert::AsRef<std::ffi::OsStr> + std::fmt::Debug,
 {
+    match compatible_toolchain(command)? {
+        Some(compatible_toolchain) => run_with(&compatible_toolchain, command, arguments),
+        None => {
+            log::error!("No Python interpreter found at all. Please install at least one!");
+            Err(ShimError::MissingInterpreter(command.to_string()).into())
+        }
+    }
+}
+
+/// Toolchain a shim named `command` would use.
+pub fn compatible_toolchain(command: &str) -> Result<Option<InstalledToolchain>> {
     // Try to detect if a command with a version appended is run, for example 'python2.7'
     // or 'python3'.
     let command_version = extract_major_version_from_executable_name(command);
 
-    let compatible_toolchain = CompatibleToolchainBuilder::new()
+    CompatibleToolchainBuilder::new()
         .load_from_file()
         .overwrite(command_version)
+        .implementation(Implementation::of_command(command))
         .pick_latest_if_none_found()
-        .compatible_version(PycorsPathsProviderFromEnv::new())?;
+        .compatible_version(PycorsPathsProviderFromEnv::new())
+}
 
-    match compatible_toolchain {
-        Some(compatible_toolchain) => run_with(&compatible_toolchain, command, arguments),
-        None => {
-            log::error!("No Python interpreter found at all. Please install at least one!");
-            Err(ShimError::MissingInterpreter(command.to_string()).into())
-        }
+/// Directories prepended to `PATH` when running a command with `toolchain`.
+fn toolchain_paths(toolchain: &InstalledToolchain) -> Result<Vec<PathBuf>> {
+    let mut dirs = os::paths_to_prepends(toolchain.implementation, &toolchain.version)?;
+    // A virtual environment's executables (like `pip`) must shadow the ones of its base
+    // interpreter. Other directories, like `/usr/bin`, must not shadow the whole `PATH`.
+    if venv::is_venv_bin_dir(&toolchain.location) && !dirs.contains(&toolchain.location) {
+        dirs.insert(0, toolchain.location.clone());
     }
+    Ok(dirs)
+}
+
+/// Absolute path of `command` in the directories `run_with()` prepends to `PATH`.
+pub fn find_command(toolchain: &InstalledToolchain, command: &str) -> Result<Option<PathBuf>> {
+    let mut dirs = toolchain_paths(toolchain)?;
+    // The toolchain might not be installed by us (for example found in `PATH`).
+    if !dirs.contains(&toolchain.location) {
+        dirs.push(toolchain.location.clone());
+    }
+    let filenames = [
+        command.to_string(),
+        format!("{}{}", command, env::consts::EXE_SUFFIX),
+    ];
+
+    Ok(dirs
+        .iter()
+        .flat_map(|dir| filenames.iter().map(move |filename| dir.join(filename)))
+        .find(|path| path.is_file()))
 }
 
 pub fn run_with<S>(toolchain: &InstalledToolchain, command: &str, arguments: &[S]) -> Result<()>
@@ -57,7 +93,7 @@ where
         }
     };
     let new_paths: Vec<PathBuf> = {
-        let mut tmp = os::paths_to_prepends(&toolchain.version)?;
+        let mut tmp = toolchain_paths(toolchain)?;
         tmp.extend_from_slice(&current_paths);
         // Delete the shims path from the list
         // This should prevent calling our shims by accident.
@@ -0,0 +1,220 @@
+use std::{
+    fs::{read_to_string, File},
+    io::BufReader,
+    path::{Path, PathBuf},
+};
+
+use pgp::{
+    types::{KeyId, KeyTrait},
+    Deserializable, SignedPublicKey, StandaloneSignature,
+};
+use thiserror::Error;
+
+use crate::{
+    constants::{KEYRING_FILENAME, KEYRING_FILENAME_CONTENT},
+    utils::directory::{PycorsHomeProviderTrait, PycorsPathsProvider},
+    Result,
+};
+
+const PUBLIC_KEY_BLOCK_BEGIN: &str = "-----BEGIN PGP PUBLIC KEY BLOCK-----";
+const PUBLIC_KEY_BLOCK_END: &str = "-----END PGP PUBLIC KEY BLOCK-----";
+
+#[derive(Debug, Error)]
+pub enum SignatureError {
+    #[error("No OpenPGP public key found in keyring {0}")]
+    EmptyKeyring(String),
+    #[error("Failed to parse OpenPGP data: {0}")]
+    Parse(String),
+    #[error("Signature of {0:?} was made with key {1}, which is not in the keyring")]
+    UnknownKey(PathBuf, String),
+    #[error("Invalid signature for {0:?}: {1}")]
+    BadSignature(PathBuf, String),
+}
+
+/// OpenPGP public keys trusted to sign downloaded archives.
+pub struct Keyring {
+    keys: Vec<SignedPublicKey>,
+}
+
+impl Keyring {
+    /// Load the keyring from `${HYGEIA_HOME}`, falling back to the bundled one.
+    pub fn load<P>(paths_provider: &PycorsPathsProvider<P>) -> Result<Keyring>
+    where
+        P: PycorsHomeProviderTrait,
+    {
+        let keyring_file = paths_provider.keyring_file();
+        if keyring_file.exists() {
+            log::debug!("Using keyring {:?}", keyring_file);
+            let armored = read_to_string(&keyring_file)?;
+            Keyring::from_armored(&armored, &format!("{:?}", keyring_file))
+        } else {
+            log::debug!("Using bundled keyring");
+            Keyring::from_armored(
+                KEYRING_FILENAME_CONTENT,
+                &format!("{:?} (bundled)", KEYRING_FILENAME),
+            )
+        }
+    }
+
+    /// Parse all the armored public key blocks found in `armored`.
+    ///
+    /// Anything outside of the key blocks (for example comments) is ignored.
+    pub fn from_armored(armored: &str, name: &str) -> Result<Keyring> {
+        let mut keys = Vec::new();
+        let mut remaining = armored;
+        while let Some(begin) = remaining.find(PUBLIC_KEY_BLOCK_BEGIN) {
+            let block = &remaining[begin..];
+            let end = block.find(PUBLIC_KEY_BLOCK_END).ok_or_else(|| {
+                SignatureError::Parse(format!("Unterminated key block in {}", name))
+            })? + PUBLIC_KEY_BLOCK_END.len();
+            let (key, _headers) = SignedPublicKey::from_string(&block[..end])
+                .map_err(|e| SignatureError::Parse(e.to_string()))?;
+            key.verify()
+                .map_err(|e| SignatureError::Parse(e.to_string()))?;
+            keys.push(key);
+            remaining = &block[end..];
+        }
+
+        if keys.is_empty() {
+            Err(SignatureError::EmptyKeyring(name.to_string()).into())
+        } else {
+            log::debug!("Loaded {} key(s) from keyring {}", keys.len(), name);
+            Ok(Keyring { keys })
+        }
+    }
+
+    /// Verify `path` against its armored detached signature.
+    pub fn verify_file<P>(&self, path: P, armored_signature: &str) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let (signature, _headers) = StandaloneSignature::from_string(armored_signature)
+            .map_err(|e| SignatureError::Parse(e.to_string()))?;
+        let signature = signature.signature;
+        let issuer = signature
+            .issuer()
+            .ok_or_else(|| SignatureError::BadSignature(path.to_path_buf(), "no issuer".into()))?;
+
+        let data = BufReader::new(File::open(path)?);
+        let result = if let Some(key) = self.keys.iter().find(|key| key.key_id() == *issuer) {
+            signature.verify(key, data)
+        } else if let Some(subkey) = self
+            .keys
+            .iter()
+            .flat_map(|key| key.public_subkeys.iter())
+            .find(|subkey| subkey.key_id() == *issuer)
+        {
+            signature.verify(subkey, data)
+        } else {
+            return Err(SignatureError::UnknownKey(path.to_path_buf(), key_id_hex(issuer)).into());
+        };
+
+        result.map_err(|e| SignatureError::BadSignature(path.to_path_buf(), e.to_string()))?;
+        log::info!(
+            "Signature of {:?} verified (key {})",
+            path,
+            key_id_hex(issuer)
+        );
+
+        Ok(())
+    }
+}
+
+fn key_id_hex(key_id: &KeyId) -> String {
+    hex::encode_upper(key_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    use hygeia_test_helpers::create_test_temp_dir;
+
+    use crate::utils::directory::MockPycorsHomeProviderTrait;
+
+    const KEYRING: &str = include_str!("../tests/fixtures/pgp/keyring.asc");
+    const SIGNATURE: &str = include_str!("../tests/fixtures/pgp/Python-0.0.1.tgz.asc");
+    const UNTRUSTED_SIGNATURE: &str =
+        include_str!("../tests/fixtures/pgp/Python-0.0.1.tgz.untrusted.asc");
+
+    fn tarball() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures")
+            .join("pgp")
+            .join("Python-0.0.1.tgz")
+    }
+
+    #[test]
+    fn verify_success() {
+        let keyring = Keyring::from_armored(KEYRING, "test").unwrap();
+        keyring.verify_file(tarball(), SIGNATURE).unwrap();
+    }
+
+    #[test]
+    fn verify_keyring_with_comments() {
+        let armored = format!("# Release managers\n\n{}\n# Trailing comment\n", KEYRING);
+        let keyring = Keyring::from_armored(&armored, "test").unwrap();
+        keyring.verify_file(tarball(), SIGNATURE).unwrap();
+    }
+
+    #[test]
+    fn verify_tampered_file() {
+        let dir = create_test_temp_dir!();
+        fs::create_dir_all(&dir).unwrap();
+        let tampered = dir.join("Python-0.0.1.tgz");
+        let mut content = fs::read(tarball()).unwrap();
+        content.push(0);
+        fs::write(&tampered, content).unwrap();
+
+        let keyring = Keyring::from_armored(KEYRING, "test").unwrap();
+        let err = keyring.verify_file(&tampered, SIGNATURE).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<SignatureError>(),
+            Some(SignatureError::BadSignature(..))
+        ));
+    }
+
+    #[test]
+    fn verify_unknown_key() {
+        let keyring = Keyring::from_armored(KEYRING, "test").unwrap();
+        let err = keyring
+            .verify_file(tarball(), UNTRUSTED_SIGNATURE)
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<SignatureError>(),
+            Some(SignatureError::UnknownKey(..))
+        ));
+    }
+
+    #[test]
+    fn empty_keyring() {
+        let err = Keyring::from_armored("# No keys here\n", "test")
+            .err()
+            .unwrap();
+        assert!(matches!(
+            err.downcast_ref::<SignatureError>(),
+            Some(SignatureError::EmptyKeyring(..))
+        ));
+    }
+
+    // FIXME: The armored keys of the release managers still have to be exported to
+    // 'cpython-release-managers.asc' (only their fingerprints are listed for now).
+    #[test]
+    #[ignore = "the bundled keyring only lists the fingerprints of the release managers"]
+    fn bundled_keyring() {
+        let project_home = create_test_temp_dir!().join(".hygeia");
+        let mut mock = MockPycorsHomeProviderTrait::new();
+        mock.expect_project_home()
+            .times(1)
+            .return_const(Some(project_home));
+        mock.expect_home().times(0).return_const(None);
+        let paths_provider = PycorsPathsProvider::from(mock);
+
+        let keyring = Keyring::load(&paths_provider).unwrap();
+        assert!(!keyring.keys.is_empty());
+    }
+}
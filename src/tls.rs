@@ -0,0 +1,345 @@
+//! Additional certificate authorities to trust, for networks re-signing TLS traffic.
+
+use std::{
+    env,
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use native_tls::{Certificate, TlsConnectorBuilder};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    config::Config,
+    mirrors::env_variable,
+    utils::directory::{PycorsHomeProviderTrait, PycorsPathsProvider},
+    Result,
+};
+
+const PEM_BEGIN: &str = "-----BEGIN CERTIFICATE-----";
+const PEM_END: &str = "-----END CERTIFICATE-----";
+
+#[derive(Debug, Error)]
+pub enum TlsError {
+    #[error("No PEM certificate found in CA bundle {0:?}")]
+    NoCertificate(PathBuf),
+    #[error("Invalid certificate in CA bundle {0:?}: {1}")]
+    InvalidCertificate(PathBuf, native_tls::Error),
+}
+
+/// `[tls]` table of the configuration file.
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TlsConfig {
+    /// PEM file, or directory of PEM files, of certificate authorities to trust
+    pub ca_bundle: Option<PathBuf>,
+    /// Also make pip (and `get-pip.py`) trust the bundle
+    pub export_ca_bundle: bool,
+}
+
+/// Certificate authorities trusted in addition to the system's, from
+/// `HYGEIA_CA_BUNDLE` or the configuration file.
+#[derive(Clone, PartialEq)]
+pub struct CaBundle {
+    pub path: PathBuf,
+    /// Export the bundle to the commands run during installation
+    /// (`HYGEIA_EXPORT_CA_BUNDLE`).
+    pub export: bool,
+    /// Certificates, each in its own PEM block
+    certificates: Vec<String>,
+}
+
+impl CaBundle {
+    pub fn load<P>(paths_provider: &PycorsPathsProvider<P>) -> Result<Option<CaBundle>>
+    where
+        P: PycorsHomeProviderTrait,
+    {
+        let config = Config::load(paths_provider)?;
+        CaBundle::new(&config.tls, |name| env::var(name).ok())
+    }
+
+    /// Load the bundle only if it is exported to the commands run during installation:
+    /// the bundle is not even read otherwise.
+    pub fn load_exported<P>(paths_provider: &PycorsPathsProvider<P>) -> Result<Option<CaBundle>>
+    where
+        P: PycorsHomeProviderTrait,
+    {
+        let config = Config::load(paths_provider)?;
+        CaBundle::new_exported(&config.tls, |name| env::var(name).ok())
+    }
+
+    /// Merge the configuration with the environment (looked up with `env`).
+    pub fn new<F>(config: &TlsConfig, env: F) -> Result<Option<CaBundle>>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        match settings(config, env) {
+            Some((path, export)) => CaBundle::read(path, export).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Like `new()`, but `None` if the bundle is not exported.
+    pub fn new_exported<F>(config: &TlsConfig, env: F) -> Result<Option<CaBundle>>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        match settings(config, env) {
+            Some((path, true)) => CaBundle::read(path, true).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    fn read(path: PathBuf, export: bool) -> Result<CaBundle> {
+        let certificates = read_certificates(&path)?;
+        if certificates.is_empty() {
+            return Err(TlsError::NoCertificate(path).into());
+        }
+        for pem in &certificates {
+            Certificate::from_pem(pem.as_bytes())
+                .map_err(|e| TlsError::InvalidCertificate(path.clone(), e))?;
+        }
+        log::debug!(
+            "Trusting {} certificate(s) from {:?}",
+            certificates.len(),
+            path
+        );
+
+        Ok(CaBundle {
+            path,
+            export,
+            certificates,
+        })
+    }
+
+    /// Trust the bundle's certificates in addition to the system's.
+    pub fn add_to(&self, builder: &mut TlsConnectorBuilder) -> Result<()> {
+        for pem in &self.certificates {
+            let certificate = Certificate::from_pem(pem.as_bytes())
+                .map_err(|e| TlsError::InvalidCertificate(self.path.clone(), e))?;
+            builder.add_root_certificate(certificate);
+        }
+        Ok(())
+    }
+
+    /// Environment variables pointing OpenSSL and pip to the bundle.
+    ///
+    /// pip only accepts a file: the certificates of a directory are concatenated into
+    /// a file in the cache. Note that pip then trusts only the bundle, not its own
+    /// certificates.
+    pub fn env_variables<P>(
+        &self,
+        paths_provider: &PycorsPathsProvider<P>,
+    ) -> Result<Vec<(&'static str, OsString)>>
+    where
+        P: PycorsHomeProviderTrait,
+    {
+        let bundle_file = if self.path.is_dir() {
+            let cache_dir = paths_provider.cache();
+            fs::create_dir_all(&cache_dir)
+                .with_context(|| format!("Failed to create directory {:?}", cache_dir))?;
+            let bundle_file = paths_provider.ca_bundle_cache_file();
+            fs::write(&bundle_file, self.certificates.concat())
+                .with_context(|| format!("Failed to write CA bundle {:?}", bundle_file))?;
+            bundle_file
+        } else {
+            self.path.clone()
+        };
+        let path = bundle_file.into_os_string();
+        Ok(vec![("SSL_CERT_FILE", path.clone()), ("PIP_CERT", path)])
+    }
+}
+
+/// Path of the bundle and whether it is exported, merging the configuration with the
+/// environment (looked up with `env`).
+fn settings<F>(config: &TlsConfig, env: F) -> Option<(PathBuf, bool)>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let path = match env(&env_variable("CA_BUNDLE")).filter(|value| !value.is_empty()) {
+        Some(value) => PathBuf::from(value),
+        None => config.ca_bundle.clone()?,
+    };
+    let export = match env(&env_variable("EXPORT_CA_BUNDLE")) {
+        Some(value) => matches!(value.to_lowercase().as_str(), "1" | "true" | "yes"),
+        None => config.export_ca_bundle,
+    };
+    Some((path, export))
+}
+
+/// PEM certificates of a file, or of all the files in a directory.
+fn read_certificates(path: &Path) -> Result<Vec<String>> {
+    let files = if path.is_dir() {
+        let mut files: Vec<PathBuf> = fs::read_dir(path)
+            .with_context(|| format!("Failed to read directory {:?}", path))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|file| file.is_file())
+            .collect();
+        files.sort();
+        files
+    } else {
+        vec![path.to_path_buf()]
+    };
+
+    let mut certificates = Vec::new();
+    for file in files {
+        let content =
+            fs::read(&file).with_context(|| format!("Failed to read CA bundle {:?}", file))?;
+        certificates.extend(split_pem(&String::from_utf8_lossy(&content)));
+    }
+    Ok(certificates)
+}
+
+/// Split a PEM bundle into its certificates.
+fn split_pem(bundle: &str) -> Vec<String> {
+    let mut certificates = Vec::new();
+    let mut remaining = bundle;
+    while let Some(begin) = remaining.find(PEM_BEGIN) {
+        let end = match remaining[begin..].find(PEM_END) {
+            Some(end) => begin + end + PEM_END.len(),
+            None => break,
+        };
+        certificates.push(format!("{}\n", &remaining[begin..end]));
+        remaining = &remaining[end..];
+    }
+    certificates
+}
+
+impl std::fmt::Debug for CaBundle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CaBundle")
+            .field("path", &self.path)
+            .field("export", &self.export)
+            .field("certificates", &self.certificates.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    use hygeia_test_helpers::create_test_temp_dir;
+
+    use crate::{constants::CA_BUNDLE_CACHE, utils::directory::MockPycorsHomeProviderTrait};
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures")
+            .join("tls")
+            .join(name)
+    }
+
+    fn env_from(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(name, value)| (env_variable(name), value.to_string()))
+            .collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    #[test]
+    fn no_bundle_by_default() {
+        assert_eq!(
+            CaBundle::new(&TlsConfig::default(), env_from(&[])).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn bundle_file_and_directory() {
+        let ca_pem = fixture("ca.pem");
+        let config = TlsConfig {
+            ca_bundle: Some(PathBuf::from("/does/not/exist.pem")),
+            export_ca_bundle: false,
+        };
+        let ca_bundle = CaBundle::new(
+            &config,
+            env_from(&[
+                ("CA_BUNDLE", ca_pem.to_str().unwrap()),
+                ("EXPORT_CA_BUNDLE", "1"),
+            ]),
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(ca_bundle.path, ca_pem);
+        assert!(ca_bundle.export);
+        assert_eq!(ca_bundle.certificates.len(), 1);
+        let mut mock = MockPycorsHomeProviderTrait::new();
+        mock.expect_project_home().times(0);
+        mock.expect_home().times(0);
+        let paths_provider = PycorsPathsProvider::from(mock);
+        assert_eq!(
+            ca_bundle.env_variables(&paths_provider).unwrap(),
+            vec![
+                ("SSL_CERT_FILE", ca_pem.clone().into_os_string()),
+                ("PIP_CERT", ca_pem.clone().into_os_string())
+            ]
+        );
+
+        // Directory with a two certificates bundle and a file to ignore
+        let ca_dir = create_test_temp_dir!();
+        fs::create_dir_all(&ca_dir).unwrap();
+        let ca = fs::read_to_string(&ca_pem).unwrap();
+        let server = fs::read_to_string(fixture("localhost.pem")).unwrap();
+        fs::write(ca_dir.join("bundle.pem"), format!("{}{}", ca, server)).unwrap();
+        fs::write(ca_dir.join("README"), "Certificates").unwrap();
+        let config = TlsConfig {
+            ca_bundle: Some(ca_dir.clone()),
+            export_ca_bundle: false,
+        };
+        let ca_bundle = CaBundle::new(&config, env_from(&[])).unwrap().unwrap();
+        assert_eq!(ca_bundle.certificates.len(), 2);
+        assert!(!ca_bundle.export);
+
+        // Concatenated into a file for pip
+        let project_home = ca_dir.join(".hygeia");
+        let mut mock = MockPycorsHomeProviderTrait::new();
+        mock.expect_project_home()
+            .times(2)
+            .return_const(Some(project_home.clone()));
+        mock.expect_home().times(0);
+        let paths_provider = PycorsPathsProvider::from(mock);
+        let bundle_file = project_home.join("cache").join(CA_BUNDLE_CACHE);
+        assert_eq!(
+            ca_bundle.env_variables(&paths_provider).unwrap(),
+            vec![
+                ("SSL_CERT_FILE", bundle_file.clone().into_os_string()),
+                ("PIP_CERT", bundle_file.clone().into_os_string())
+            ]
+        );
+        let bundle = fs::read_to_string(&bundle_file).unwrap();
+        assert_eq!(split_pem(&bundle), ca_bundle.certificates);
+    }
+
+    #[test]
+    fn bundle_without_certificate() {
+        let config = TlsConfig {
+            ca_bundle: Some(fixture("localhost-key.pem")),
+            export_ca_bundle: false,
+        };
+        let err = CaBundle::new(&config, env_from(&[])).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<TlsError>(),
+            Some(TlsError::NoCertificate(_))
+        ));
+
+        // Not read when not exported
+        assert_eq!(
+            CaBundle::new_exported(&config, env_from(&[])).unwrap(),
+            None
+        );
+        let err =
+            CaBundle::new_exported(&config, env_from(&[("EXPORT_CA_BUNDLE", "1")])).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<TlsError>(),
+            Some(TlsError::NoCertificate(_))
+        ));
+    }
+}
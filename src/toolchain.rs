@@ -1,9 +1,6 @@
 use std::{
     collections::HashMap,
-    env,
-    fs::{self, File},
-    io,
-    io::{BufRead, BufReader},
+    env, fmt, fs, io,
     path::{Path, PathBuf},
     str::FromStr,
 };
@@ -15,7 +12,11 @@ use semver::{Version, VersionReq};
 use thiserror::Error;
 
 use crate::{
-    constants::{EXECUTABLE_NAME, SHIMS_DIRECTORY_IDENTIFIER_FILE, TOOLCHAIN_FILE},
+    config::Config,
+    constants::{
+        version_env_variable, EXECUTABLE_NAME, PYPROJECT_FILE, RUNTIME_FILE,
+        SHIMS_DIRECTORY_IDENTIFIER_FILE, TOOLCHAIN_FILE, TOOL_VERSIONS_FILE,
+    },
     utils::{
         self,
         directory::{PycorsHomeProviderTrait, PycorsPathsProvider, PycorsPathsProviderFromEnv},
@@ -23,11 +24,16 @@ use crate::{
     Result,
 };
 
+pub mod implementation;
+pub mod index;
 pub mod installed;
 pub mod selected;
 #[cfg(test)]
 pub mod tests;
+pub mod version_files;
 
+use implementation::Implementation;
+use index::InterpreterIndex;
 use installed::{InstalledToolchain, NotInstalledToolchain};
 
 #[derive(Debug, Error)]
@@ -38,85 +44,292 @@ pub enum ToolchainError {
     EmptyToolchainFile(PathBuf),
 }
 
+/// Keyword selecting the system interpreter (one not installed by hygeia)
+pub const SYSTEM_KEYWORD: &str = "system";
+
+/// One entry (line) of a toolchain file.
 #[derive(Debug, PartialEq)]
-pub enum ToolchainFile {
+pub enum ToolchainFileEntry {
+    /// Version requirement on CPython
     VersionReq(VersionReq),
+    /// Version requirement on another implementation, like `pypy3.10`
+    Implementation(Implementation, VersionReq),
     Path(PathBuf),
+    System,
 }
 
-impl FromStr for ToolchainFile {
+impl FromStr for ToolchainFileEntry {
     type Err = std::io::Error;
 
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
         // One can use 'latest' to mean '*'
-        if s == "latest" {
-            "*"
-        } else {
-            s
-        };
+        let s = if s == "latest" { "*" } else { s };
+
+        if s == SYSTEM_KEYWORD {
+            log::info!("Parsed {:?} as system interpreter", s);
+            return Ok(ToolchainFileEntry::System);
+        }
 
-        match semver::VersionReq::parse(s) {
-            Ok(version_req) => {
+        match Implementation::parse_requirement(s) {
+            Ok((Implementation::CPython, version_req)) => {
                 log::info!("Parsed {:?} as semantic version: {}", s, version_req);
-                Ok(ToolchainFile::VersionReq(version_req))
+                Ok(ToolchainFileEntry::VersionReq(version_req))
+            }
+            Ok((implementation, version_req)) => {
+                log::info!(
+                    "Parsed {:?} as {} semantic version: {}",
+                    s,
+                    implementation,
+                    version_req
+                );
+                Ok(ToolchainFileEntry::Implementation(
+                    implementation,
+                    version_req,
+                ))
             }
             Err(_) => {
                 let path = Path::new(s);
                 log::info!("Parsed {:?} as Path: {:?}", s, path);
                 if path.exists() {
-                    Ok(ToolchainFile::Path(
+                    Ok(ToolchainFileEntry::Path(
                         path.canonicalize().expect("path is expected to exists"),
                     ))
                 } else {
                     log::warn!("Requested path {:?} not found.", path);
-                    Ok(ToolchainFile::Path(path.to_path_buf()))
+                    Ok(ToolchainFileEntry::Path(path.to_path_buf()))
                 }
             }
         }
     }
 }
 
+impl fmt::Display for ToolchainFileEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ToolchainFileEntry::VersionReq(version_req) => write!(f, "{}", version_req),
+            ToolchainFileEntry::Implementation(implementation, version_req) => {
+                write!(f, "{}{}", implementation.name(), version_req)
+            }
+            ToolchainFileEntry::Path(path) => write!(f, "{}", path.display()),
+            ToolchainFileEntry::System => write!(f, "{}", SYSTEM_KEYWORD),
+        }
+    }
+}
+
+impl ToolchainFileEntry {
+    /// Installed toolchains satisfying this entry.
+    fn matching_toolchains(
+        &self,
+        installed_toolchains: &[InstalledToolchain],
+        include_pre_releases: bool,
+    ) -> Vec<InstalledToolchain> {
+        match self {
+            ToolchainFileEntry::VersionReq(version_req) => installed_toolchains
+                .iter()
+                .filter(|t| t.implementation == Implementation::CPython)
+                .filter(|t| utils::version_matches(version_req, &t.version, include_pre_releases))
+                .cloned()
+                .collect(),
+            ToolchainFileEntry::Implementation(implementation, version_req) => installed_toolchains
+                .iter()
+                .filter(|t| t.implementation == *implementation)
+                .filter(|t| utils::version_matches(version_req, &t.version, include_pre_releases))
+                .cloned()
+                .collect(),
+            ToolchainFileEntry::System => installed_toolchains
+                .iter()
+                .filter(|t| !t.is_custom_install())
+                .cloned()
+                .collect(),
+            ToolchainFileEntry::Path(path) => match path.canonicalize() {
+                Ok(normalized_path) => match SelectedToolchain::from_path(&normalized_path) {
+                    SelectedToolchain::InstalledToolchain(t) => vec![t],
+                    SelectedToolchain::NotInstalledToolchain(_) => Vec::new(),
+                },
+                Err(e) => {
+                    log::error!("Cannot use {:?} as toolchain path: {:?}", path, e);
+                    Vec::new()
+                }
+            },
+        }
+    }
+
+    /// Latest installed toolchain satisfying this entry (and `version_req`, if any).
+    fn compatible_toolchain(
+        &self,
+        installed_toolchains: &[InstalledToolchain],
+        version_req: Option<&VersionReq>,
+        include_pre_releases: bool,
+    ) -> Option<InstalledToolchain> {
+        let matching_toolchains =
+            self.matching_toolchains(installed_toolchains, include_pre_releases);
+        // Pre-releases were already filtered out (or explicitly requested) by the entry.
+        latest_compatible_toolchain(
+            version_req.unwrap_or(&VersionReq::STAR),
+            matching_toolchains.iter(),
+            true,
+        )
+        .cloned()
+    }
+}
+
+/// Content of a toolchain file (`.python-version`).
+///
+/// Each line is an entry (a version requirement, a path or `system`), in order of
+/// preference. Empty lines and lines starting with `#` are ignored.
+#[derive(Debug, PartialEq)]
+pub struct ToolchainFile {
+    pub entries: Vec<ToolchainFileEntry>,
+}
+
+impl FromStr for ToolchainFile {
+    type Err = std::io::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let entries = s
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::parse)
+            .collect::<std::result::Result<Vec<ToolchainFileEntry>, _>>()?;
+        Ok(ToolchainFile { entries })
+    }
+}
+
 impl ToolchainFile {
+    /// Load the requested toolchain.
+    ///
+    /// The `${HYGEIA_VERSION}` environment variable, if set, has precedence. Otherwise
+    /// the current directory and its parents are searched, the nearest declaration
+    /// winning. In each directory, a `.python-version` is looked for first, then (unless
+    /// disabled in the configuration) a `pyproject.toml`, `.tool-versions` or
+    /// `runtime.txt`, in this order. Files of other tools not declaring a Python version,
+    /// or that cannot be parsed, are skipped.
     pub fn load() -> Result<Option<ToolchainFile>> {
-        let mut search_path: PathBuf =
-            env::current_dir().map_err(ToolchainError::FailedCurrentDir)?;
-        let toolchain_file: Option<PathBuf> = loop {
+        if let Some(version_override) = version_override() {
+            log::debug!("Using {}={:?}", version_env_variable(), version_override);
+            let parsed = version_override.parse::<ToolchainFile>().expect(
+                "ToolchainFile::parse() should not fail (will interpret content as PathBuf)",
+            );
+            return Ok(Some(parsed));
+        }
+
+        let current_dir: PathBuf = env::current_dir().map_err(ToolchainError::FailedCurrentDir)?;
+
+        // The configuration is only read once a directory without '.python-version' is reached
+        let mut other_version_files: Option<bool> = None;
+
+        for search_path in current_dir.ancestors() {
             let toolchain_file: PathBuf = search_path.join(TOOLCHAIN_FILE);
             if utils::path_exists(&toolchain_file) {
-                // We've found the file, stop.
-                log::debug!("Found file {:?}", toolchain_file);
-                break Some(toolchain_file);
+                log::debug!("Reading configuration from file {:?}", toolchain_file);
+                let content = fs::read_to_string(&toolchain_file)?;
+                let parsed = content.parse::<ToolchainFile>().expect(
+                    "ToolchainFile::parse() should not fail (will interpret content as PathBuf)",
+                );
+                if parsed.entries.is_empty() {
+                    return Err(ToolchainError::EmptyToolchainFile(toolchain_file).into());
+                }
+                return Ok(Some(parsed));
             }
 
-            if search_path.parent().is_none() {
-                // We are at the root directory, we haven't found anything.
-                break None;
+            if other_version_files.is_none() {
+                let paths_provider = PycorsPathsProviderFromEnv::new();
+                other_version_files =
+                    Some(Config::load_or_default(&paths_provider).other_version_files);
+            }
+            if other_version_files == Some(false) {
+                continue;
             }
 
-            search_path.pop();
-        };
-        let toolchain_file: Option<ToolchainFile> = match toolchain_file {
-            None => None,
-            Some(toolchain_file) => {
-                log::debug!("Reading configuration from file {:?}", toolchain_file);
-
-                let input = File::open(&toolchain_file)?;
-                let buffered = BufReader::new(input);
+            for filename in &[PYPROJECT_FILE, TOOL_VERSIONS_FILE, RUNTIME_FILE] {
+                let version_file: PathBuf = search_path.join(filename);
+                if !utils::path_exists(&version_file) {
+                    continue;
+                }
+                log::debug!("Reading configuration from file {:?}", version_file);
+                let content = fs::read_to_string(&version_file)?;
 
-                // Read first line only
-                let line: String = match buffered.lines().next() {
-                    Some(line_result) => line_result?,
-                    None => return Err(ToolchainError::EmptyToolchainFile(toolchain_file).into()),
+                // Another tool's file must not break every shim run below it
+                let entries = match version_files::parse(filename, &content) {
+                    Ok(entries) => entries,
+                    Err(e) => {
+                        log::warn!(
+                            "Ignoring {:?}: failed to read Python version: {}",
+                            version_file,
+                            e
+                        );
+                        continue;
+                    }
                 };
-
-                Some(line.parse::<ToolchainFile>().expect(
-                    "ToolchainFile::parse() should not fail (will interpret content as PathBuf)",
-                ))
+                if entries.is_empty() {
+                    log::debug!("No Python version declared in {:?}", version_file);
+                    continue;
+                }
+                return Ok(Some(ToolchainFile { entries }));
             }
-        };
+        }
+
+        // We reached the root directory without finding anything.
+        Ok(None)
+    }
+
+    /// Load the global toolchain file from `${HYGEIA_HOME}`, if present.
+    pub fn load_global<P>(paths_provider: &PycorsPathsProvider<P>) -> Result<Option<ToolchainFile>>
+    where
+        P: PycorsHomeProviderTrait,
+    {
+        let global_toolchain_file = paths_provider.global_toolchain_file();
+        if !utils::path_exists(&global_toolchain_file) {
+            log::debug!("No global toolchain file {:?}", global_toolchain_file);
+            return Ok(None);
+        }
 
-        Ok(toolchain_file)
+        log::debug!(
+            "Reading global toolchain from file {:?}",
+            global_toolchain_file
+        );
+        let content = fs::read_to_string(&global_toolchain_file)?;
+        let parsed = content
+            .parse::<ToolchainFile>()
+            .expect("ToolchainFile::parse() should not fail (will interpret content as PathBuf)");
+        if parsed.entries.is_empty() {
+            return Err(ToolchainError::EmptyToolchainFile(global_toolchain_file).into());
+        }
+
+        Ok(Some(parsed))
     }
+
+    /// First entry satisfied by an installed toolchain (compatible with
+    /// `version_req`, if any).
+    pub fn compatible_toolchain(
+        &self,
+        installed_toolchains: &[InstalledToolchain],
+        version_req: Option<&VersionReq>,
+        include_pre_releases: bool,
+    ) -> Option<InstalledToolchain> {
+        self.entries.iter().find_map(|entry| {
+            entry.compatible_toolchain(installed_toolchains, version_req, include_pre_releases)
+        })
+    }
+
+    /// Whether an entry can be satisfied by a toolchain of `implementation`.
+    pub fn accepts(&self, implementation: Implementation) -> bool {
+        self.entries.iter().any(|entry| match entry {
+            ToolchainFileEntry::VersionReq(_) => implementation == Implementation::CPython,
+            ToolchainFileEntry::Implementation(entry_implementation, _) => {
+                *entry_implementation == implementation
+            }
+            ToolchainFileEntry::Path(_) | ToolchainFileEntry::System => true,
+        })
+    }
+}
+
+/// Toolchain requested through `${HYGEIA_VERSION}`, overriding the toolchain file.
+pub fn version_override() -> Option<String> {
+    env::var(version_env_variable())
+        .ok()
+        .filter(|value| !value.trim().is_empty())
 }
 
 #[derive(Debug, PartialEq)]
@@ -138,6 +351,7 @@ impl SelectedToolchain {
             None => {
                 log::error!("No toolchain found in path {:?}", path.as_ref());
                 SelectedToolchain::NotInstalledToolchain(NotInstalledToolchain {
+                    implementation: Implementation::CPython,
                     version: None,
                     location: Some(path.as_ref().to_path_buf()),
                 })
@@ -145,6 +359,7 @@ impl SelectedToolchain {
             Some(highest_version) => {
                 log::debug!("Highest_version found in path: {:?}", highest_version);
                 SelectedToolchain::InstalledToolchain(InstalledToolchain {
+                    implementation: Implementation::CPython,
                     version: highest_version.0,
                     location: highest_version.1,
                 })
@@ -152,39 +367,47 @@ impl SelectedToolchain {
         }
     }
 
+    /// Toolchain selected by the first satisfied entry of the toolchain file.
+    ///
+    /// If no entry is satisfied, the first one is reported as not installed.
     pub fn from_toolchain_file(
         toolchain_file: &ToolchainFile,
         installed_toolchains: &[InstalledToolchain],
+        include_pre_releases: bool,
     ) -> SelectedToolchain {
-        match toolchain_file {
-            ToolchainFile::VersionReq(version_req) => {
-                match find_compatible_toolchain(version_req, installed_toolchains) {
-                    Some(compatible_toolchain) => {
-                        SelectedToolchain::InstalledToolchain(compatible_toolchain.clone())
-                    }
-                    None => SelectedToolchain::NotInstalledToolchain(NotInstalledToolchain {
-                        version: Some(version_req.clone()),
-                        location: None,
-                    }),
-                }
+        if let Some(compatible_toolchain) =
+            toolchain_file.compatible_toolchain(installed_toolchains, None, include_pre_releases)
+        {
+            return SelectedToolchain::InstalledToolchain(compatible_toolchain);
+        }
+
+        let (implementation, version, location) = match toolchain_file.entries.first() {
+            Some(ToolchainFileEntry::VersionReq(version_req)) => {
+                (Implementation::CPython, Some(version_req.clone()), None)
             }
-            ToolchainFile::Path(path) => {
-                let normalized_path = path.canonicalize();
-                match normalized_path {
-                    Ok(normalized_path) => SelectedToolchain::from_path(&normalized_path),
-                    Err(e) => {
-                        log::error!("Cannot use {:?} as toolchain path: {:?}", path, e);
-                        log::error!(
-                            "Please select a valid toolchain using: {} select",
-                            EXECUTABLE_NAME
-                        );
-                        SelectedToolchain::NotInstalledToolchain(NotInstalledToolchain {
-                            version: None,
-                            location: Some(path.clone()),
-                        })
-                    }
-                }
+            Some(ToolchainFileEntry::Implementation(implementation, version_req)) => {
+                (*implementation, Some(version_req.clone()), None)
+            }
+            Some(ToolchainFileEntry::Path(path)) => {
+                log::error!(
+                    "Please select a valid toolchain using: {} select",
+                    EXECUTABLE_NAME
+                );
+                (Implementation::CPython, None, Some(path.clone()))
             }
+            Some(ToolchainFileEntry::System) | None => (Implementation::CPython, None, None),
+        };
+        SelectedToolchain::NotInstalledToolchain(NotInstalledToolchain {
+            implementation,
+            version,
+            location,
+        })
+    }
+
+    pub fn implementation(&self) -> Implementation {
+        match self {
+            SelectedToolchain::InstalledToolchain(t) => t.implementation,
+            SelectedToolchain::NotInstalledToolchain(t) => t.implementation,
         }
     }
 
@@ -258,94 +481,118 @@ where
     P: AsRef<Path> + std::convert::AsRef<std::ffi::OsStr>,
     S: PycorsHomeProviderTrait,
 {
-    let path: &Path = path.as_ref();
-
     let mut other_pythons: HashMap<Version, PathBuf> = HashMap::new();
 
+    for full_executable_path in python_executables_in_path(path, paths_provider) {
+        if let Some(python_version) = python_version_of(&full_executable_path) {
+            let python_path = parent_dir(&full_executable_path);
+            other_pythons.insert(python_version, python_path);
+        }
+    }
+
+    other_pythons
+}
+
+/// Python executables found in a directory (canonicalized), skipping the shims directory.
+fn python_executables_in_path<P, S>(
+    path: P,
+    paths_provider: &PycorsPathsProvider<S>,
+) -> Vec<PathBuf>
+where
+    P: AsRef<Path> + std::convert::AsRef<std::ffi::OsStr>,
+    S: PycorsHomeProviderTrait,
+{
+    let path: &Path = path.as_ref();
+
     if !path.exists() {
         log::debug!("Skipping non-existing directory {}", path.display());
-        return other_pythons;
+        return Vec::new();
     }
 
     let path = match path.canonicalize() {
         Ok(path) => path,
         Err(e) => {
             log::error!("Failed to canonicalize path: {:?}", e);
-            return other_pythons;
+            return Vec::new();
         }
     };
 
     let shims_dir = paths_provider.shims();
     if path == shims_dir.canonicalize().unwrap_or(shims_dir) {
         log::debug!("Skipping shims directory");
-        return other_pythons;
+        return Vec::new();
     }
 
     match path.read_dir() {
-        Ok(entries) => {
-            for entry in entries.flatten() {
-                let entry_path = entry.path();
-                if path_is_python_executable(&entry_path) {
-                    let python_path = path.clone();
-                    let full_executable_path = entry_path;
-
-                    let cmd_output = std::process::Command::new(&full_executable_path)
-                        .arg("-V")
-                        .stdout(std::process::Stdio::piped())
-                        .stderr(std::process::Stdio::piped())
-                        .output()
-                        .with_context(|| {
-                            format!("Failed to execute command: {:?}", full_executable_path)
-                        });
-                    let python_version: String =
-                        match extract_version_from_command(&full_executable_path, cmd_output) {
-                            Ok(python_version) => python_version,
-                            Err(e) => {
-                                log::error!("extract_version_from_command() failed: {:?}", e);
-                                continue;
-                            }
-                        };
+        Ok(entries) => entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|entry_path| path_is_python_executable(entry_path))
+            .collect(),
+        Err(e) => {
+            log::error!(
+                "Failed to get directory entries for {}: {}",
+                path.display(),
+                e
+            );
+            Vec::new()
+        }
+    }
+}
 
-                    let python_version_str = match python_version.split_whitespace().nth(1) {
-                        None => {
-                            log::error!(
-                                "Failed to parse output from `{} -V`: {}",
-                                full_executable_path.display(),
-                                python_version
-                            );
-                            continue;
-                        }
-                        Some(python_version_str) => python_version_str.trim_end_matches('+'),
-                    };
-                    let python_version = match Version::parse(python_version_str) {
-                        Err(e) => {
-                            log::error!(
-                                "Failed to parse version string {:?}: {:?}",
-                                python_version_str,
-                                e
-                            );
-                            continue;
-                        }
-                        Ok(python_version) => python_version,
-                    };
-                    log::debug!(
-                        "Found python executable in {}: {}",
-                        python_path.display(),
-                        python_version
-                    );
+fn parent_dir(full_executable_path: &Path) -> PathBuf {
+    full_executable_path
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default()
+}
 
-                    other_pythons.insert(python_version, python_path);
-                }
+/// Version of a Python interpreter, from the output of `python -V`.
+pub fn python_version_of(full_executable_path: &Path) -> Option<Version> {
+    let cmd_output = std::process::Command::new(full_executable_path)
+        .arg("-V")
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .output()
+        .with_context(|| format!("Failed to execute command: {:?}", full_executable_path));
+    let python_version: String =
+        match extract_version_from_command(full_executable_path, cmd_output) {
+            Ok(python_version) => python_version,
+            Err(e) => {
+                log::error!("extract_version_from_command() failed: {:?}", e);
+                return None;
             }
+        };
+
+    let python_version_str = match python_version.split_whitespace().nth(1) {
+        None => {
+            log::error!(
+                "Failed to parse output from `{} -V`: {}",
+                full_executable_path.display(),
+                python_version
+            );
+            return None;
         }
-        Err(e) => log::error!(
-            "Failed to get directory entries for {}: {}",
-            path.display(),
-            e
-        ),
-    }
+        Some(python_version_str) => python_version_str.trim_end_matches('+'),
+    };
+    let python_version = match Version::parse(python_version_str) {
+        Err(e) => {
+            log::error!(
+                "Failed to parse version string {:?}: {:?}",
+                python_version_str,
+                e
+            );
+            return None;
+        }
+        Ok(python_version) => python_version,
+    };
+    log::debug!(
+        "Found python executable {}: {}",
+        full_executable_path.display(),
+        python_version
+    );
 
-    other_pythons
+    Some(python_version)
 }
 
 fn extract_version_from_command(
@@ -418,7 +665,32 @@ pub fn find_installed_toolchains<P>(
 where
     P: PycorsHomeProviderTrait,
 {
-    let install_dir = paths_provider.installed();
+    let mut installed_python = Vec::new();
+    for implementation in &Implementation::ALL {
+        installed_python.extend(find_installed_toolchains_of(
+            paths_provider,
+            *implementation,
+        ));
+    }
+
+    // Find other Python installed (f.e. in system directories)
+    let other_pythons = get_python_versions_from_paths(paths_provider);
+    installed_python.extend(other_pythons);
+
+    installed_python.sort_unstable_by(|p1, p2| p2.version.cmp(&p1.version));
+
+    Ok(installed_python)
+}
+
+/// Toolchains of an implementation installed by us, one per directory named by version.
+fn find_installed_toolchains_of<P>(
+    paths_provider: &PycorsPathsProvider<P>,
+    implementation: Implementation,
+) -> Vec<InstalledToolchain>
+where
+    P: PycorsHomeProviderTrait,
+{
+    let install_dir = paths_provider.installed_implementation(implementation);
 
     let mut installed_python = Vec::new();
 
@@ -457,9 +729,14 @@ where
                             Ok(version) => version,
                         };
 
-                        let location = paths_provider.bin_dir(&version);
+                        let location =
+                            paths_provider.implementation_bin_dir(implementation, &version);
 
-                        installed_python.push(InstalledToolchain { location, version });
+                        installed_python.push(InstalledToolchain {
+                            implementation,
+                            location,
+                            version,
+                        });
                     }
                     Err(e) => {
                         log::error!("Error listing directory: {:?}", e);
@@ -467,18 +744,15 @@ where
                 }
             }
         }
-        Err(e) => {
+        Err(e) if implementation == Implementation::CPython => {
             log::warn!("Install dir {:?} does not exists: {:?}", install_dir, e);
         }
+        Err(e) => {
+            log::debug!("Install dir {:?} does not exists: {:?}", install_dir, e);
+        }
     };
 
-    // Find other Python installed (f.e. in system directories)
-    let other_pythons = get_python_versions_from_paths(paths_provider);
-    installed_python.extend(other_pythons);
-
-    installed_python.sort_unstable_by(|p1, p2| p2.version.cmp(&p1.version));
-
-    Ok(installed_python)
+    installed_python
 }
 
 fn get_python_versions_from_paths<S>(
@@ -487,19 +761,39 @@ fn get_python_versions_from_paths<S>(
 where
     S: PycorsHomeProviderTrait,
 {
-    let mut other_pythons: HashMap<Version, PathBuf> = HashMap::new();
-
+    let mut executables: Vec<PathBuf> = Vec::new();
     for path in paths_provider.paths() {
         if path.join(SHIMS_DIRECTORY_IDENTIFIER_FILE).exists() {
             log::debug!("Skipping shims directory found in PATH ({:?})", path);
         } else {
-            other_pythons.extend(get_python_versions_from_path(path, paths_provider));
+            executables.extend(python_executables_in_path(path, paths_provider));
+        }
+    }
+
+    // Only run `python -V` for interpreters not found in the index (or modified since).
+    let mut index = InterpreterIndex::load(paths_provider);
+    let (versions, index_modified) = index.versions(&executables, python_version_of);
+    if index_modified {
+        if let Err(e) = index.save(paths_provider) {
+            log::warn!("Failed to save interpreter index: {:?}", e);
+        }
+    }
+
+    let mut other_pythons: HashMap<Version, PathBuf> = HashMap::new();
+    for executable in &executables {
+        if let Some(Some(version)) = versions.get(executable) {
+            other_pythons.insert(version.clone(), parent_dir(executable));
         }
     }
 
     let mut other_pythons: Vec<InstalledToolchain> = other_pythons
         .into_iter()
-        .map(|(version, location)| InstalledToolchain { location, version })
+        .map(|(version, location)| InstalledToolchain {
+            // Interpreters found in `PATH` are assumed to be CPython
+            implementation: Implementation::CPython,
+            location,
+            version,
+        })
         .collect();
     other_pythons.sort_unstable_by(|p1, p2| p1.version.cmp(&p2.version));
     let other_pythons: Vec<InstalledToolchain> = other_pythons.into_iter().rev().collect();
@@ -508,19 +802,58 @@ where
     other_pythons
 }
 
+/// Latest installed CPython toolchain compatible with `version_req`.
 pub fn find_compatible_toolchain<'a>(
     version_req: &VersionReq,
     installed_toolchains: &'a [InstalledToolchain],
+    include_pre_releases: bool,
 ) -> Option<&'a InstalledToolchain> {
+    find_compatible_toolchain_of(
+        Implementation::CPython,
+        version_req,
+        installed_toolchains,
+        include_pre_releases,
+    )
+}
+
+/// Latest installed toolchain of `implementation` compatible with `version_req`.
+pub fn find_compatible_toolchain_of<'a>(
+    implementation: Implementation,
+    version_req: &VersionReq,
+    installed_toolchains: &'a [InstalledToolchain],
+    include_pre_releases: bool,
+) -> Option<&'a InstalledToolchain> {
+    latest_compatible_toolchain(
+        version_req,
+        installed_toolchains
+            .iter()
+            .filter(|installed_python| installed_python.implementation == implementation),
+        include_pre_releases,
+    )
+}
+
+fn latest_compatible_toolchain<'a, I>(
+    version_req: &VersionReq,
+    installed_toolchains: I,
+    include_pre_releases: bool,
+) -> Option<&'a InstalledToolchain>
+where
+    I: Iterator<Item = &'a InstalledToolchain>,
+{
     // Find all compatible versions from the installed list
     let mut compatible_versions: Vec<&'a InstalledToolchain> = installed_toolchains
-        .iter()
-        .filter(|installed_python| version_req.matches(&installed_python.version))
+        .filter(|installed_python| {
+            utils::version_matches(version_req, &installed_python.version, include_pre_releases)
+        })
         .collect();
-    // Sort to get latest version. If two versions are identical, pick the
+    // Sort to get latest version. In a same series (major.minor), a final release
+    // is preferred over a pre-release. If two versions are identical, pick the
     // one that is custom installed (not a system one).
     compatible_versions.sort_unstable_by(|a, b| {
-        let version_comparison = a.version.cmp(&b.version);
+        let version_comparison = (a.version.major, a.version.minor)
+            .cmp(&(b.version.major, b.version.minor))
+            .then_with(|| a.version.pre.is_empty().cmp(&b.version.pre.is_empty()))
+            .then_with(|| a.version.cmp(&b.version));
         if version_comparison == std::cmp::Ordering::Equal {
             if a.is_custom_install() {
                 std::cmp::Ordering::Greater
@@ -544,6 +877,7 @@ pub struct CompatibleToolchainBuilder {
     pick_latest_if_none_found: bool,
     load_from: CompatibleToolchainSource,
     overwrite: Option<VersionReq>,
+    implementation: Implementation,
 }
 
 impl CompatibleToolchainBuilder {
@@ -552,6 +886,7 @@ impl CompatibleToolchainBuilder {
             pick_latest_if_none_found: false,
             load_from: CompatibleToolchainSource::File,
             overwrite: None,
+            implementation: Implementation::CPython,
         }
     }
     pub fn load_from_file(mut self) -> Self {
@@ -570,6 +905,11 @@ impl CompatibleToolchainBuilder {
         self.overwrite = with;
         self
     }
+    /// Implementation to fall back to when the toolchain file selects none.
+    pub fn implementation(mut self, implementation: Implementation) -> Self {
+        self.implementation = implementation;
+        self
+    }
     pub fn compatible_version<P>(
         self,
         paths_provider: PycorsPathsProvider<P>,
@@ -579,103 +919,153 @@ impl CompatibleToolchainBuilder {
     {
         let installed_toolchains: Vec<InstalledToolchain> =
             find_installed_toolchains(&paths_provider)?;
+        let include_pre_releases = Config::load_or_default(&paths_provider).pre_releases;
+
+        // Load requested version from either .python-version (if present) or string
+        let parsed_requested_toolchain: Option<ToolchainFile> = match &self.load_from {
+            CompatibleToolchainSource::File => match ToolchainFile::load()? {
+                Some(parsed) => Some(parsed),
+                None => {
+                    // We could not load a toolchain file, use the global one (if any).
+                    log::warn!(
+                        "File {:?} does not exists and could not be loaded.",
+                        TOOLCHAIN_FILE
+                    );
+                    ToolchainFile::load_global(&paths_provider)?
+                }
+            },
+            CompatibleToolchainSource::String(s) => {
+                let parsed = ToolchainFile::from_str(s)?;
+                Some(parsed)
+            }
+        };
 
-        let compatible = match self.overwrite {
+        let compatible: Option<InstalledToolchain> = match self.overwrite {
             Some(version_req) => {
                 log::info!("Overwriting version with {}", version_req);
 
-                let search_result = find_compatible_toolchain(&version_req, &installed_toolchains);
+                // Prefer a toolchain listed in the toolchain file (for example, `python3.8`
+                // resolves to the `~3.8` entry), then any compatible one of the same
+                // implementation: a file only listing `pypy3.10` never runs CPython.
+                let accepts_implementation = parsed_requested_toolchain
+                    .as_ref()
+                    .map_or(true, |requested_toolchain| {
+                        requested_toolchain.accepts(self.implementation)
+                    });
+                let search_result = parsed_requested_toolchain
+                    .and_then(|requested_toolchain| {
+                        requested_toolchain.compatible_toolchain(
+                            &installed_toolchains,
+                            Some(&version_req),
+                            include_pre_releases,
+                        )
+                    })
+                    .or_else(|| {
+                        if !accepts_implementation {
+                            return None;
+                        }
+                        find_compatible_toolchain_of(
+                            self.implementation,
+                            &version_req,
+                            &installed_toolchains,
+                            include_pre_releases,
+                        )
+                        .cloned()
+                    });
                 log::debug!("Compatible version found: {:?}", search_result);
                 search_result
             }
-            None => {
-                // Load requested version from either .python-version (if present) or string
-                let parsed_requested_toolchain: Option<ToolchainFile> = match &self.load_from {
-                    CompatibleToolchainSource::File => {
-                        let parsed: Option<ToolchainFile> = ToolchainFile::load()?.or_else(|| {
-                            // We could not load a toolchain file.
-                            log::warn!(
-                                "File {:?} does not exists and could not be loaded.",
-                                TOOLCHAIN_FILE
-                            );
-                            None
-                        });
-                        parsed
+            None => match parsed_requested_toolchain {
+                None => {
+                    log::warn!("No compatible toolchain found.");
+
+                    // No requested version (.python-version flag nor --version flag)
+                    // Pick up the latest installed one (if asked for).
+                    if self.pick_latest_if_none_found {
+                        log::warn!("Trying latest installed...");
+                        latest_installed(
+                            &installed_toolchains,
+                            self.implementation,
+                            include_pre_releases,
+                        )
+                        .cloned()
+                    } else {
+                        // We did not asked for a version (through the .python-version file
+                        // or --version flag) and we did not asked to find the latest installed.
+                        // We thus don't have any toolchain to run.
+                        None
                     }
-                    CompatibleToolchainSource::String(s) => {
-                        let parsed = ToolchainFile::from_str(s)?;
-                        Some(parsed)
+                }
+                Some(requested_toolchain) => {
+                    log::debug!("Searching for compatible toolchain in installed list...");
+
+                    let selected_toolchain = SelectedToolchain::from_toolchain_file(
+                        &requested_toolchain,
+                        &installed_toolchains,
+                        include_pre_releases,
+                    );
+                    if selected_toolchain.is_installed() {
+                        log::debug!("Compatible version found: {:?}", selected_toolchain);
+                    } else if let Some(version_req) = selected_toolchain.version_req() {
+                        log::warn!(
+                            "No installed {} toolchain compatible with {}.",
+                            selected_toolchain.implementation().label(),
+                            version_req
+                        );
+                    } else {
+                        log::warn!("None of the requested toolchains is installed.");
                     }
-                };
 
-                let compatible: Option<&InstalledToolchain> = match parsed_requested_toolchain {
-                    None => {
-                        log::warn!("No compatible toolchain found.");
-
-                        // No requested version (.python-version flag nor --version flag)
-                        // Pick up the latest installed one (if asked for).
-                        if self.pick_latest_if_none_found {
-                            log::warn!("Trying latest installed...");
-                            latest_installed(&installed_toolchains)
-                        } else {
-                            // We did not asked for a version (through the .python-version file
-                            // or --version flag) and we did not asked to find the latest installed.
-                            // We thus don't have any toolchain to run.
-                            None
+                    match selected_toolchain {
+                        SelectedToolchain::InstalledToolchain(compatible_toolchain) => {
+                            Some(compatible_toolchain)
                         }
-                    }
-                    Some(requested_toolchain) => {
-                        log::debug!("Searching for compatible toolchain in installed list...");
-
-                        let selected_toolchain: SelectedToolchain =
-                            SelectedToolchain::from_toolchain_file(
-                                &requested_toolchain,
-                                &installed_toolchains,
-                            );
-
-                        match selected_toolchain.version_req() {
-                            Some(version_req) => {
-                                log::debug!(
-                                    "Searching for installed version compatible with: {}",
-                                    version_req
-                                );
-                                let search_result =
-                                    find_compatible_toolchain(&version_req, &installed_toolchains);
-                                log::debug!("Compatible version found: {:?}", search_result);
-                                search_result
-                            }
-                            None => {
-                                log::warn!("Cannot find a compatible toolchain since selected toolchain is not installed.");
-
-                                // We couldn't get a VersionReq from the toolchain, because we loaded a path
-                                // from the toolchain file that does not contain a valid Python interpreter.
-                                assert!(!selected_toolchain.is_installed());
-
-                                if self.pick_latest_if_none_found {
-                                    log::debug!("Finding latest installed one.");
-                                    latest_installed(&installed_toolchains)
-                                } else {
-                                    // We asked for a specific version but couldn't find it and we did
-                                    // not asked to find the latest installed.
-                                    // We thus don't have any toolchain to run.
-                                    None
-                                }
+                        SelectedToolchain::NotInstalledToolchain(not_installed) => {
+                            // Another version than the requested one is never picked: only
+                            // a file without a version (a path or `system`) falls back.
+                            if self.pick_latest_if_none_found && not_installed.version.is_none() {
+                                log::debug!("Finding latest installed one.");
+                                latest_installed(
+                                    &installed_toolchains,
+                                    self.implementation,
+                                    include_pre_releases,
+                                )
+                                .cloned()
+                            } else {
+                                // We asked for a specific version but couldn't find it, or we
+                                // did not asked to find the latest installed.
+                                // We thus don't have any toolchain to run.
+                                None
                             }
                         }
                     }
-                };
-                compatible
-            }
+                }
+            },
         };
 
-        Ok(compatible.cloned())
+        Ok(compatible)
     }
 }
 
-fn latest_installed(installed_toolchains: &[InstalledToolchain]) -> Option<&InstalledToolchain> {
+fn latest_installed(
+    installed_toolchains: &[InstalledToolchain],
+    implementation: Implementation,
+    include_pre_releases: bool,
+) -> Option<&InstalledToolchain> {
     // We could not get a compatible version.
-    // Let's pick the latest installed one instead, if any.
-    let latest_toolchain: Option<&InstalledToolchain> = installed_toolchains.get(0);
+    // Let's pick the latest installed one instead, if any. Pre-releases are only picked
+    // when asked for, or when nothing else is installed.
+    let mut toolchains = installed_toolchains
+        .iter()
+        .filter(|t| t.implementation == implementation);
+    let latest_toolchain: Option<&InstalledToolchain> = if include_pre_releases {
+        toolchains.next()
+    } else {
+        toolchains
+            .clone()
+            .find(|t| t.version.pre.is_empty())
+            .or_else(|| toolchains.next())
+    };
     log::debug!(
         "Latest installed: {}",
         match latest_toolchain {
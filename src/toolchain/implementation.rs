@@ -0,0 +1,150 @@
+use std::fmt;
+
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+
+/// Implementation of the Python language.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Implementation {
+    /// The reference implementation, published on python.org
+    CPython,
+    /// The JIT compiled implementation published on pypy.org
+    PyPy,
+}
+
+impl Default for Implementation {
+    fn default() -> Self {
+        Implementation::CPython
+    }
+}
+
+impl Implementation {
+    pub const ALL: [Implementation; 2] = [Implementation::CPython, Implementation::PyPy];
+
+    /// Lower case name, naming its installation directory and prefixing its version
+    /// requirements (like `pypy3.10`).
+    pub fn name(self) -> &'static str {
+        match self {
+            Implementation::CPython => "cpython",
+            Implementation::PyPy => "pypy",
+        }
+    }
+
+    /// Name of its interpreters in messages, for example `PyPy 3.10.12`.
+    pub fn label(self) -> &'static str {
+        match self {
+            Implementation::CPython => "Python",
+            Implementation::PyPy => "PyPy",
+        }
+    }
+
+    /// Toolchain file entry selecting exactly `version` of this implementation.
+    pub fn pin(self, version: &Version) -> String {
+        match self {
+            Implementation::CPython => format!("={}", version),
+            implementation => format!("{}={}", implementation.name(), version),
+        }
+    }
+
+    /// Implementation a command (like `pypy3`) is provided by.
+    pub fn of_command(command: &str) -> Implementation {
+        if command.starts_with(Implementation::PyPy.name()) {
+            Implementation::PyPy
+        } else {
+            Implementation::CPython
+        }
+    }
+
+    /// Split a requirement like `pypy3.10` into its implementation and the
+    /// requirement on the version of Python it implements.
+    ///
+    /// Without a prefix, CPython is meant. After a prefix, a bare version selects its
+    /// series (`pypy3.10` is `~3.10`) and nothing selects any version (`pypy`).
+    pub fn parse_requirement(
+        requirement: &str,
+    ) -> std::result::Result<(Implementation, VersionReq), semver::Error> {
+        for implementation in &Implementation::ALL {
+            let rest = match requirement.strip_prefix(implementation.name()) {
+                Some(rest) => rest,
+                None => continue,
+            };
+            let version_req = if rest.is_empty() {
+                VersionReq::STAR
+            } else if rest.chars().all(|c| c.is_ascii_digit() || c == '.') {
+                VersionReq::parse(&format!("~{}", rest))?
+            } else {
+                VersionReq::parse(rest)?
+            };
+            return Ok((*implementation, version_req));
+        }
+
+        Ok((Implementation::CPython, VersionReq::parse(requirement)?))
+    }
+}
+
+impl fmt::Display for Implementation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Implementation::CPython => write!(f, "CPython"),
+            Implementation::PyPy => write!(f, "PyPy"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn req(version_req: &str) -> VersionReq {
+        VersionReq::parse(version_req).unwrap()
+    }
+
+    #[test]
+    fn parse_requirement() {
+        for (requirement, expected) in &[
+            ("3.8", (Implementation::CPython, req("3.8"))),
+            ("~3.8", (Implementation::CPython, req("~3.8"))),
+            ("cpython3.8", (Implementation::CPython, req("~3.8"))),
+            ("pypy", (Implementation::PyPy, VersionReq::STAR)),
+            ("pypy3", (Implementation::PyPy, req("~3"))),
+            ("pypy3.10", (Implementation::PyPy, req("~3.10"))),
+            ("pypy=3.10.12", (Implementation::PyPy, req("=3.10.12"))),
+            ("pypy>=3.9", (Implementation::PyPy, req(">=3.9"))),
+        ] {
+            assert_eq!(
+                &Implementation::parse_requirement(requirement).unwrap(),
+                expected,
+                "{}",
+                requirement
+            );
+        }
+
+        assert!(Implementation::parse_requirement("pypy-venv/bin").is_err());
+        assert!(Implementation::parse_requirement("jython2.7").is_err());
+    }
+
+    #[test]
+    fn pin_round_trip() {
+        let version = Version::new(3, 10, 12);
+        for implementation in &Implementation::ALL {
+            let pinned = implementation.pin(&version);
+            assert_eq!(
+                Implementation::parse_requirement(&pinned).unwrap(),
+                (*implementation, req("=3.10.12"))
+            );
+        }
+        assert_eq!(Implementation::CPython.pin(&version), "=3.10.12");
+        assert_eq!(Implementation::PyPy.pin(&version), "pypy=3.10.12");
+    }
+
+    #[test]
+    fn of_command() {
+        assert_eq!(
+            Implementation::of_command("python3"),
+            Implementation::CPython
+        );
+        assert_eq!(Implementation::of_command("pip"), Implementation::CPython);
+        assert_eq!(Implementation::of_command("pypy3.10"), Implementation::PyPy);
+    }
+}
@@ -0,0 +1,234 @@
+//! Persistent index of the Python interpreters found in `PATH`.
+//!
+//! Running `python -V` for every interpreter on every shim invocation is slow, so
+//! the versions are saved along with a fingerprint (modification time, inode and
+//! size) of the executable. Only new or modified executables are probed again.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    process,
+    sync::Arc,
+    thread,
+    time::SystemTime,
+};
+
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    utils::directory::{PycorsHomeProviderTrait, PycorsPathsProvider},
+    Result,
+};
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct InterpreterIndex {
+    /// Entries, by executable path
+    interpreters: HashMap<PathBuf, IndexEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct IndexEntry {
+    fingerprint: Fingerprint,
+    /// `None` if the executable is not a working Python interpreter
+    version: Option<Version>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Fingerprint {
+    modified: Option<SystemTime>,
+    inode: u64,
+    len: u64,
+}
+
+impl Fingerprint {
+    fn of(path: &Path) -> Option<Fingerprint> {
+        // Follow symbolic links: upgrading the interpreter they point to must invalidate the entry.
+        let metadata = fs::metadata(path).ok()?;
+
+        #[cfg(unix)]
+        let inode = {
+            use std::os::unix::fs::MetadataExt;
+            metadata.ino()
+        };
+        #[cfg(not(unix))]
+        let inode = 0;
+
+        Some(Fingerprint {
+            modified: metadata.modified().ok(),
+            inode,
+            len: metadata.len(),
+        })
+    }
+}
+
+impl InterpreterIndex {
+    /// Load the index from the cache directory; a missing or invalid index is empty.
+    pub fn load<P>(paths_provider: &PycorsPathsProvider<P>) -> InterpreterIndex
+    where
+        P: PycorsHomeProviderTrait,
+    {
+        let index_file = paths_provider.interpreter_index_cache_file();
+        match fs::read_to_string(&index_file) {
+            Ok(content) => match serde_json::from_str(&content) {
+                Ok(index) => index,
+                Err(e) => {
+                    log::warn!("Ignoring invalid interpreter index {:?}: {}", index_file, e);
+                    InterpreterIndex::default()
+                }
+            },
+            Err(_) => {
+                log::debug!("No interpreter index {:?}", index_file);
+                InterpreterIndex::default()
+            }
+        }
+    }
+
+    pub fn save<P>(&self, paths_provider: &PycorsPathsProvider<P>) -> Result<()>
+    where
+        P: PycorsHomeProviderTrait,
+    {
+        let index_file = paths_provider.interpreter_index_cache_file();
+        log::debug!("Saving interpreter index to {:?}", index_file);
+        fs::create_dir_all(paths_provider.cache())?;
+
+        // Written next to the index and moved atomically: a concurrent shim never reads
+        // a partially written index.
+        let mut tmp_file_name = index_file.file_name().unwrap_or_default().to_os_string();
+        tmp_file_name.push(format!(".{}.tmp", process::id()));
+        let tmp_file = index_file.with_file_name(tmp_file_name);
+        fs::write(&tmp_file, serde_json::to_string_pretty(self)?)?;
+        if let Err(e) = fs::rename(&tmp_file, &index_file) {
+            let _ = fs::remove_file(&tmp_file);
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    /// Versions of the given executables, probing (in parallel) the ones not indexed
+    /// or modified since they were. Also returns whether the index was modified.
+    pub fn versions<F>(
+        &mut self,
+        executables: &[PathBuf],
+        probe: F,
+    ) -> (HashMap<PathBuf, Option<Version>>, bool)
+    where
+        F: Fn(&Path) -> Option<Version> + Send + Sync + 'static,
+    {
+        let mut modified = false;
+
+        // Forget executables that were removed
+        let nb_entries = self.interpreters.len();
+        self.interpreters
+            .retain(|executable, _| executable.exists());
+        modified |= self.interpreters.len() != nb_entries;
+
+        let stale: Vec<(&PathBuf, Option<Fingerprint>)> = executables
+            .iter()
+            .map(|executable| (executable, Fingerprint::of(executable)))
+            .filter(|(executable, fingerprint)| {
+                match (self.interpreters.get(*executable), fingerprint) {
+                    (Some(entry), Some(fingerprint)) => entry.fingerprint != *fingerprint,
+                    _ => true,
+                }
+            })
+            .collect();
+
+        if !stale.is_empty() {
+            log::debug!("Probing {} interpreter(s)", stale.len());
+            let probe = Arc::new(probe);
+            let handles: Vec<_> = stale
+                .into_iter()
+                .map(|(executable, fingerprint)| {
+                    let executable = executable.clone();
+                    let probe = Arc::clone(&probe);
+                    thread::spawn(move || {
+                        let version = probe(&executable);
+                        (executable, fingerprint, version)
+                    })
+                })
+                .collect();
+            let probed: Vec<(PathBuf, Option<Fingerprint>, Option<Version>)> = handles
+                .into_iter()
+                .filter_map(|handle| handle.join().ok())
+                .collect();
+            for (executable, fingerprint, version) in probed {
+                if let Some(fingerprint) = fingerprint {
+                    self.interpreters.insert(
+                        executable,
+                        IndexEntry {
+                            fingerprint,
+                            version,
+                        },
+                    );
+                    modified = true;
+                }
+            }
+        }
+
+        let versions = executables
+            .iter()
+            .filter_map(|executable| {
+                self.interpreters
+                    .get(executable)
+                    .map(|entry| (executable.clone(), entry.version.clone()))
+            })
+            .collect();
+
+        (versions, modified)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    use hygeia_test_helpers::create_test_temp_dir;
+
+    #[test]
+    fn versions_probed_once() {
+        let dir = create_test_temp_dir!();
+        let python3 = dir.join("python3");
+        let python = dir.join("python");
+        fs::write(&python3, "3.8.1").unwrap();
+        fs::write(&python, "not python").unwrap();
+        let executables = vec![python3.clone(), python.clone()];
+
+        static NB_PROBES: AtomicUsize = AtomicUsize::new(0);
+        let nb_probes = &NB_PROBES;
+        let probe = |path: &Path| {
+            NB_PROBES.fetch_add(1, Ordering::SeqCst);
+            Version::parse(&fs::read_to_string(path).unwrap()).ok()
+        };
+
+        let mut index = InterpreterIndex::default();
+        let (versions, modified) = index.versions(&executables, probe);
+        assert!(modified);
+        assert_eq!(nb_probes.load(Ordering::SeqCst), 2);
+        assert_eq!(versions[&python3], Some(Version::new(3, 8, 1)));
+        assert_eq!(versions[&python], None);
+
+        // Nothing changed: nothing probed
+        let (versions, modified) = index.versions(&executables, probe);
+        assert!(!modified);
+        assert_eq!(nb_probes.load(Ordering::SeqCst), 2);
+        assert_eq!(versions[&python3], Some(Version::new(3, 8, 1)));
+
+        // Upgraded in place: probed again
+        fs::write(&python3, "3.8.10").unwrap();
+        let (versions, modified) = index.versions(&executables, probe);
+        assert!(modified);
+        assert_eq!(nb_probes.load(Ordering::SeqCst), 3);
+        assert_eq!(versions[&python3], Some(Version::new(3, 8, 10)));
+
+        // Removed: forgotten
+        fs::remove_file(&python).unwrap();
+        let (versions, modified) = index.versions(&executables[..1], probe);
+        assert!(modified);
+        assert_eq!(versions.len(), 1);
+        assert!(!index.interpreters.contains_key(&python));
+    }
+}
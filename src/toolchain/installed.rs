@@ -11,7 +11,7 @@ use thiserror::Error;
 
 use crate::{
     constants::TOOLCHAIN_FILE,
-    toolchain::{self, get_python_versions_from_path},
+    toolchain::{self, get_python_versions_from_path, implementation::Implementation},
     utils::directory::PycorsPathsProviderFromEnv,
 };
 
@@ -23,18 +23,27 @@ pub struct ToolchainNotInstalled {
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct InstalledToolchain {
+    pub implementation: Implementation,
     pub location: PathBuf,
     pub version: Version,
 }
 
 impl fmt::Display for InstalledToolchain {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Python {} ({})", self.version, self.location.display())
+        write!(
+            f,
+            "{} {} ({})",
+            self.implementation.label(),
+            self.version,
+            self.location.display()
+        )
     }
 }
 
 #[derive(Debug, PartialEq)]
 pub struct NotInstalledToolchain {
+    /// Implementation of the requested toolchain (CPython unless named)
+    pub implementation: Implementation,
     pub version: Option<VersionReq>,
     pub location: Option<PathBuf>,
 }
@@ -53,6 +62,7 @@ impl InstalledToolchain {
         log::debug!("highest_version: {:?}", highest_version);
 
         Some(InstalledToolchain {
+            implementation: Implementation::CPython,
             version: highest_version.0,
             location: highest_version.1,
         })
@@ -63,7 +73,7 @@ impl InstalledToolchain {
     }
 
     pub fn save_version(&self) -> Result<usize> {
-        let version = format!("={}", self.version);
+        let version = self.implementation.pin(&self.version);
         save(&version, TOOLCHAIN_FILE)
     }
 
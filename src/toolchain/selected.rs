@@ -3,9 +3,13 @@ use std::{
     str::FromStr,
 };
 
+use crate::toolchain::implementation::Implementation;
+
 #[derive(Debug, PartialEq)]
 pub enum VersionOrPath {
     VersionReq(semver::VersionReq),
+    /// Version requirement on another implementation than CPython
+    Implementation(Implementation, semver::VersionReq),
     Path(PathBuf),
 }
 
@@ -20,11 +24,20 @@ impl FromStr for VersionOrPath {
             s
         };
 
-        match semver::VersionReq::parse(s) {
-            Ok(version_req) => {
+        match Implementation::parse_requirement(s) {
+            Ok((Implementation::CPython, version_req)) => {
                 log::info!("Parsed {:?} as semantic version: {}", s, version_req);
                 Ok(VersionOrPath::VersionReq(version_req))
             }
+            Ok((implementation, version_req)) => {
+                log::info!(
+                    "Parsed {:?} as {} semantic version: {}",
+                    s,
+                    implementation,
+                    version_req
+                );
+                Ok(VersionOrPath::Implementation(implementation, version_req))
+            }
             Err(e) => {
                 log::debug!("e: {:?}", e);
                 let path = Path::new(s);
@@ -20,39 +20,39 @@ use crate::{constants::INFO_FILE, utils::directory::MockPycorsHomeProviderTrait}
 #[test]
 fn version_or_path_from_str_success_major_minor_patch() {
     let v = "3.7.4";
-    let vop: ToolchainFile = v.parse().unwrap();
+    let vop: ToolchainFileEntry = v.parse().unwrap();
     assert_eq!(
         vop,
-        ToolchainFile::VersionReq(VersionReq::parse(v).unwrap())
+        ToolchainFileEntry::VersionReq(VersionReq::parse(v).unwrap())
     );
 }
 #[test]
 fn version_or_path_from_str_success_eq_major_minor_patch() {
     let v = "=3.7.4";
-    let vop: ToolchainFile = v.parse().unwrap();
+    let vop: ToolchainFileEntry = v.parse().unwrap();
     assert_eq!(
         vop,
-        ToolchainFile::VersionReq(VersionReq::parse(v).unwrap())
+        ToolchainFileEntry::VersionReq(VersionReq::parse(v).unwrap())
     );
 }
 
 #[test]
 fn version_or_path_from_str_success_tilde_major_minor() {
     let v = "~3.7";
-    let vop: ToolchainFile = v.parse().unwrap();
+    let vop: ToolchainFileEntry = v.parse().unwrap();
     assert_eq!(
         vop,
-        ToolchainFile::VersionReq(VersionReq::parse(v).unwrap())
+        ToolchainFileEntry::VersionReq(VersionReq::parse(v).unwrap())
     );
 }
 
 #[test]
 fn version_or_path_from_str_success_tilde_major() {
     let v = "~3";
-    let vop: ToolchainFile = v.parse().unwrap();
+    let vop: ToolchainFileEntry = v.parse().unwrap();
     assert_eq!(
         vop,
-        ToolchainFile::VersionReq(VersionReq::parse(v).unwrap())
+        ToolchainFileEntry::VersionReq(VersionReq::parse(v).unwrap())
     );
 }
 
@@ -60,16 +60,16 @@ fn version_or_path_from_str_success_tilde_major() {
 fn version_or_path_from_str_err_path_success() {
     let dir = create_test_temp_dir!();
     let v = dir.to_string_lossy();
-    let vop: ToolchainFile = v.parse().unwrap();
-    assert_eq!(vop, ToolchainFile::Path(dir));
+    let vop: ToolchainFileEntry = v.parse().unwrap();
+    assert_eq!(vop, ToolchainFileEntry::Path(dir));
 }
 
 #[test]
 fn version_or_path_from_str_err_path_failed_dir_not_found() {
     let dir = create_test_temp_dir!();
     let v = dir.to_string_lossy();
-    let vop: ToolchainFile = v.parse().unwrap();
-    assert_eq!(vop, ToolchainFile::Path(dir));
+    let vop: ToolchainFileEntry = v.parse().unwrap();
+    assert_eq!(vop, ToolchainFileEntry::Path(dir));
 }
 
 use std::sync::{Arc, Mutex};
@@ -145,7 +145,9 @@ fn toolchain_file_load_error_garbage() {
     // In case ToolchainFile cannot parse a Version, it will be interpreted as a Path.
     assert_eq!(
         vop.unwrap().unwrap(),
-        ToolchainFile::Path(PathBuf::from_str(v).unwrap())
+        ToolchainFile {
+            entries: vec![ToolchainFileEntry::Path(PathBuf::from_str(v).unwrap())]
+        }
     );
 }
 
@@ -167,7 +169,278 @@ fn toolchain_file_load_success_some() {
 
     assert_eq!(
         vop,
-        ToolchainFile::VersionReq(VersionReq::parse(v).unwrap())
+        ToolchainFile {
+            entries: vec![ToolchainFileEntry::VersionReq(
+                VersionReq::parse(v).unwrap()
+            )]
+        }
+    );
+}
+
+#[test]
+fn toolchain_file_load_other_version_files() {
+    let dir = create_test_temp_dir!();
+    fs::write(dir.join(RUNTIME_FILE), "python-3.7.4\n").unwrap();
+    let project_dir = dir.join("project");
+    fs::create_dir_all(&project_dir).unwrap();
+    fs::write(project_dir.join(RUNTIME_FILE), "python-3.8.1\n").unwrap();
+    fs::write(project_dir.join(TOOL_VERSIONS_FILE), "python 3.9.2\n").unwrap();
+    fs::write(project_dir.join(PYPROJECT_FILE), "[tool.black]\n").unwrap();
+
+    // The closest directory wins; a 'pyproject.toml' without Python version is skipped.
+    let vop = with_directory(&project_dir, ToolchainFile::load).unwrap();
+    assert_eq!(
+        vop.unwrap(),
+        ToolchainFile {
+            entries: vec![ToolchainFileEntry::VersionReq(
+                VersionReq::parse("=3.9.2").unwrap()
+            )]
+        }
+    );
+
+    fs::write(
+        project_dir.join(PYPROJECT_FILE),
+        "[project]\nrequires-python = \">=3.8\"\n",
+    )
+    .unwrap();
+    let vop = with_directory(&project_dir, ToolchainFile::load).unwrap();
+    assert_eq!(
+        vop.unwrap(),
+        ToolchainFile {
+            entries: vec![ToolchainFileEntry::VersionReq(
+                VersionReq::parse(">=3.8.0").unwrap()
+            )]
+        }
+    );
+
+    // A malformed file is skipped
+    fs::write(project_dir.join(PYPROJECT_FILE), "[project\n").unwrap();
+    let vop = with_directory(&project_dir, ToolchainFile::load).unwrap();
+    assert_eq!(
+        vop.unwrap(),
+        ToolchainFile {
+            entries: vec![ToolchainFileEntry::VersionReq(
+                VersionReq::parse("=3.9.2").unwrap()
+            )]
+        }
+    );
+
+    // The nearest declaration wins, even over a '.python-version' in a parent
+    fs::write(dir.join(TOOLCHAIN_FILE), "~3.10").unwrap();
+    fs::write(
+        project_dir.join(PYPROJECT_FILE),
+        "[project]\nrequires-python = \">=3.8\"\n",
+    )
+    .unwrap();
+    let vop = with_directory(&project_dir, ToolchainFile::load).unwrap();
+    assert_eq!(
+        vop.unwrap(),
+        ToolchainFile {
+            entries: vec![ToolchainFileEntry::VersionReq(
+                VersionReq::parse(">=3.8.0").unwrap()
+            )]
+        }
+    );
+
+    // In the same directory, a '.python-version' has precedence over the files of other tools
+    fs::write(project_dir.join(TOOLCHAIN_FILE), "~3.11").unwrap();
+    let vop = with_directory(&project_dir, ToolchainFile::load).unwrap();
+    assert_eq!(
+        vop.unwrap(),
+        ToolchainFile {
+            entries: vec![ToolchainFileEntry::VersionReq(
+                VersionReq::parse("~3.11").unwrap()
+            )]
+        }
+    );
+}
+
+#[test]
+fn toolchain_file_from_str_multiple_entries() {
+    let dir = create_test_temp_dir!();
+    let content = format!(
+        "# Versions tested with tox\n~3.11\n\n  ~3.8  \n# Fallbacks\n{}\nsystem\n",
+        dir.display()
+    );
+    let toolchain_file: ToolchainFile = content.parse().unwrap();
+    assert_eq!(
+        toolchain_file,
+        ToolchainFile {
+            entries: vec![
+                ToolchainFileEntry::VersionReq(VersionReq::parse("~3.11").unwrap()),
+                ToolchainFileEntry::VersionReq(VersionReq::parse("~3.8").unwrap()),
+                ToolchainFileEntry::Path(dir),
+                ToolchainFileEntry::System,
+            ]
+        }
+    );
+}
+
+#[test]
+fn toolchain_file_from_str_implementation() {
+    let toolchain_file: ToolchainFile = "pypy3.10\n~3.10\npypy=3.9.18\n".parse().unwrap();
+    assert_eq!(
+        toolchain_file,
+        ToolchainFile {
+            entries: vec![
+                ToolchainFileEntry::Implementation(
+                    Implementation::PyPy,
+                    VersionReq::parse("~3.10").unwrap()
+                ),
+                ToolchainFileEntry::VersionReq(VersionReq::parse("~3.10").unwrap()),
+                ToolchainFileEntry::Implementation(
+                    Implementation::PyPy,
+                    VersionReq::parse("=3.9.18").unwrap()
+                ),
+            ]
+        }
+    );
+    assert_eq!(toolchain_file.entries[0].to_string(), "pypy~3.10",);
+}
+
+#[test]
+fn toolchain_file_load_error_only_comments() {
+    let dir = create_test_temp_dir!();
+    fs::write(dir.join(TOOLCHAIN_FILE), "# Nothing selected\n\n").unwrap();
+
+    let vop: Result<Option<ToolchainFile>> = with_directory(dir, ToolchainFile::load);
+
+    assert!(matches!(
+        vop.unwrap_err().downcast_ref::<ToolchainError>(),
+        Some(ToolchainError::EmptyToolchainFile(_))
+    ));
+}
+
+fn fixture_fallback_toolchains(hygeia_home: &Path) -> Vec<InstalledToolchain> {
+    let custom_dir = hygeia_home.join("installed").join("cpython").join("3.8.10");
+    fs::create_dir_all(&custom_dir).unwrap();
+    File::create(custom_dir.join(INFO_FILE)).unwrap();
+    vec![
+        InstalledToolchain {
+            implementation: Implementation::CPython,
+            location: custom_dir.join("bin"),
+            version: Version::parse("3.8.10").unwrap(),
+        },
+        InstalledToolchain {
+            implementation: Implementation::CPython,
+            location: PathBuf::from("/usr/bin"),
+            version: Version::parse("3.6.9").unwrap(),
+        },
+    ]
+}
+
+#[test]
+fn selected_toolchain_from_toolchain_file_fallbacks() {
+    let hygeia_home = create_test_temp_dir!().join(".hygeia");
+    let installed_toolchains = fixture_fallback_toolchains(&hygeia_home);
+
+    // First entry not installed: fallback to the second one
+    let toolchain_file: ToolchainFile = "~3.11\n~3.8\n".parse().unwrap();
+    assert_eq!(
+        SelectedToolchain::from_toolchain_file(&toolchain_file, &installed_toolchains, false),
+        SelectedToolchain::InstalledToolchain(installed_toolchains[0].clone())
+    );
+
+    // 'system' skips interpreters installed by hygeia
+    let toolchain_file: ToolchainFile = "~3.11\nsystem\n~3.8\n".parse().unwrap();
+    assert_eq!(
+        SelectedToolchain::from_toolchain_file(&toolchain_file, &installed_toolchains, false),
+        SelectedToolchain::InstalledToolchain(installed_toolchains[1].clone())
+    );
+
+    // No entry satisfied: the first one is reported
+    let toolchain_file: ToolchainFile = "~3.11\n~3.10\n".parse().unwrap();
+    assert_eq!(
+        SelectedToolchain::from_toolchain_file(&toolchain_file, &installed_toolchains, false),
+        SelectedToolchain::NotInstalledToolchain(NotInstalledToolchain {
+            implementation: Implementation::CPython,
+            version: Some(VersionReq::parse("~3.11").unwrap()),
+            location: None,
+        })
+    );
+}
+
+#[test]
+fn toolchain_file_compatible_toolchain_versioned_shim() {
+    let hygeia_home = create_test_temp_dir!().join(".hygeia");
+    let installed_toolchains = fixture_fallback_toolchains(&hygeia_home);
+    let toolchain_file: ToolchainFile = "~3.11\n~3.8\nsystem\n".parse().unwrap();
+
+    // 'python3.8' resolves against the second entry
+    assert_eq!(
+        toolchain_file.compatible_toolchain(
+            &installed_toolchains,
+            Some(&VersionReq::parse("~3.8").unwrap()),
+            false
+        ),
+        Some(installed_toolchains[0].clone())
+    );
+    // 'python3.6' resolves against 'system'
+    assert_eq!(
+        toolchain_file.compatible_toolchain(
+            &installed_toolchains,
+            Some(&VersionReq::parse("~3.6").unwrap()),
+            false
+        ),
+        Some(installed_toolchains[1].clone())
+    );
+    assert_eq!(
+        toolchain_file.compatible_toolchain(
+            &installed_toolchains,
+            Some(&VersionReq::parse("~3.7").unwrap()),
+            false
+        ),
+        None
+    );
+}
+
+#[test]
+fn selected_toolchain_from_toolchain_file_implementation() {
+    let hygeia_home = create_test_temp_dir!().join(".hygeia");
+    let mut installed_toolchains = fixture_fallback_toolchains(&hygeia_home);
+    let pypy_dir = hygeia_home.join("installed").join("pypy").join("3.8.16");
+    fs::create_dir_all(&pypy_dir).unwrap();
+    File::create(pypy_dir.join(INFO_FILE)).unwrap();
+    installed_toolchains.insert(
+        0,
+        InstalledToolchain {
+            implementation: Implementation::PyPy,
+            location: pypy_dir.join("bin"),
+            version: Version::parse("3.8.16").unwrap(),
+        },
+    );
+
+    // A plain requirement never selects PyPy, even if more recent
+    let toolchain_file: ToolchainFile = "~3.8\n".parse().unwrap();
+    assert_eq!(
+        SelectedToolchain::from_toolchain_file(&toolchain_file, &installed_toolchains, false),
+        SelectedToolchain::InstalledToolchain(installed_toolchains[1].clone())
+    );
+
+    let toolchain_file: ToolchainFile = "pypy3.8\n~3.8\n".parse().unwrap();
+    assert_eq!(
+        SelectedToolchain::from_toolchain_file(&toolchain_file, &installed_toolchains, false),
+        SelectedToolchain::InstalledToolchain(installed_toolchains[0].clone())
+    );
+
+    // 'python3.6' falls back to the next entries
+    assert_eq!(
+        toolchain_file.compatible_toolchain(
+            &installed_toolchains,
+            Some(&VersionReq::parse("~3.6").unwrap()),
+            false
+        ),
+        None
+    );
+
+    let toolchain_file: ToolchainFile = "pypy3.9\n".parse().unwrap();
+    assert_eq!(
+        SelectedToolchain::from_toolchain_file(&toolchain_file, &installed_toolchains, false),
+        SelectedToolchain::NotInstalledToolchain(NotInstalledToolchain {
+            implementation: Implementation::PyPy,
+            version: Some(VersionReq::parse("~3.9").unwrap()),
+            location: None,
+        })
     );
 }
 
@@ -200,16 +473,20 @@ fn extract_version_from_command_success_py2() {
 #[test]
 fn selected_toolchain_from_toolchain_file_version_req_installed() {
     let version_req = VersionReq::parse("=3.7.4").unwrap();
-    let toolchain_file: ToolchainFile = ToolchainFile::VersionReq(version_req);
+    let toolchain_file = ToolchainFile {
+        entries: vec![ToolchainFileEntry::VersionReq(version_req)],
+    };
     let installed_toolchains: &[InstalledToolchain] = &[InstalledToolchain {
+        implementation: Implementation::CPython,
         location: PathBuf::from("/usr/bin"),
         version: Version::parse("3.7.4").unwrap(),
     }];
     let selected_toolchain =
-        SelectedToolchain::from_toolchain_file(&toolchain_file, installed_toolchains);
+        SelectedToolchain::from_toolchain_file(&toolchain_file, installed_toolchains, false);
     assert_eq!(
         selected_toolchain,
         SelectedToolchain::InstalledToolchain(InstalledToolchain {
+            implementation: Implementation::CPython,
             location: installed_toolchains[0].location.clone(),
             version: installed_toolchains[0].version.clone(),
         })
@@ -219,13 +496,16 @@ fn selected_toolchain_from_toolchain_file_version_req_installed() {
 #[test]
 fn selected_toolchain_from_toolchain_file_version_req_not_installed() {
     let version_req = VersionReq::parse("=3.7.4").unwrap();
-    let toolchain_file: ToolchainFile = ToolchainFile::VersionReq(version_req.clone());
+    let toolchain_file = ToolchainFile {
+        entries: vec![ToolchainFileEntry::VersionReq(version_req.clone())],
+    };
     let installed_toolchains: &[InstalledToolchain] = &[];
     let selected_toolchain =
-        SelectedToolchain::from_toolchain_file(&toolchain_file, installed_toolchains);
+        SelectedToolchain::from_toolchain_file(&toolchain_file, installed_toolchains, false);
     assert_eq!(
         selected_toolchain,
         SelectedToolchain::NotInstalledToolchain(NotInstalledToolchain {
+            implementation: Implementation::CPython,
             version: Some(version_req),
             location: None,
         }),
@@ -236,16 +516,20 @@ fn selected_toolchain_from_toolchain_file_version_req_not_installed() {
 fn selected_toolchain_from_toolchain_file_path_not_installed() {
     let dir = create_test_temp_dir!().canonicalize().unwrap();
 
-    let toolchain_file: ToolchainFile = ToolchainFile::Path(dir.clone());
+    let toolchain_file = ToolchainFile {
+        entries: vec![ToolchainFileEntry::Path(dir.clone())],
+    };
     let installed_toolchains: &[InstalledToolchain] = &[InstalledToolchain {
+        implementation: Implementation::CPython,
         location: dir,
         version: Version::parse("3.7.4").unwrap(),
     }];
     let selected_toolchain =
-        SelectedToolchain::from_toolchain_file(&toolchain_file, installed_toolchains);
+        SelectedToolchain::from_toolchain_file(&toolchain_file, installed_toolchains, false);
     assert_eq!(
         selected_toolchain,
         SelectedToolchain::NotInstalledToolchain(NotInstalledToolchain {
+            implementation: Implementation::CPython,
             location: Some(installed_toolchains[0].location.clone()),
             version: None,
         })
@@ -255,6 +539,7 @@ fn selected_toolchain_from_toolchain_file_path_not_installed() {
 #[test]
 fn selected_toolchain_installed_toolchain_version_req() {
     let selected_toolchain = SelectedToolchain::InstalledToolchain(InstalledToolchain {
+        implementation: Implementation::CPython,
         location: PathBuf::from("/usr/bin"),
         version: Version::parse("3.7.4").unwrap(),
     });
@@ -267,6 +552,7 @@ fn selected_toolchain_installed_toolchain_version_req() {
 #[test]
 fn selected_toolchain_not_installed_toolchain_version_req_some() {
     let selected_toolchain = SelectedToolchain::NotInstalledToolchain(NotInstalledToolchain {
+        implementation: Implementation::CPython,
         location: None,
         version: Some(VersionReq::parse("=3.7.4").unwrap()),
     });
@@ -279,6 +565,7 @@ fn selected_toolchain_not_installed_toolchain_version_req_some() {
 #[test]
 fn selected_toolchain_not_installed_toolchain_version_req_none() {
     let selected_toolchain = SelectedToolchain::NotInstalledToolchain(NotInstalledToolchain {
+        implementation: Implementation::CPython,
         location: Some(PathBuf::from("/usr/bin")),
         version: None,
     });
@@ -288,6 +575,7 @@ fn selected_toolchain_not_installed_toolchain_version_req_none() {
 #[test]
 fn selected_toolchain_installed_toolchain_is_installed_true() {
     let selected_toolchain = SelectedToolchain::InstalledToolchain(InstalledToolchain {
+        implementation: Implementation::CPython,
         location: PathBuf::from("/usr/bin"),
         version: Version::parse("3.7.4").unwrap(),
     });
@@ -297,6 +585,7 @@ fn selected_toolchain_installed_toolchain_is_installed_true() {
 #[test]
 fn selected_toolchain_installed_toolchain_is_installed_false() {
     let selected_toolchain = SelectedToolchain::NotInstalledToolchain(NotInstalledToolchain {
+        implementation: Implementation::CPython,
         location: None,
         version: None,
     });
@@ -308,6 +597,7 @@ fn selected_toolchain_installed_toolchain_same_version_true() {
     let version_req = VersionReq::parse("=3.7.4").unwrap();
 
     let selected_toolchain = SelectedToolchain::InstalledToolchain(InstalledToolchain {
+        implementation: Implementation::CPython,
         location: PathBuf::from("/usr/bin"),
         version: Version::parse("3.7.4").unwrap(),
     });
@@ -319,6 +609,7 @@ fn selected_toolchain_installed_toolchain_same_version_false() {
     let version_req = VersionReq::parse("=2.7.4").unwrap();
 
     let selected_toolchain = SelectedToolchain::InstalledToolchain(InstalledToolchain {
+        implementation: Implementation::CPython,
         location: PathBuf::from("/usr/bin"),
         version: Version::parse("3.7.4").unwrap(),
     });
@@ -330,6 +621,7 @@ fn selected_toolchain_not_installed_toolchain_same_version_version_true() {
     let version_req = VersionReq::parse("=3.7.4").unwrap();
 
     let selected_toolchain = SelectedToolchain::NotInstalledToolchain(NotInstalledToolchain {
+        implementation: Implementation::CPython,
         location: None,
         version: Some(VersionReq::parse("=3.7.4").unwrap()),
     });
@@ -341,6 +633,7 @@ fn selected_toolchain_not_installed_toolchain_same_version_version_false() {
     let version_req = VersionReq::parse("=3.7.4").unwrap();
 
     let selected_toolchain = SelectedToolchain::NotInstalledToolchain(NotInstalledToolchain {
+        implementation: Implementation::CPython,
         location: None,
         version: Some(VersionReq::parse("3.7.4").unwrap()),
     });
@@ -352,6 +645,7 @@ fn selected_toolchain_not_installed_toolchain_same_version_none_false() {
     let version_req = VersionReq::parse("=3.7.4").unwrap();
 
     let selected_toolchain = SelectedToolchain::NotInstalledToolchain(NotInstalledToolchain {
+        implementation: Implementation::CPython,
         location: None,
         version: None,
     });
@@ -363,6 +657,7 @@ fn selected_toolchain_installed_toolchain_same_location_true() {
     let location = PathBuf::from("/usr/bin");
 
     let selected_toolchain = SelectedToolchain::InstalledToolchain(InstalledToolchain {
+        implementation: Implementation::CPython,
         location: location.clone(),
         version: Version::parse("3.7.4").unwrap(),
     });
@@ -374,6 +669,7 @@ fn selected_toolchain_installed_toolchain_same_location_false() {
     let location = PathBuf::from("/usr/bin");
 
     let selected_toolchain = SelectedToolchain::InstalledToolchain(InstalledToolchain {
+        implementation: Implementation::CPython,
         location: PathBuf::from("/usr/local/bin"),
         version: Version::parse("3.7.4").unwrap(),
     });
@@ -385,6 +681,7 @@ fn selected_toolchain_not_installed_toolchain_same_location_some_true() {
     let location = PathBuf::from("/usr/bin");
 
     let selected_toolchain = SelectedToolchain::NotInstalledToolchain(NotInstalledToolchain {
+        implementation: Implementation::CPython,
         location: Some(location.clone()),
         version: None,
     });
@@ -396,6 +693,7 @@ fn selected_toolchain_not_installed_toolchain_same_location_some_false() {
     let location = PathBuf::from("/usr/bin");
 
     let selected_toolchain = SelectedToolchain::NotInstalledToolchain(NotInstalledToolchain {
+        implementation: Implementation::CPython,
         location: Some(location.join("different")),
         version: None,
     });
@@ -407,6 +705,7 @@ fn selected_toolchain_not_installed_toolchain_same_location_none_false() {
     let location = PathBuf::from("/usr/bin");
 
     let selected_toolchain = SelectedToolchain::NotInstalledToolchain(NotInstalledToolchain {
+        implementation: Implementation::CPython,
         location: None,
         version: None,
     });
@@ -718,7 +1017,7 @@ fn find_installed_toolchains_absent_dir() {
 
     let mut mock = MockPycorsHomeProviderTrait::new();
     mock.expect_project_home()
-        .times(1)
+        .times(3) // Installed toolchains (CPython and PyPy) and interpreter index
         .return_const(mocked_hygeia_home);
     mock.expect_home().times(0).return_const(mocked_home);
     mock.expect_paths().times(1).return_const(mocked_paths);
@@ -746,7 +1045,7 @@ fn find_installed_toolchains_empty_installed_dir() {
 
     let mut mock = MockPycorsHomeProviderTrait::new();
     mock.expect_project_home()
-        .times(2)
+        .times(4) // +1 for PyPy, +1 for the interpreter index
         .return_const(mocked_hygeia_home);
     mock.expect_home().times(0).return_const(mocked_home);
     mock.expect_paths().times(1).return_const(mocked_paths);
@@ -777,7 +1076,7 @@ fn find_installed_toolchains_dummy_custom_installs() {
 
     let mut mock = MockPycorsHomeProviderTrait::new();
     mock.expect_project_home()
-        .times(4)
+        .times(6) // +1 for PyPy, +1 for the interpreter index
         .return_const(mocked_hygeia_home);
     mock.expect_home().times(0).return_const(mocked_home);
     mock.expect_paths().times(1).return_const(mocked_paths);
@@ -823,6 +1122,7 @@ fn find_installed_toolchains_dummy_custom_installs() {
     assert_eq!(
         found_installed_toolchains[0],
         InstalledToolchain {
+            implementation: Implementation::CPython,
             location: expected_installed_dir("3.7.5"),
             version: Version::parse("3.7.5").unwrap()
         }
@@ -831,6 +1131,7 @@ fn find_installed_toolchains_dummy_custom_installs() {
     assert_eq!(
         found_installed_toolchains[1],
         InstalledToolchain {
+            implementation: Implementation::CPython,
             location: expected_installed_dir("3.7.4"),
             version: Version::parse("3.7.4").unwrap()
         }
@@ -854,7 +1155,7 @@ fn find_installed_toolchains_dummy_system_installs() {
 
     let mut mock = MockPycorsHomeProviderTrait::new();
     mock.expect_project_home()
-        .times(4)
+        .times(8) // +1 for PyPy, +3 for the interpreter index (loaded and saved)
         .return_const(mocked_hygeia_home);
     mock.expect_home().times(0).return_const(mocked_home);
     mock.expect_paths().times(1).return_const(mocked_paths);
@@ -910,6 +1211,7 @@ fn find_installed_toolchains_dummy_system_installs() {
     assert_eq!(
         found_installed_toolchains[0],
         InstalledToolchain {
+            implementation: Implementation::CPython,
             location: mocked_usr_local_bin,
             version: Version::parse("3.7.5").unwrap()
         }
@@ -918,6 +1220,7 @@ fn find_installed_toolchains_dummy_system_installs() {
     assert_eq!(
         found_installed_toolchains[1],
         InstalledToolchain {
+            implementation: Implementation::CPython,
             location: mocked_usr_bin,
             version: Version::parse("2.7.17").unwrap()
         }
@@ -927,48 +1230,117 @@ fn find_installed_toolchains_dummy_system_installs() {
 #[test]
 fn find_compatible_toolchain_macos_default() {
     let installed_toolchains: &[InstalledToolchain] = &[InstalledToolchain {
+        implementation: Implementation::CPython,
         location: PathBuf::from("/usr/bin"),
         version: Version::parse("2.7.17").unwrap(),
     }];
 
     // No Python 3 available by default on macOS (Mojave)
     assert_eq!(
-        find_compatible_toolchain(&VersionReq::parse("3").unwrap(), installed_toolchains),
+        find_compatible_toolchain(
+            &VersionReq::parse("3").unwrap(),
+            installed_toolchains,
+            false
+        ),
         None
     );
     assert_eq!(
-        find_compatible_toolchain(&VersionReq::parse("^3").unwrap(), installed_toolchains),
+        find_compatible_toolchain(
+            &VersionReq::parse("^3").unwrap(),
+            installed_toolchains,
+            false
+        ),
         None
     );
     assert_eq!(
-        find_compatible_toolchain(&VersionReq::parse("=3.7.5").unwrap(), installed_toolchains),
+        find_compatible_toolchain(
+            &VersionReq::parse("=3.7.5").unwrap(),
+            installed_toolchains,
+            false
+        ),
         None
     );
 
     assert_eq!(
-        find_compatible_toolchain(&VersionReq::parse("2").unwrap(), installed_toolchains),
+        find_compatible_toolchain(
+            &VersionReq::parse("2").unwrap(),
+            installed_toolchains,
+            false
+        ),
         Some(&installed_toolchains[0])
     );
     assert_eq!(
-        find_compatible_toolchain(&VersionReq::parse("^2").unwrap(), installed_toolchains),
+        find_compatible_toolchain(
+            &VersionReq::parse("^2").unwrap(),
+            installed_toolchains,
+            false
+        ),
         Some(&installed_toolchains[0])
     );
     assert_eq!(
-        find_compatible_toolchain(&VersionReq::parse("~2").unwrap(), installed_toolchains),
+        find_compatible_toolchain(
+            &VersionReq::parse("~2").unwrap(),
+            installed_toolchains,
+            false
+        ),
         Some(&installed_toolchains[0])
     );
     assert_eq!(
-        find_compatible_toolchain(&VersionReq::parse("^2.7").unwrap(), installed_toolchains),
+        find_compatible_toolchain(
+            &VersionReq::parse("^2.7").unwrap(),
+            installed_toolchains,
+            false
+        ),
         Some(&installed_toolchains[0])
     );
     assert_eq!(
-        find_compatible_toolchain(&VersionReq::parse("~2.7").unwrap(), installed_toolchains),
+        find_compatible_toolchain(
+            &VersionReq::parse("~2.7").unwrap(),
+            installed_toolchains,
+            false
+        ),
+        Some(&installed_toolchains[0])
+    );
+    assert_eq!(
+        find_compatible_toolchain(
+            &VersionReq::parse("=2.7.17").unwrap(),
+            installed_toolchains,
+            false
+        ),
+        Some(&installed_toolchains[0])
+    );
+}
+
+#[test]
+fn latest_installed_pre_releases() {
+    let toolchain = |version: &str| InstalledToolchain {
+        implementation: Implementation::CPython,
+        location: PathBuf::from("/usr/bin"),
+        version: Version::parse(version).unwrap(),
+    };
+    let installed_toolchains = &[
+        toolchain("3.13.0-rc1"),
+        toolchain("3.12.4"),
+        toolchain("3.11.9"),
+    ];
+
+    assert_eq!(
+        latest_installed(installed_toolchains, Implementation::CPython, false),
+        Some(&installed_toolchains[1])
+    );
+    assert_eq!(
+        latest_installed(installed_toolchains, Implementation::CPython, true),
         Some(&installed_toolchains[0])
     );
+    // Only a pre-release installed
     assert_eq!(
-        find_compatible_toolchain(&VersionReq::parse("=2.7.17").unwrap(), installed_toolchains),
+        latest_installed(&installed_toolchains[..1], Implementation::CPython, false),
         Some(&installed_toolchains[0])
     );
+    assert_eq!(
+        latest_installed(installed_toolchains, Implementation::PyPy, false),
+        None
+    );
 }
 
 #[test]
@@ -978,10 +1350,12 @@ fn find_compatible_toolchain_multiple() {
 
     let installed_toolchains: &[InstalledToolchain] = &[
         InstalledToolchain {
+            implementation: Implementation::CPython,
             location: PathBuf::from("/usr/local/bin"),
             version: Version::parse("3.7.5").unwrap(),
         },
         InstalledToolchain {
+            implementation: Implementation::CPython,
             location: hygeia_home
                 .join("installed")
                 .join("cpython")
@@ -990,6 +1364,7 @@ fn find_compatible_toolchain_multiple() {
             version: Version::parse("3.7.4").unwrap(),
         },
         InstalledToolchain {
+            implementation: Implementation::CPython,
             location: hygeia_home
                 .join("installed")
                 .join("cpython")
@@ -998,64 +1373,117 @@ fn find_compatible_toolchain_multiple() {
             version: Version::parse("3.8.0").unwrap(),
         },
         InstalledToolchain {
+            implementation: Implementation::CPython,
             location: PathBuf::from("/usr/bin"),
             version: Version::parse("2.7.17").unwrap(),
         },
     ];
 
     assert_eq!(
-        find_compatible_toolchain(&VersionReq::parse("~3.7").unwrap(), installed_toolchains),
+        find_compatible_toolchain(
+            &VersionReq::parse("~3.7").unwrap(),
+            installed_toolchains,
+            false
+        ),
         Some(&installed_toolchains[0])
     );
     assert_eq!(
-        find_compatible_toolchain(&VersionReq::parse("=3.7.5").unwrap(), installed_toolchains),
+        find_compatible_toolchain(
+            &VersionReq::parse("=3.7.5").unwrap(),
+            installed_toolchains,
+            false
+        ),
         Some(&installed_toolchains[0])
     );
 
     assert_eq!(
-        find_compatible_toolchain(&VersionReq::parse("=3.7.4").unwrap(), installed_toolchains),
+        find_compatible_toolchain(
+            &VersionReq::parse("=3.7.4").unwrap(),
+            installed_toolchains,
+            false
+        ),
         Some(&installed_toolchains[1])
     );
 
     assert_eq!(
-        find_compatible_toolchain(&VersionReq::parse("3").unwrap(), installed_toolchains),
+        find_compatible_toolchain(
+            &VersionReq::parse("3").unwrap(),
+            installed_toolchains,
+            false
+        ),
         Some(&installed_toolchains[2])
     );
     assert_eq!(
-        find_compatible_toolchain(&VersionReq::parse("^3").unwrap(), installed_toolchains),
+        find_compatible_toolchain(
+            &VersionReq::parse("^3").unwrap(),
+            installed_toolchains,
+            false
+        ),
         Some(&installed_toolchains[2])
     );
     assert_eq!(
-        find_compatible_toolchain(&VersionReq::parse("~3").unwrap(), installed_toolchains),
+        find_compatible_toolchain(
+            &VersionReq::parse("~3").unwrap(),
+            installed_toolchains,
+            false
+        ),
         Some(&installed_toolchains[2])
     );
     assert_eq!(
-        find_compatible_toolchain(&VersionReq::parse("~3.8").unwrap(), installed_toolchains),
+        find_compatible_toolchain(
+            &VersionReq::parse("~3.8").unwrap(),
+            installed_toolchains,
+            false
+        ),
         Some(&installed_toolchains[2])
     );
     assert_eq!(
-        find_compatible_toolchain(&VersionReq::parse("=3.8.0").unwrap(), installed_toolchains),
+        find_compatible_toolchain(
+            &VersionReq::parse("=3.8.0").unwrap(),
+            installed_toolchains,
+            false
+        ),
         Some(&installed_toolchains[2])
     );
 
     assert_eq!(
-        find_compatible_toolchain(&VersionReq::parse("2").unwrap(), installed_toolchains),
+        find_compatible_toolchain(
+            &VersionReq::parse("2").unwrap(),
+            installed_toolchains,
+            false
+        ),
         Some(&installed_toolchains[3])
     );
     assert_eq!(
-        find_compatible_toolchain(&VersionReq::parse("^2").unwrap(), installed_toolchains),
+        find_compatible_toolchain(
+            &VersionReq::parse("^2").unwrap(),
+            installed_toolchains,
+            false
+        ),
         Some(&installed_toolchains[3])
     );
     assert_eq!(
-        find_compatible_toolchain(&VersionReq::parse("~2").unwrap(), installed_toolchains),
+        find_compatible_toolchain(
+            &VersionReq::parse("~2").unwrap(),
+            installed_toolchains,
+            false
+        ),
         Some(&installed_toolchains[3])
     );
     assert_eq!(
-        find_compatible_toolchain(&VersionReq::parse("~2.7").unwrap(), installed_toolchains),
+        find_compatible_toolchain(
+            &VersionReq::parse("~2.7").unwrap(),
+            installed_toolchains,
+            false
+        ),
         Some(&installed_toolchains[3])
     );
     assert_eq!(
-        find_compatible_toolchain(&VersionReq::parse("=2.7.17").unwrap(), installed_toolchains),
+        find_compatible_toolchain(
+            &VersionReq::parse("=2.7.17").unwrap(),
+            installed_toolchains,
+            false
+        ),
         Some(&installed_toolchains[3])
     );
 }
@@ -1067,10 +1495,12 @@ fn find_compatible_toolchain_same_system_custom() {
 
     let installed_toolchains: &[InstalledToolchain] = &[
         InstalledToolchain {
+            implementation: Implementation::CPython,
             location: PathBuf::from("/usr/local/bin"),
             version: Version::parse("3.7.5").unwrap(),
         },
         InstalledToolchain {
+            implementation: Implementation::CPython,
             location: hygeia_home
                 .join("installed")
                 .join("cpython")
@@ -1079,6 +1509,7 @@ fn find_compatible_toolchain_same_system_custom() {
             version: Version::parse("3.7.5").unwrap(),
         },
         InstalledToolchain {
+            implementation: Implementation::CPython,
             location: hygeia_home
                 .join("installed")
                 .join("cpython")
@@ -1087,6 +1518,7 @@ fn find_compatible_toolchain_same_system_custom() {
             version: Version::parse("4.0.0").unwrap(),
         },
         InstalledToolchain {
+            implementation: Implementation::CPython,
             location: PathBuf::from("/usr/bin"),
             version: Version::parse("2.7.17").unwrap(),
         },
@@ -1105,27 +1537,118 @@ fn find_compatible_toolchain_same_system_custom() {
     }
 
     assert_eq!(
-        find_compatible_toolchain(&VersionReq::parse("3").unwrap(), installed_toolchains),
+        find_compatible_toolchain(
+            &VersionReq::parse("3").unwrap(),
+            installed_toolchains,
+            false
+        ),
         Some(&installed_toolchains[1])
     );
     assert_eq!(
-        find_compatible_toolchain(&VersionReq::parse("^3").unwrap(), installed_toolchains),
+        find_compatible_toolchain(
+            &VersionReq::parse("^3").unwrap(),
+            installed_toolchains,
+            false
+        ),
         Some(&installed_toolchains[1])
     );
     assert_eq!(
-        find_compatible_toolchain(&VersionReq::parse("~3").unwrap(), installed_toolchains),
+        find_compatible_toolchain(
+            &VersionReq::parse("~3").unwrap(),
+            installed_toolchains,
+            false
+        ),
         Some(&installed_toolchains[1])
     );
     assert_eq!(
-        find_compatible_toolchain(&VersionReq::parse("~3.7").unwrap(), installed_toolchains),
+        find_compatible_toolchain(
+            &VersionReq::parse("~3.7").unwrap(),
+            installed_toolchains,
+            false
+        ),
         Some(&installed_toolchains[1])
     );
     assert_eq!(
-        find_compatible_toolchain(&VersionReq::parse("=3.7.5").unwrap(), installed_toolchains),
+        find_compatible_toolchain(
+            &VersionReq::parse("=3.7.5").unwrap(),
+            installed_toolchains,
+            false
+        ),
         Some(&installed_toolchains[1])
     );
 }
 
+#[test]
+fn find_compatible_toolchain_pre_releases() {
+    let installed_toolchains: &[InstalledToolchain] = &[
+        InstalledToolchain {
+            implementation: Implementation::CPython,
+            location: PathBuf::from("/opt/python3.12.1rc1/bin"),
+            version: Version::parse("3.12.1-rc1").unwrap(),
+        },
+        InstalledToolchain {
+            implementation: Implementation::CPython,
+            location: PathBuf::from("/opt/python3.12.0/bin"),
+            version: Version::parse("3.12.0").unwrap(),
+        },
+        InstalledToolchain {
+            implementation: Implementation::CPython,
+            location: PathBuf::from("/opt/python3.13.0a1/bin"),
+            version: Version::parse("3.13.0-a1").unwrap(),
+        },
+    ];
+
+    // Pre-releases are ignored unless requested
+    assert_eq!(
+        find_compatible_toolchain(
+            &VersionReq::parse("~3.13").unwrap(),
+            installed_toolchains,
+            false
+        ),
+        None
+    );
+    assert_eq!(
+        find_compatible_toolchain(
+            &VersionReq::parse("3").unwrap(),
+            installed_toolchains,
+            false
+        ),
+        Some(&installed_toolchains[1])
+    );
+    // ...or explicitly requested
+    assert_eq!(
+        find_compatible_toolchain(
+            &VersionReq::parse("=3.13.0-a1").unwrap(),
+            installed_toolchains,
+            false
+        ),
+        Some(&installed_toolchains[2])
+    );
+
+    assert_eq!(
+        find_compatible_toolchain(
+            &VersionReq::parse("~3.13").unwrap(),
+            installed_toolchains,
+            true
+        ),
+        Some(&installed_toolchains[2])
+    );
+    // A final release is preferred over a pre-release of the same series
+    assert_eq!(
+        find_compatible_toolchain(
+            &VersionReq::parse("~3.12").unwrap(),
+            installed_toolchains,
+            true
+        ),
+        Some(&installed_toolchains[1])
+    );
+    // ...but not over a pre-release of a more recent series
+    assert_eq!(
+        find_compatible_toolchain(&VersionReq::parse("3").unwrap(), installed_toolchains, true),
+        Some(&installed_toolchains[2])
+    );
+}
+
 #[test]
 fn compatible_toolchain_builder_load_from_string() {
     let home = create_test_temp_dir!();
@@ -1139,7 +1662,7 @@ fn compatible_toolchain_builder_load_from_string() {
 
     let mut mock = MockPycorsHomeProviderTrait::new();
     mock.expect_project_home()
-        .times(1)
+        .times(4) // Installed toolchains (CPython and PyPy), interpreter index and configuration file
         .return_const(mocked_hygeia_home);
     mock.expect_home().times(0).return_const(mocked_home);
     mock.expect_paths().times(1).return_const(mocked_paths);
@@ -1154,3 +1677,64 @@ fn compatible_toolchain_builder_load_from_string() {
 
     assert!(compatible_toolchain.is_none());
 }
+
+#[test]
+fn compatible_toolchain_builder_requested_not_installed() {
+    let home = create_test_temp_dir!();
+    let hygeia_home = home.join(".hygeia");
+    fs::create_dir_all(hygeia_home.join("installed").join("cpython").join("3.8.1")).unwrap();
+
+    let mocked_home = Some(home.clone());
+    let mocked_hygeia_home = Some(hygeia_home);
+    let mocked_paths = vec![home.join("usr_bin")];
+
+    let mut mock = MockPycorsHomeProviderTrait::new();
+    mock.expect_project_home()
+        .times(5) // Installed toolchains (CPython and PyPy), 3.8.1's bin directory, interpreter index and configuration file
+        .return_const(mocked_hygeia_home);
+    mock.expect_home().times(0).return_const(mocked_home);
+    mock.expect_paths().times(1).return_const(mocked_paths);
+    let paths_provider = PycorsPathsProvider::from(mock);
+    // Another version than the requested one is never run
+    let compatible_toolchain = CompatibleToolchainBuilder::new()
+        .load_from_string("~3.6")
+        .pick_latest_if_none_found()
+        .compatible_version(paths_provider)
+        .unwrap();
+
+    assert!(compatible_toolchain.is_none());
+}
+
+#[test]
+fn compatible_toolchain_builder_other_implementation_not_installed() {
+    let home = create_test_temp_dir!();
+    let hygeia_home = home.join(".hygeia");
+    fs::create_dir_all(hygeia_home.join("installed").join("cpython").join("3.10.1")).unwrap();
+    let paths_provider = || {
+        let mut mock = MockPycorsHomeProviderTrait::new();
+        mock.expect_project_home()
+            .times(5) // Installed toolchains (CPython and PyPy), 3.10.1's bin directory, interpreter index and configuration file
+            .return_const(Some(hygeia_home.clone()));
+        mock.expect_home().times(0).return_const(Some(home.clone()));
+        mock.expect_paths()
+            .times(1)
+            .return_const(vec![home.join("usr_bin")]);
+        PycorsPathsProvider::from(mock)
+    };
+
+    // PyPy is requested: CPython is never run instead
+    let compatible_toolchain = CompatibleToolchainBuilder::new()
+        .load_from_string("pypy3.10")
+        .pick_latest_if_none_found()
+        .compatible_version(paths_provider())
+        .unwrap();
+    assert!(compatible_toolchain.is_none());
+
+    let compatible_toolchain = CompatibleToolchainBuilder::new()
+        .load_from_string("pypy3.10")
+        .overwrite(Some(VersionReq::parse("3").unwrap()))
+        .pick_latest_if_none_found()
+        .compatible_version(paths_provider())
+        .unwrap();
+    assert!(compatible_toolchain.is_none());
+}
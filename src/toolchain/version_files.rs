@@ -0,0 +1,393 @@
+//! Python version requirements declared in files used by other tools
+//! (`pyproject.toml`, asdf's `.tool-versions` and Heroku's `runtime.txt`).
+
+use std::path::Path;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use semver::VersionReq;
+use thiserror::Error;
+
+use crate::{
+    constants::{PYPROJECT_FILE, RUNTIME_FILE, TOOL_VERSIONS_FILE},
+    toolchain::{ToolchainFileEntry, SYSTEM_KEYWORD},
+    Result,
+};
+
+#[derive(Debug, Error)]
+pub enum VersionFileError {
+    #[error("Unsupported version specifier {0:?}")]
+    UnsupportedSpecifier(String),
+    #[error("Expected a line like 'python-3.8.1', got {0:?}")]
+    InvalidRuntime(String),
+    #[error("Expected a string for {0:?}")]
+    NotAString(&'static str),
+}
+
+/// Entries declared in `content`, read from a file named `filename`.
+///
+/// An empty list is returned if the file does not declare a Python version.
+pub fn parse(filename: &str, content: &str) -> Result<Vec<ToolchainFileEntry>> {
+    match filename {
+        PYPROJECT_FILE => parse_pyproject(content),
+        TOOL_VERSIONS_FILE => parse_tool_versions(content),
+        RUNTIME_FILE => parse_runtime(content),
+        _ => unreachable!("Unexpected version file {:?}", filename),
+    }
+}
+
+/// `project.requires-python` (PEP 621), or `tool.poetry.dependencies.python`.
+pub fn parse_pyproject(content: &str) -> Result<Vec<ToolchainFileEntry>> {
+    let pyproject: toml::Value = toml::from_str(content)?;
+
+    if let Some(requires_python) = pyproject
+        .get("project")
+        .and_then(|project| project.get("requires-python"))
+    {
+        let requires_python = requires_python
+            .as_str()
+            .ok_or(VersionFileError::NotAString("project.requires-python"))?;
+        let version_req = pep440_to_version_req(requires_python)?;
+        return Ok(vec![ToolchainFileEntry::VersionReq(version_req)]);
+    }
+
+    if let Some(python) = pyproject
+        .get("tool")
+        .and_then(|tool| tool.get("poetry"))
+        .and_then(|poetry| poetry.get("dependencies"))
+        .and_then(|dependencies| dependencies.get("python"))
+    {
+        let python = python.as_str().ok_or(VersionFileError::NotAString(
+            "tool.poetry.dependencies.python",
+        ))?;
+        return Ok(poetry_to_version_reqs(python)?
+            .into_iter()
+            .map(ToolchainFileEntry::VersionReq)
+            .collect());
+    }
+
+    Ok(Vec::new())
+}
+
+/// All versions listed on the `python` line(s), in order.
+pub fn parse_tool_versions(content: &str) -> Result<Vec<ToolchainFileEntry>> {
+    let mut entries = Vec::new();
+    for line in content.lines() {
+        let line = line.split('#').next().unwrap_or_default();
+        let mut words = line.split_whitespace();
+        if words.next() != Some("python") {
+            continue;
+        }
+        for version in words {
+            if version == SYSTEM_KEYWORD {
+                entries.push(ToolchainFileEntry::System);
+            } else if let Some(path) = version.strip_prefix("path:") {
+                entries.push(ToolchainFileEntry::Path(Path::new(path).join("bin")));
+            } else if version == "latest" {
+                entries.push(ToolchainFileEntry::VersionReq(VersionReq::STAR));
+            } else {
+                let version = version.strip_prefix("latest:").unwrap_or(version);
+                match python_version_to_semver(version) {
+                    Some(version) => entries.push(ToolchainFileEntry::VersionReq(
+                        VersionReq::parse(&format!("={}", version))?,
+                    )),
+                    None => log::warn!("Ignoring unsupported Python version {:?}", version),
+                }
+            }
+        }
+    }
+    Ok(entries)
+}
+
+/// Heroku's `runtime.txt`, containing a single line like `python-3.8.1`.
+pub fn parse_runtime(content: &str) -> Result<Vec<ToolchainFileEntry>> {
+    let line = content.trim();
+    let version = line
+        .strip_prefix("python-")
+        .and_then(python_version_to_semver)
+        .ok_or_else(|| VersionFileError::InvalidRuntime(line.to_string()))?;
+    Ok(vec![ToolchainFileEntry::VersionReq(VersionReq::parse(
+        &format!("={}", version),
+    )?)])
+}
+
+/// Translate a PEP 440 specifier set (like `>=3.8,<3.11`) into a version requirement.
+///
+/// Exclusions (`!=`) cannot be expressed and are ignored.
+pub fn pep440_to_version_req(specifiers: &str) -> Result<VersionReq> {
+    let mut comparators = Vec::new();
+    for clause in clauses(specifiers) {
+        comparators.extend(pep440_clause(&clause)?);
+    }
+    to_version_req(specifiers, &comparators)
+}
+
+/// Translate a Poetry constraint into version requirements, one per `||` alternative.
+pub fn poetry_to_version_reqs(constraint: &str) -> Result<Vec<VersionReq>> {
+    constraint
+        .split("||")
+        .map(|alternative| {
+            let mut comparators = Vec::new();
+            for clause in clauses(alternative) {
+                if clause == "*" {
+                    continue;
+                } else if clause.starts_with('^')
+                    || (clause.starts_with('~') && !clause.starts_with("~="))
+                {
+                    // Same meaning as in semver
+                    comparators.push(clause);
+                } else if clause.starts_with(|c: char| c.is_ascii_digit()) {
+                    // A bare version is an exact requirement
+                    comparators.extend(pep440_clause(&format!("=={}", clause))?);
+                } else {
+                    comparators.extend(pep440_clause(&clause)?);
+                }
+            }
+            to_version_req(alternative, &comparators)
+        })
+        .collect()
+}
+
+fn to_version_req(specifiers: &str, comparators: &[String]) -> Result<VersionReq> {
+    let version_req = if comparators.is_empty() {
+        VersionReq::STAR
+    } else {
+        VersionReq::parse(&comparators.join(", "))?
+    };
+    log::debug!("Translated {:?} into {}", specifiers, version_req);
+    Ok(version_req)
+}
+
+/// Split specifiers on commas and whitespace, keeping operators with their version.
+fn clauses(specifiers: &str) -> Vec<String> {
+    let mut clauses: Vec<String> = Vec::new();
+    let mut pending_operator = String::new();
+    for token in specifiers.split(|c: char| c == ',' || c.is_whitespace()) {
+        if token.is_empty() {
+            continue;
+        }
+        if token.chars().all(|c| "<>=!~^".contains(c)) {
+            pending_operator.push_str(token);
+        } else {
+            clauses.push(format!("{}{}", pending_operator, token));
+            pending_operator.clear();
+        }
+    }
+    clauses
+}
+
+/// Semver comparators equivalent to a single PEP 440 clause.
+fn pep440_clause(clause: &str) -> Result<Vec<String>> {
+    let unsupported = || VersionFileError::UnsupportedSpecifier(clause.to_string());
+
+    let operator_len = clause
+        .find(|c: char| !"<>=!~".contains(c))
+        .ok_or_else(unsupported)?;
+    let (operator, version) = clause.split_at(operator_len);
+    let version = version.trim();
+
+    if operator == "!=" {
+        log::warn!("Ignoring exclusion {:?}", clause);
+        return Ok(Vec::new());
+    }
+
+    if let Some(prefix) = version.strip_suffix(".*") {
+        // Prefix matching, only valid with '==': semver's partial versions match the same way.
+        let prefix = python_version_to_semver(prefix).ok_or_else(unsupported)?;
+        return match operator {
+            "==" => Ok(vec![format!("={}", prefix)]),
+            _ => Err(unsupported().into()),
+        };
+    }
+
+    let version = python_version_to_semver(version).ok_or_else(unsupported)?;
+    let release: Vec<u64> = version
+        .split('-')
+        .next()
+        .unwrap_or_default()
+        .split('.')
+        .map(|part| part.parse::<u64>())
+        .collect::<std::result::Result<_, _>>()
+        .map_err(|_| unsupported())?;
+    // PEP 440 pads missing components with zeros ('<=3.8' means '<=3.8.0')
+    let padded = pad(&version, release.len());
+
+    match operator {
+        "~=" => {
+            // '~=3.8' means '>=3.8, ==3.*' and '~=3.8.1' means '>=3.8.1, ==3.8.*'
+            if release.len() < 2 {
+                return Err(unsupported().into());
+            }
+            let upper: Vec<String> = release[..release.len() - 2]
+                .iter()
+                .map(u64::to_string)
+                .chain(std::iter::once(
+                    (release[release.len() - 2] + 1).to_string(),
+                ))
+                .collect();
+            Ok(vec![
+                format!(">={}", padded),
+                format!("<{}", pad(&upper.join("."), upper.len())),
+            ])
+        }
+        "==" | "===" => Ok(vec![format!("={}", padded)]),
+        ">=" | "<=" | ">" | "<" => Ok(vec![format!("{}{}", operator, padded)]),
+        _ => Err(unsupported().into()),
+    }
+}
+
+/// Pad a version with `.0` up to three release components.
+fn pad(version: &str, nb_components: usize) -> String {
+    let (release, pre) = match version.find('-') {
+        Some(index) => version.split_at(index),
+        None => (version, ""),
+    };
+    let padding = ".0".repeat(3usize.saturating_sub(nb_components));
+    format!("{}{}{}", release, padding, pre)
+}
+
+/// Convert a Python version (like `3.8`, `3.8.1` or `3.9.0rc1`) to its semver notation.
+///
+/// Returns `None` for anything not describing a CPython release.
+fn python_version_to_semver(version: &str) -> Option<String> {
+    lazy_static! {
+        static ref RE: Regex =
+            Regex::new(r"^v?(\d+(?:\.\d+){0,2})(?:(a|b|rc)(\d+))?$").expect("valid regex");
+    }
+    let captures = RE.captures(version.trim())?;
+    let release = captures.get(1)?.as_str();
+    match (captures.get(2), captures.get(3)) {
+        (Some(pre), Some(number)) => Some(format!(
+            "{}-{}{}",
+            pad(release, release.split('.').count()),
+            pre.as_str(),
+            number.as_str()
+        )),
+        _ => Some(release.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn req(s: &str) -> VersionReq {
+        VersionReq::parse(s).unwrap()
+    }
+
+    #[test]
+    fn pep440_specifiers() {
+        for (specifiers, expected) in &[
+            (">=3.8", ">=3.8.0"),
+            (">=3.8,<3.11", ">=3.8.0, <3.11.0"),
+            (">= 3.8, != 3.9.1, < 4", ">=3.8.0, <4.0.0"),
+            ("~=3.8", ">=3.8.0, <4.0.0"),
+            ("~=3.8.1", ">=3.8.1, <3.9.0"),
+            ("==3.10.*", "=3.10"),
+            ("==3.10", "=3.10.0"),
+            ("<=3.8", "<=3.8.0"),
+            (">3.9.0rc1", ">3.9.0-rc1"),
+            ("", "*"),
+        ] {
+            assert_eq!(
+                pep440_to_version_req(specifiers).unwrap(),
+                req(expected),
+                "{}",
+                specifiers
+            );
+        }
+    }
+
+    #[test]
+    fn pep440_unsupported() {
+        for specifiers in &[">=3.*", "==pypy3", "~=3", "=>3.8"] {
+            let err = pep440_to_version_req(specifiers).unwrap_err();
+            assert!(
+                matches!(
+                    err.downcast_ref::<VersionFileError>(),
+                    Some(VersionFileError::UnsupportedSpecifier(_))
+                ),
+                "{}",
+                specifiers
+            );
+        }
+    }
+
+    #[test]
+    fn poetry_constraints() {
+        assert_eq!(poetry_to_version_reqs("^3.8").unwrap(), vec![req("^3.8")]);
+        assert_eq!(poetry_to_version_reqs("~3.8").unwrap(), vec![req("~3.8")]);
+        assert_eq!(poetry_to_version_reqs("3.8.*").unwrap(), vec![req("=3.8")]);
+        assert_eq!(poetry_to_version_reqs("3.8").unwrap(), vec![req("=3.8.0")]);
+        assert_eq!(
+            poetry_to_version_reqs(">=3.8 <3.11").unwrap(),
+            vec![req(">=3.8.0, <3.11.0")]
+        );
+        assert_eq!(
+            poetry_to_version_reqs("~3.10 || ^3.7").unwrap(),
+            vec![req("~3.10"), req("^3.7")]
+        );
+    }
+
+    #[test]
+    fn pyproject_pep621() {
+        let content = r#"
+[project]
+name = "demo"
+requires-python = ">=3.8"
+
+[tool.poetry.dependencies]
+python = "^3.10"
+"#;
+        assert_eq!(
+            parse_pyproject(content).unwrap(),
+            vec![ToolchainFileEntry::VersionReq(req(">=3.8.0"))]
+        );
+    }
+
+    #[test]
+    fn pyproject_poetry() {
+        let content = r#"
+[tool.poetry.dependencies]
+python = "^3.10"
+requests = "^2.28"
+"#;
+        assert_eq!(
+            parse_pyproject(content).unwrap(),
+            vec![ToolchainFileEntry::VersionReq(req("^3.10"))]
+        );
+    }
+
+    #[test]
+    fn pyproject_without_python() {
+        let content = "[tool.black]\nline-length = 100\n";
+        assert_eq!(parse_pyproject(content).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn tool_versions() {
+        let content = "nodejs 18.12.1\npython 3.11.4 3.10.0rc2 pypy3.9-7.3.9 system # Fallbacks\n";
+        assert_eq!(
+            parse_tool_versions(content).unwrap(),
+            vec![
+                ToolchainFileEntry::VersionReq(req("=3.11.4")),
+                ToolchainFileEntry::VersionReq(req("=3.10.0-rc2")),
+                ToolchainFileEntry::System,
+            ]
+        );
+        assert_eq!(parse_tool_versions("nodejs 18.12.1\n").unwrap(), vec![]);
+    }
+
+    #[test]
+    fn runtime() {
+        assert_eq!(
+            parse_runtime("python-3.8.1\n").unwrap(),
+            vec![ToolchainFileEntry::VersionReq(req("=3.8.1"))]
+        );
+        let err = parse_runtime("3.8.1\n").unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<VersionFileError>(),
+            Some(VersionFileError::InvalidRuntime(_))
+        ));
+    }
+}
@@ -1,4 +1,5 @@
 use std::{
+    cell::RefCell,
     env,
     fs::{self, File},
     io::{self, BufRead, BufReader, BufWriter, Write},
@@ -16,7 +17,10 @@ use terminal_size::{terminal_size, Width};
 use crate::{
     constants::{EXECUTABLE_NAME, INFO_FILE},
     os,
-    toolchain::installed::InstalledToolchain,
+    tls::CaBundle,
+    toolchain::{
+        find_compatible_toolchain, implementation::Implementation, installed::InstalledToolchain,
+    },
     Result,
 };
 
@@ -122,29 +126,35 @@ where
 pub fn active_version<'a>(
     version: &VersionReq,
     installed_toolchains: &'a [InstalledToolchain],
+    include_pre_releases: bool,
 ) -> Option<&'a InstalledToolchain> {
-    // Find the compatible versions from the installed list
-    let mut compatible_versions: Vec<&'a InstalledToolchain> = installed_toolchains
-        .iter()
-        .filter(|installed_python| version.matches(&installed_python.version))
-        .collect();
-    // Sort to get latest version. If two versions are identical, pick the
-    // one that is custom installed (not a system one).
-    compatible_versions.sort_unstable_by(|a, b| {
-        let version_comparison = a.version.cmp(&b.version);
-        if version_comparison == std::cmp::Ordering::Equal {
-            if a.is_custom_install() {
-                std::cmp::Ordering::Greater
-            } else {
-                std::cmp::Ordering::Less
-            }
-        } else {
-            version_comparison
-        }
-    });
-    log::debug!("Compatible versions found: {:?}", compatible_versions);
+    let compatible_version =
+        find_compatible_toolchain(version, installed_toolchains, include_pre_releases);
+    log::debug!("Compatible version found: {:?}", compatible_version);
 
-    compatible_versions.last().cloned()
+    compatible_version
+}
+
+/// Check if `version` is compatible with `version_req`.
+///
+/// semver only matches a pre-release against a requirement containing a
+/// pre-release on the same `major.minor.patch` (for example `=3.9.0-a2`). When
+/// pre-releases are requested, they are instead also compared as if they were
+/// the final release, so that `~3.9` matches `3.9.0-a2`.
+pub fn version_matches(
+    version_req: &VersionReq,
+    version: &Version,
+    include_pre_releases: bool,
+) -> bool {
+    if version_req.matches(version) {
+        true
+    } else if include_pre_releases && !version.pre.is_empty() {
+        let mut final_release = version.clone();
+        final_release.pre = semver::Prerelease::EMPTY;
+        version_req.matches(&final_release)
+    } else {
+        false
+    }
 }
 
 pub fn get_info_file<P>(install_dir: P) -> PathBuf
@@ -172,6 +182,18 @@ where
     Ok(())
 }
 
+/// Add a line to the info file of an installation, for example where it comes from.
+pub fn append_to_info_file<P>(install_dir: P, line: &str) -> Result<()>
+where
+    P: AsRef<Path>,
+{
+    let filename = get_info_file(install_dir);
+    let mut file = fs::OpenOptions::new().append(true).open(&filename)?;
+    writeln!(file, "{}", line)?;
+
+    Ok(())
+}
+
 /// Wrapper around `std::process::Child`
 ///
 /// NOTE: According to [](https://doc.rust-lang.org/std/process/struct.Child.html),
@@ -232,7 +254,14 @@ where
         })
         .for_each(|s| log::debug!("    {}", s));
 
-    let logs_dir = PycorsPathsProviderFromEnv::new().logs();
+    let paths_provider = PycorsPathsProviderFromEnv::new();
+    let logs_dir = paths_provider.logs();
+
+    // Let pip (and `get-pip.py`) trust the same certificate authorities as downloads
+    let ca_bundle_envs = match CaBundle::load_exported(&paths_provider)? {
+        Some(ca_bundle) => ca_bundle.env_variables(&paths_provider)?,
+        None => Vec::new(),
+    };
 
     // FIXME: Extract generics part to own function to reduce bloat
     let cwd = cwd.as_ref();
@@ -272,7 +301,7 @@ where
         }
     };
     let new_paths: Vec<PathBuf> = {
-        let mut tmp = os::paths_to_prepends(version)?;
+        let mut tmp = os::paths_to_prepends(Implementation::CPython, version)?;
         tmp.extend_from_slice(&current_paths);
         tmp
     };
@@ -283,17 +312,22 @@ where
         )
     })?;
 
-    let original_current_dir =
-        env::current_dir().with_context(|| "Failed to get current working directory")?;
-
-    env::set_current_dir(&cwd)
-        .with_context(|| format!("Failed to set current working directory to {:?}", cwd))?;
+    // The process-wide current directory is left untouched (versions can be built
+    // concurrently): commands relative to `cwd` (like `./configure`) are run with
+    // their full path instead.
+    let program = if Path::new(cmd).components().count() > 1 && Path::new(cmd).is_relative() {
+        cwd.join(cmd)
+    } else {
+        PathBuf::from(cmd)
+    };
 
     // Wrap in a custom `ChildProcess` that implements `Drop` to kill the child process
     let mut process = ChildProcess(
-        std::process::Command::new(cmd)
+        std::process::Command::new(&program)
             .args(args)
+            .current_dir(cwd)
             .env("PATH", &new_path)
+            .envs(ca_bundle_envs)
             .envs(
                 envs.iter()
                     .map(|(name, value)| (name.as_ref(), value.as_ref())),
@@ -364,17 +398,6 @@ where
         .join()
         .map_err(|e| anyhow::anyhow!("Failed to join threads: {:?}", e))?;
 
-    log::debug!(
-        "Changing back current directory to {:?}",
-        original_current_dir
-    );
-    env::set_current_dir(&original_current_dir).with_context(|| {
-        format!(
-            "Failed to set current working directory back to original {:?}",
-            original_current_dir
-        )
-    })?;
-
     if exit_status.success() {
         log::debug!("Success!");
         Ok(())
@@ -419,6 +442,24 @@ pub fn create_spinner(msg: &str) -> ProgressBar {
     pb
 }
 
+thread_local! {
+    static PROGRESS_BAR: RefCell<Option<ProgressBar>> = RefCell::new(None);
+}
+
+/// Run `f`, with the spinners it starts on this thread (see `spinner_in_thread()`)
+/// reporting on `pb` instead of drawing their own.
+///
+/// Used when installing several versions at once, each with its own progress bar.
+pub fn with_progress_bar<F, R>(pb: &ProgressBar, f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    let previous = PROGRESS_BAR.with(|current| current.replace(Some(pb.clone())));
+    let result = f();
+    PROGRESS_BAR.with(|current| current.replace(previous));
+    result
+}
+
 pub fn spinner_in_thread<S: Into<String>>(
     message: S,
 ) -> (
@@ -427,8 +468,15 @@ pub fn spinner_in_thread<S: Into<String>>(
 ) {
     let message = message.into();
     let (tx, rx) = channel();
+    let shared_pb = PROGRESS_BAR.with(|pb| pb.borrow().clone());
     let child = thread::spawn(move || {
-        let pb = create_spinner(&message);
+        let (pb, owned) = match shared_pb {
+            Some(pb) => {
+                pb.set_message(message);
+                (pb, false)
+            }
+            None => (create_spinner(&message), true),
+        };
         let d = Duration::from_millis(100);
 
         loop {
@@ -441,7 +489,10 @@ pub fn spinner_in_thread<S: Into<String>>(
             pb.inc(1);
         }
 
-        pb.finish();
+        // A shared progress bar is finished by its owner
+        if owned {
+            pb.finish();
+        }
     });
 
     (tx, child)
@@ -461,22 +512,27 @@ mod tests {
     fn fixture_installed_toolchains() -> Vec<InstalledToolchain> {
         vec![
             InstalledToolchain {
+                implementation: Implementation::CPython,
                 location: PathBuf::from("unimportant"),
                 version: Version::new(3, 7, 4),
             },
             InstalledToolchain {
+                implementation: Implementation::CPython,
                 location: PathBuf::from("unimportant"),
                 version: Version::new(3, 7, 5),
             },
             InstalledToolchain {
+                implementation: Implementation::CPython,
                 location: PathBuf::from("unimportant"),
                 version: Version::new(3, 7, 2),
             },
             InstalledToolchain {
+                implementation: Implementation::CPython,
                 location: PathBuf::from("unimportant"),
                 version: Version::new(3, 6, 1),
             },
             InstalledToolchain {
+                implementation: Implementation::CPython,
                 location: PathBuf::from("unimportant"),
                 version: Version::new(3, 8, 0),
             },
@@ -561,7 +617,7 @@ mod tests {
     fn active_version_empty_list() {
         let version_req = VersionReq::parse("=3.7.5").unwrap();
         let installed_toolchains = vec![];
-        let compatible_version = active_version(&version_req, &installed_toolchains);
+        let compatible_version = active_version(&version_req, &installed_toolchains, false);
         assert!(compatible_version.is_none());
     }
 
@@ -569,7 +625,8 @@ mod tests {
     fn active_version_tilde() {
         let version_req = VersionReq::parse("~3.7").unwrap();
         let installed_toolchains = fixture_installed_toolchains();
-        let compatible_version = active_version(&version_req, &installed_toolchains).unwrap();
+        let compatible_version =
+            active_version(&version_req, &installed_toolchains, false).unwrap();
         assert_eq!(compatible_version.version, Version::new(3, 7, 5));
     }
 
@@ -577,14 +634,15 @@ mod tests {
     fn active_version_exact_not_in_list() {
         let version_req = VersionReq::parse("=3.7.3").unwrap();
         let installed_toolchains = fixture_installed_toolchains();
-        assert!(active_version(&version_req, &installed_toolchains).is_none());
+        assert!(active_version(&version_req, &installed_toolchains, false).is_none());
     }
 
     #[test]
     fn active_version_found() {
         let version_req = VersionReq::parse("=3.7.2").unwrap();
         let installed_toolchains = fixture_installed_toolchains();
-        let compatible_version = active_version(&version_req, &installed_toolchains).unwrap();
+        let compatible_version =
+            active_version(&version_req, &installed_toolchains, false).unwrap();
         assert_eq!(compatible_version.version, Version::new(3, 7, 2));
     }
 
@@ -681,6 +739,40 @@ mod tests {
         assert_eq!(&caps["cmd"], r#"non-existent-command ["-V"]"#);
     }
 
+    #[test]
+    #[cfg(unix)]
+    fn run_cmd_template_relative_to_cwd() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let version = Version::new(0, 0, 0);
+        let line_header = "0 utils::tests::run_cmd_template_relative_to_cwd";
+        let envs: &[(&str, String)] = &[];
+        let tmp_dir = create_test_temp_dir!();
+        fs::create_dir_all(&tmp_dir).unwrap();
+        let script = tmp_dir.join("script.sh");
+        fs::write(&script, "#!/bin/sh\npwd > pwd.txt\n").unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+        let original_current_dir = env::current_dir().unwrap();
+
+        let no_args: &[&str] = &[];
+        run_cmd_template(
+            &version,
+            line_header,
+            "./script.sh",
+            no_args,
+            envs,
+            &tmp_dir,
+        )
+        .unwrap();
+
+        assert_eq!(env::current_dir().unwrap(), original_current_dir);
+        let pwd = fs::read_to_string(tmp_dir.join("pwd.txt")).unwrap();
+        assert_eq!(
+            Path::new(pwd.trim()).canonicalize().unwrap(),
+            tmp_dir.canonicalize().unwrap()
+        );
+    }
+
     #[test]
     #[ignore] // stderr is not saved for now in run_cmd_template()
     fn run_cmd_template_fail_stderr() {
@@ -1,9 +1,13 @@
 use semver::Version;
 use std::{env, path::PathBuf};
 
-use crate::constants::{
-    self, AVAILABLE_TOOLCHAIN_CACHE, DEFAULT_DOT_DIR, EXECUTABLE_NAME, EXTRA_PACKAGES_FILENAME,
-    SHIMS_DIRECTORY_IDENTIFIER_FILE,
+use crate::{
+    constants::{
+        self, AVAILABLE_TOOLCHAIN_CACHE, CA_BUNDLE_CACHE, CONFIG_FILENAME, DEFAULT_DOT_DIR,
+        EXECUTABLE_NAME, EXTRA_PACKAGES_FILENAME, GLOBAL_TOOLCHAIN_FILE, INTERPRETER_INDEX_CACHE,
+        KEYRING_FILENAME, SHIMS_DIRECTORY_IDENTIFIER_FILE,
+    },
+    toolchain::implementation::Implementation,
 };
 
 #[cfg_attr(test, mockall::automock)]
@@ -14,6 +18,7 @@ pub trait PycorsHomeProviderTrait {
     fn paths(&self) -> Vec<PathBuf>;
 }
 
+#[derive(Clone)]
 pub struct PycorsPathsProvider<P>
 where
     P: PycorsHomeProviderTrait,
@@ -39,6 +44,7 @@ where
     }
 }
 
+#[derive(Clone)]
 pub struct PycorsPathsProviderFromEnv;
 
 impl PycorsPathsProviderFromEnv {
@@ -102,12 +108,38 @@ where
         self.project_home().join(EXTRA_PACKAGES_FILENAME)
     }
 
+    pub fn config_file(&self) -> PathBuf {
+        self.project_home().join(CONFIG_FILENAME)
+    }
+
+    pub fn keyring_file(&self) -> PathBuf {
+        self.project_home().join(KEYRING_FILENAME)
+    }
+
+    pub fn global_toolchain_file(&self) -> PathBuf {
+        self.project_home().join(GLOBAL_TOOLCHAIN_FILE)
+    }
+
     pub fn cache(&self) -> PathBuf {
         self.project_home().join("cache")
     }
 
     pub fn installed(&self) -> PathBuf {
-        self.project_home().join("installed").join("cpython")
+        self.installed_implementation(Implementation::CPython)
+    }
+
+    pub fn installed_implementation(&self, implementation: Implementation) -> PathBuf {
+        self.project_home()
+            .join("installed")
+            .join(implementation.name())
+    }
+
+    pub fn venvs(&self) -> PathBuf {
+        self.project_home().join("venvs")
+    }
+
+    pub fn venv_dir(&self, name: &str) -> PathBuf {
+        self.venvs().join(name)
     }
 
     pub fn logs(&self) -> PathBuf {
@@ -126,6 +158,14 @@ where
         self.cache().join(AVAILABLE_TOOLCHAIN_CACHE)
     }
 
+    pub fn interpreter_index_cache_file(&self) -> PathBuf {
+        self.cache().join(INTERPRETER_INDEX_CACHE)
+    }
+
+    pub fn ca_bundle_cache_file(&self) -> PathBuf {
+        self.cache().join(CA_BUNDLE_CACHE)
+    }
+
     pub fn shims_directory_identifier_file(&self) -> PathBuf {
         self.shims().join(SHIMS_DIRECTORY_IDENTIFIER_FILE)
     }
@@ -135,16 +175,38 @@ where
     }
 
     pub fn install_dir(&self, version: &Version) -> PathBuf {
-        self.installed().join(format!("{}", version))
+        self.implementation_install_dir(Implementation::CPython, version)
+    }
+
+    pub fn implementation_install_dir(
+        &self,
+        implementation: Implementation,
+        version: &Version,
+    ) -> PathBuf {
+        self.installed_implementation(implementation)
+            .join(format!("{}", version))
     }
 
-    #[cfg(not(windows))]
     pub fn bin_dir(&self, version: &Version) -> PathBuf {
-        self.install_dir(version).join("bin")
+        self.implementation_bin_dir(Implementation::CPython, version)
+    }
+
+    #[cfg(not(windows))]
+    pub fn implementation_bin_dir(
+        &self,
+        implementation: Implementation,
+        version: &Version,
+    ) -> PathBuf {
+        self.implementation_install_dir(implementation, version)
+            .join("bin")
     }
     #[cfg(windows)]
-    pub fn bin_dir(&self, version: &Version) -> PathBuf {
-        self.install_dir(version)
+    pub fn implementation_bin_dir(
+        &self,
+        implementation: Implementation,
+        version: &Version,
+    ) -> PathBuf {
+        self.implementation_install_dir(implementation, version)
     }
 }
 
@@ -490,6 +552,29 @@ pub mod tests {
             assert_eq!(to_validate, expected);
         }
 
+        #[test]
+        fn installed_implementation() {
+            let home = default_home_full_path();
+            let hygeia_home = default_dot_full_path();
+
+            let mocked_home = Some(home);
+            let mocked_hygeia_home = Some(hygeia_home.clone());
+
+            let version = Version::new(3, 10, 12);
+            let expected = hygeia_home.join("installed").join("pypy").join("3.10.12");
+
+            let mut mock = MockPycorsHomeProviderTrait::new();
+            mock.expect_project_home()
+                .times(1)
+                .return_const(mocked_hygeia_home);
+            mock.expect_home().times(0).return_const(mocked_home);
+
+            let paths_provider = PycorsPathsProvider::from(mock);
+            let to_validate =
+                paths_provider.implementation_install_dir(Implementation::PyPy, &version);
+            assert_eq!(to_validate, expected);
+        }
+
         #[test]
         fn installed_from_env_variable() {
             let home = default_home_full_path();
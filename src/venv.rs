@@ -0,0 +1,264 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use semver::Version;
+use thiserror::Error;
+
+use crate::{
+    utils::directory::{PycorsHomeProviderTrait, PycorsPathsProvider},
+    Result,
+};
+
+/// File created by `python -m venv`, describing the base interpreter.
+const PYVENV_CFG: &str = "pyvenv.cfg";
+
+#[derive(Debug, Error)]
+pub enum VenvError {
+    #[error("Invalid virtual environment name {0:?}")]
+    InvalidName(String),
+    #[error("Virtual environment {0:?} not found")]
+    NotFound(String),
+    #[error("Virtual environment {0:?} already exists")]
+    AlreadyExists(String),
+    #[error("No Python interpreter found to create virtual environment {0:?}")]
+    MissingInterpreter(String),
+    #[error("Failed to create virtual environment {0:?} (exit code: {1:?})")]
+    CreationFailed(String, Option<i32>),
+}
+
+/// A named virtual environment, stored in `${HYGEIA_HOME}/venvs`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Venv {
+    pub name: String,
+    pub location: PathBuf,
+    /// Directory of the base interpreter (`home` in `pyvenv.cfg`)
+    pub home: Option<PathBuf>,
+    /// Version of the base interpreter (`version` in `pyvenv.cfg`)
+    pub version: Option<Version>,
+}
+
+impl Venv {
+    /// Validate a name and return the virtual environment's location.
+    pub fn location<P>(name: &str, paths_provider: &PycorsPathsProvider<P>) -> Result<PathBuf>
+    where
+        P: PycorsHomeProviderTrait,
+    {
+        let is_valid = !name.is_empty()
+            && !name.starts_with('.')
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "._-".contains(c));
+        if is_valid {
+            Ok(paths_provider.venv_dir(name))
+        } else {
+            Err(VenvError::InvalidName(name.to_string()).into())
+        }
+    }
+
+    pub fn load<P>(name: &str, paths_provider: &PycorsPathsProvider<P>) -> Result<Venv>
+    where
+        P: PycorsHomeProviderTrait,
+    {
+        let location = Venv::location(name, paths_provider)?;
+        if !location.join(PYVENV_CFG).exists() {
+            return Err(VenvError::NotFound(name.to_string()).into());
+        }
+        Venv::from_dir(name, location)
+    }
+
+    fn from_dir(name: &str, location: PathBuf) -> Result<Venv> {
+        let cfg = fs::read_to_string(location.join(PYVENV_CFG))?;
+        let (home, version) = parse_pyvenv_cfg(&cfg);
+        Ok(Venv {
+            name: name.to_string(),
+            location,
+            home,
+            version,
+        })
+    }
+
+    /// All virtual environments, sorted by name.
+    pub fn list<P>(paths_provider: &PycorsPathsProvider<P>) -> Result<Vec<Venv>>
+    where
+        P: PycorsHomeProviderTrait,
+    {
+        let venvs_dir = paths_provider.venvs();
+        let entries = match fs::read_dir(&venvs_dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                log::debug!("Cannot read venvs directory {:?}: {:?}", venvs_dir, e);
+                return Ok(Vec::new());
+            }
+        };
+
+        let mut venvs = Vec::new();
+        for entry in entries.flatten() {
+            let location = entry.path();
+            if !location.join(PYVENV_CFG).exists() {
+                log::debug!("Skipping {:?}: not a virtual environment", location);
+                continue;
+            }
+            match entry.file_name().to_str() {
+                Some(name) => venvs.push(Venv::from_dir(name, location)?),
+                None => log::error!("Could not convert directory to str: {:?}", location),
+            }
+        }
+        venvs.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(venvs)
+    }
+
+    /// Directory containing the virtual environment's executables.
+    pub fn bin_dir(&self) -> PathBuf {
+        if cfg!(windows) {
+            self.location.join("Scripts")
+        } else {
+            self.location.join("bin")
+        }
+    }
+
+    /// Whether the base interpreter is gone (for example uninstalled).
+    pub fn is_broken(&self) -> bool {
+        match &self.home {
+            Some(home) => !home.exists(),
+            None => true,
+        }
+    }
+
+    /// Whether the virtual environment was created from an interpreter under `dir`.
+    pub fn is_based_on<P>(&self, dir: P) -> bool
+    where
+        P: AsRef<Path>,
+    {
+        match &self.home {
+            Some(home) => {
+                let dir = dir.as_ref();
+                home.starts_with(dir)
+                    || match (home.canonicalize(), dir.canonicalize()) {
+                        (Ok(home), Ok(dir)) => home.starts_with(dir),
+                        _ => false,
+                    }
+            }
+            None => false,
+        }
+    }
+}
+
+/// Whether `bin_dir` contains the executables of a virtual environment (not
+/// necessarily one created with `hygeia venv`).
+pub fn is_venv_bin_dir(bin_dir: &Path) -> bool {
+    matches!(bin_dir.parent(), Some(location) if location.join(PYVENV_CFG).is_file())
+}
+
+/// `home` and `version` keys of a `pyvenv.cfg` file.
+fn parse_pyvenv_cfg(cfg: &str) -> (Option<PathBuf>, Option<Version>) {
+    let mut home = None;
+    let mut version = None;
+    for line in cfg.lines() {
+        let mut key_value = line.splitn(2, '=');
+        let (key, value) = match (key_value.next(), key_value.next()) {
+            (Some(key), Some(value)) => (key.trim(), value.trim()),
+            _ => continue,
+        };
+        match key {
+            "home" => home = Some(PathBuf::from(value)),
+            "version" => version = Version::parse(value).ok(),
+            _ => {}
+        }
+    }
+    (home, version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use hygeia_test_helpers::create_test_temp_dir;
+
+    use crate::utils::directory::MockPycorsHomeProviderTrait;
+
+    fn paths_provider(project_home: PathBuf) -> PycorsPathsProvider<MockPycorsHomeProviderTrait> {
+        let mut mock = MockPycorsHomeProviderTrait::new();
+        mock.expect_project_home().return_const(Some(project_home));
+        mock.expect_home().times(0).return_const(None);
+        PycorsPathsProvider::from(mock)
+    }
+
+    #[test]
+    fn parse_cfg() {
+        let cfg = "home = /usr/bin\ninclude-system-site-packages = false\nversion = 3.8.10\n";
+        assert_eq!(
+            parse_pyvenv_cfg(cfg),
+            (
+                Some(PathBuf::from("/usr/bin")),
+                Some(Version::new(3, 8, 10))
+            )
+        );
+        assert_eq!(parse_pyvenv_cfg(""), (None, None));
+    }
+
+    #[test]
+    fn venv_bin_dir() {
+        let location = create_test_temp_dir!();
+        let bin_dir = location.join("bin");
+        fs::create_dir_all(&bin_dir).unwrap();
+        assert!(!is_venv_bin_dir(&bin_dir));
+
+        fs::write(location.join(PYVENV_CFG), "home = /usr/bin\n").unwrap();
+        assert!(is_venv_bin_dir(&bin_dir));
+        assert!(!is_venv_bin_dir(&location));
+    }
+
+    #[test]
+    fn invalid_names() {
+        let project_home = create_test_temp_dir!().join(".hygeia");
+        let paths_provider = paths_provider(project_home.clone());
+        for name in &["", "..", ".hidden", "a/b", "a b"] {
+            assert!(Venv::location(name, &paths_provider).is_err(), "{:?}", name);
+        }
+        assert_eq!(
+            Venv::location("my-venv_3.8", &paths_provider).unwrap(),
+            project_home.join("venvs").join("my-venv_3.8")
+        );
+    }
+
+    #[test]
+    fn list_and_broken() {
+        let home = create_test_temp_dir!();
+        let project_home = home.join(".hygeia");
+        let base = home.join("usr_bin");
+        fs::create_dir_all(&base).unwrap();
+        let paths_provider = paths_provider(project_home.clone());
+
+        for (name, base) in &[("web", base.clone()), ("old", home.join("uninstalled"))] {
+            let location = project_home.join("venvs").join(name);
+            fs::create_dir_all(&location).unwrap();
+            fs::write(
+                location.join(PYVENV_CFG),
+                format!("home = {}\nversion = 3.8.1\n", base.display()),
+            )
+            .unwrap();
+        }
+        // Not a virtual environment
+        fs::create_dir_all(project_home.join("venvs").join("other")).unwrap();
+
+        let venvs = Venv::list(&paths_provider).unwrap();
+        let names: Vec<&str> = venvs.iter().map(|venv| venv.name.as_str()).collect();
+        assert_eq!(names, vec!["old", "web"]);
+        assert!(venvs[0].is_broken());
+        assert!(!venvs[1].is_broken());
+        assert!(venvs[1].is_based_on(&base));
+        assert!(venvs[1].is_based_on(&home));
+        assert!(!venvs[0].is_based_on(&base));
+        assert_eq!(venvs[1].version, Some(Version::new(3, 8, 1)));
+
+        assert_eq!(Venv::load("web", &paths_provider).unwrap(), venvs[1]);
+        let err = Venv::load("other", &paths_provider).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<VenvError>(),
+            Some(VenvError::NotFound(_))
+        ));
+    }
+}
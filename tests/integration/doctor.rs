@@ -0,0 +1,63 @@
+use super::*;
+
+fn doctor_command(home: &Path, hygeia_home: &Path, cwd: &Path) -> Command {
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    cmd.arg("doctor")
+        .env(project_home_env_variable(), hygeia_home)
+        .env(home_overwrite_env_variable(), home)
+        .env("PATH", hygeia_home.join("shims"))
+        .env("SHELL", "/bin/bash")
+        .env("RUST_LOG", "")
+        .current_dir(cwd);
+    cmd
+}
+
+#[cfg_attr(windows, ignore)]
+#[test]
+fn doctor_after_setup() {
+    let home = create_test_temp_dir!();
+    let hygeia_home = home.join(".hygeia");
+    let cwd = home.join("current_dir");
+    fs::create_dir_all(&cwd).unwrap();
+
+    // Nothing set up yet
+    doctor_command(&home, &hygeia_home, &cwd)
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "❌ Shims directory identifier file",
+        ))
+        .stderr(predicate::str::contains("problem(s) found"));
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    cmd.arg("setup")
+        .arg("bash")
+        .env(project_home_env_variable(), &hygeia_home)
+        .env(home_overwrite_env_variable(), &home)
+        .env("PATH", hygeia_home.join("usr_bin"))
+        .env("RUST_LOG", "")
+        .current_dir(&cwd)
+        .unwrap();
+
+    doctor_command(&home, &hygeia_home, &cwd)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "✅ Shims directory is first in PATH",
+        ))
+        .stdout(predicate::str::contains("No problem found!"));
+
+    // A shim left behind by an older setup
+    let pip = hygeia_home.join("shims").join("pip");
+    fs::remove_file(&pip).unwrap();
+    fs::write(&pip, "#!/bin/sh\n").unwrap();
+    doctor_command(&home, &hygeia_home, &cwd)
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "❌ 1 shim(s) are not hard links to the shim executable: pip",
+        ))
+        .stdout(predicate::str::contains(
+            "↳ Run 'hygeia setup bash' to recreate them",
+        ));
+}
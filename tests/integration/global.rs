@@ -0,0 +1,75 @@
+use super::*;
+
+fn global(hygeia_home: &Path, cwd: &Path, args: &[&str]) -> assert_cmd::assert::Assert {
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    cmd.arg("global")
+        .args(args)
+        .env(project_home_env_variable(), hygeia_home)
+        .env("PATH", hygeia_home.join("usr_bin"))
+        .env("RUST_LOG", "")
+        .current_dir(cwd)
+        .assert()
+}
+
+fn path(hygeia_home: &Path, cwd: &Path) -> assert_cmd::assert::Assert {
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    cmd.arg("path")
+        .env(project_home_env_variable(), hygeia_home)
+        .env("PATH", hygeia_home.join("usr_bin"))
+        .env("RUST_LOG", "")
+        .current_dir(cwd)
+        .assert()
+}
+
+#[test]
+fn used_instead_of_latest() {
+    let home = create_test_temp_dir!();
+    let hygeia_home = home.join(".hygeia");
+    let cwd = home.join("current_dir");
+    let location_380_dir = installed(&hygeia_home, "3.8.0", false).unwrap();
+    let location_375_dir = installed(&hygeia_home, "3.7.5", true).unwrap();
+    fs::create_dir_all(&cwd).unwrap();
+
+    global(&hygeia_home, &cwd, &["~3.7"])
+        .success()
+        .stdout(predicate::str::is_empty().trim())
+        .stderr(predicate::str::is_empty().trim());
+    assert_eq!(
+        fs::read_to_string(hygeia_home.join("global-python-version")).unwrap(),
+        "~3.7\n"
+    );
+    global(&hygeia_home, &cwd, &[])
+        .success()
+        .stdout(predicate::str::diff("~3.7\n"));
+    path(&hygeia_home, &cwd)
+        .success()
+        .stdout(predicate::str::diff(location_375_dir));
+
+    // A project's '.python-version' has precedence
+    select("=3.8.0", &cwd);
+    path(&hygeia_home, &cwd)
+        .success()
+        .stdout(predicate::str::diff(location_380_dir.clone()));
+    fs::remove_file(cwd.join(TOOLCHAIN_FILE)).unwrap();
+
+    global(&hygeia_home, &cwd, &["--unset"]).success();
+    assert!(!hygeia_home.join("global-python-version").exists());
+    path(&hygeia_home, &cwd)
+        .success()
+        .stdout(predicate::str::diff(location_380_dir));
+}
+
+#[test]
+fn system() {
+    let home = create_test_temp_dir!();
+    let hygeia_home = home.join(".hygeia");
+    let cwd = home.join("current_dir");
+    let location_370_dir = installed(&hygeia_home, "3.7.0", false).unwrap();
+    let _location_380_dir = installed(&hygeia_home, "3.8.0", true).unwrap();
+    fs::create_dir_all(&cwd).unwrap();
+
+    global(&hygeia_home, &cwd, &["system"]).success();
+    path(&hygeia_home, &cwd)
+        .success()
+        .stdout(predicate::str::diff(location_370_dir));
+}
@@ -5,7 +5,10 @@ use rstest::rstest;
 
 use hygeia_test_helpers::EXECUTABLE_EXTENSION;
 
+#[cfg(unix)]
+mod from_source;
 mod long_compilation;
+mod mirror;
 mod windows_only;
 
 fn assert_pip_successfully_installed<P>(paths_provider: &PycorsPathsProvider<P>)
@@ -0,0 +1,266 @@
+use super::*;
+
+use std::os::unix::fs::PermissionsExt;
+
+/// A fake CPython source tree: `configure` writes a `Makefile` whose `install`
+/// target copies a `python3.12` script to the prefix. The script cannot import
+/// `_lzma`.
+fn fake_source_tree(dir: &Path) {
+    fs::create_dir_all(dir.join("Include")).unwrap();
+    fs::write(
+        dir.join("Include").join("patchlevel.h"),
+        "#define PY_VERSION              \"3.12.0rc1+\"\n",
+    )
+    .unwrap();
+
+    let python = indoc!(
+        r#"
+        #!/bin/sh
+        if [ "$1" = "-E" ]; then
+            for module in "$@"; do
+                if [ "$module" = "_lzma" ]; then
+                    echo "missing _lzma"
+                fi
+            done
+            echo "done"
+        else
+            echo "Python 3.12.0rc1+"
+        fi
+        "#
+    );
+    fs::write(dir.join("fake_python"), python).unwrap();
+
+    let configure = indoc!(
+        r#"
+        #!/bin/sh
+        prefix="$2"
+        srcdir="$(cd "$(dirname "$0")" && pwd)"
+        cat > Makefile <<MAKEFILE
+        all:
+        	@echo "Built with $*"
+        install:
+        	mkdir -p $prefix/bin
+        	cp $srcdir/fake_python $prefix/bin/python3.12
+        	chmod +x $prefix/bin/python3.12
+        MAKEFILE
+        "#
+    );
+    let configure_path = dir.join("configure");
+    fs::write(&configure_path, configure).unwrap();
+    fs::set_permissions(&configure_path, fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+#[test]
+fn from_source_directory() {
+    let home = create_test_temp_dir!();
+    let hygeia_home = home.join(".hygeia");
+    let cwd = home.join("current_dir");
+    fs::create_dir_all(&cwd).unwrap();
+    let checkout = home.join("cpython");
+    fake_source_tree(&checkout);
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    let output = cmd
+        .arg("install")
+        .arg("--from-source")
+        .arg(&checkout)
+        // The fake sources need none of the dependencies
+        .arg("--ignore-missing-deps")
+        .env(project_home_env_variable(), &hygeia_home)
+        .env("RUST_LOG", "")
+        .current_dir(&cwd)
+        .unwrap();
+    output.assert().success().stdout(predicate::str::contains(
+        "Python 3.12.0-rc1 successfully installed!",
+    ));
+
+    let install_dir = hygeia_home
+        .join("installed")
+        .join("cpython")
+        .join("3.12.0-rc1");
+    assert!(install_dir.join("bin").join("python3.12").is_file());
+    assert!(install_dir.join("bin").join("python3").is_file());
+    let info = fs::read_to_string(install_dir.join(INFO_FILE)).unwrap();
+    assert!(info.contains(&format!("Built from source {}", checkout.display())));
+    assert!(info.contains("Missing modules: _lzma"));
+    // Built out of tree
+    assert!(!checkout.join("Makefile").exists());
+}
+
+#[test]
+fn from_source_archive() {
+    let home = create_test_temp_dir!();
+    let hygeia_home = home.join(".hygeia");
+    let cwd = home.join("current_dir");
+    fs::create_dir_all(&cwd).unwrap();
+    fake_source_tree(&home.join("Python-3.12.0rc1"));
+    let archive_path = home.join("Python-3.12.0rc1.tgz");
+    let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+        fs::File::create(&archive_path).unwrap(),
+        flate2::Compression::default(),
+    ));
+    builder
+        .append_dir_all("Python-3.12.0rc1", home.join("Python-3.12.0rc1"))
+        .unwrap();
+    builder.into_inner().unwrap().finish().unwrap();
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    let output = cmd
+        .arg("install")
+        .arg("--from-source")
+        .arg(url::Url::from_file_path(&archive_path).unwrap().as_str())
+        .arg("--select")
+        .arg("--ignore-missing-deps")
+        .env(project_home_env_variable(), &hygeia_home)
+        .env("RUST_LOG", "")
+        .current_dir(&cwd)
+        .unwrap();
+    output
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Version 3.12.0-rc1 is selected"));
+    assert_eq!(
+        fs::read_to_string(cwd.join(TOOLCHAIN_FILE)).unwrap(),
+        "=3.12.0-rc1\n"
+    );
+    assert!(hygeia_home
+        .join("installed")
+        .join("cpython")
+        .join("3.12.0-rc1")
+        .join("bin")
+        .join("python3.12")
+        .is_file());
+}
+
+#[test]
+fn from_source_missing_dependencies() {
+    let home = create_test_temp_dir!();
+    let hygeia_home = home.join(".hygeia");
+    let cwd = home.join("current_dir");
+    fs::create_dir_all(&cwd).unwrap();
+    let checkout = home.join("cpython");
+    fake_source_tree(&checkout);
+
+    // A compiler that never works
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    let output = cmd
+        .arg("install")
+        .arg("--from-source")
+        .arg(&checkout)
+        .env(project_home_env_variable(), &hygeia_home)
+        .env("CC", "false")
+        .env("RUST_LOG", "")
+        .current_dir(&cwd)
+        .assert();
+    output
+        .failure()
+        .stderr(predicate::str::contains("C compiler not found"))
+        .stderr(predicate::str::contains(
+            "use '--ignore-missing-deps' to build anyway",
+        ));
+    // Nothing was configured
+    assert!(!hygeia_home
+        .join("installed")
+        .join("cpython")
+        .join("3.12.0-rc1")
+        .exists());
+}
+
+#[test]
+fn from_source_fail_on_missing_modules() {
+    let home = create_test_temp_dir!();
+    let hygeia_home = home.join(".hygeia");
+    let cwd = home.join("current_dir");
+    fs::create_dir_all(&cwd).unwrap();
+    fs::create_dir_all(&hygeia_home).unwrap();
+    fs::write(
+        hygeia_home.join("config.toml"),
+        "[check]\nfail_on_missing = true\n",
+    )
+    .unwrap();
+    let checkout = home.join("cpython");
+    fake_source_tree(&checkout);
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    let output = cmd
+        .arg("install")
+        .arg("--from-source")
+        .arg(&checkout)
+        .arg("--ignore-missing-deps")
+        .env(project_home_env_variable(), &hygeia_home)
+        .env("RUST_LOG", "")
+        .current_dir(&cwd)
+        .assert();
+    output
+        .failure()
+        .stderr(predicate::str::contains(
+            "Python 3.12.0-rc1 is missing modules: _lzma",
+        ))
+        .stderr(predicate::str::contains("Removed the failed installation"));
+    assert!(!hygeia_home
+        .join("installed")
+        .join("cpython")
+        .join("3.12.0-rc1")
+        .exists());
+}
+
+#[test]
+fn check_after_from_source() {
+    let home = create_test_temp_dir!();
+    let hygeia_home = home.join(".hygeia");
+    let cwd = home.join("current_dir");
+    fs::create_dir_all(&cwd).unwrap();
+    let checkout = home.join("cpython");
+    fake_source_tree(&checkout);
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    cmd.arg("install")
+        .arg("--from-source")
+        .arg(&checkout)
+        .arg("--ignore-missing-deps")
+        .env(project_home_env_variable(), &hygeia_home)
+        .env("RUST_LOG", "")
+        .current_dir(&cwd)
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    cmd.arg("check")
+        .arg("=3.12.0-rc1")
+        .env(project_home_env_variable(), &hygeia_home)
+        .env("RUST_LOG", "")
+        .current_dir(&cwd)
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("✅ _ssl"))
+        .stdout(predicate::str::contains("❌ _lzma"))
+        .stderr(predicate::str::contains(
+            "Python 3.12.0-rc1 is missing modules: _lzma",
+        ));
+
+    // Only verify the modules that are needed
+    fs::write(
+        hygeia_home.join("config.toml"),
+        "[check]\nmodules = [\"_ssl\", \"_sqlite3\"]\n",
+    )
+    .unwrap();
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    cmd.arg("check")
+        .arg("=3.12.0-rc1")
+        .env(project_home_env_variable(), &hygeia_home)
+        .env("RUST_LOG", "")
+        .current_dir(&cwd)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "All modules of Python 3.12.0-rc1 can be imported.",
+        ));
+    let info = fs::read_to_string(
+        hygeia_home
+            .join("installed")
+            .join("cpython")
+            .join("3.12.0-rc1")
+            .join(INFO_FILE),
+    )
+    .unwrap();
+    assert!(info.ends_with("Missing modules: none\n"));
+}
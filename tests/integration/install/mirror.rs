@@ -0,0 +1,348 @@
+use super::*;
+
+use bzip2::write::BzEncoder;
+use flate2::{write::GzEncoder, Compression};
+use sha2::{Digest, Sha256};
+use url::Url;
+
+fn archive_name(version: &str) -> String {
+    format!(
+        "cpython-{}+20200217-x86_64-unknown-linux-gnu-install_only.tar.gz",
+        version
+    )
+}
+
+/// Fake python-build-standalone archives of `versions`, their release list and checksums,
+/// in `mirror_dir`.
+fn standalone_mirror(mirror_dir: &Path, versions: &[&str]) -> Url {
+    fs::create_dir_all(mirror_dir).unwrap();
+
+    let mut sha256sums = String::new();
+    let mut assets = Vec::new();
+    for version in versions {
+        let script = format!("#!/bin/sh\necho 'Python {}'\n", version);
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        let mut header = tar::Header::new_gnu();
+        header.set_size(script.len() as u64);
+        header.set_mode(0o755);
+        header.set_cksum();
+        let major_minor = version
+            .rsplit_once('.')
+            .map(|(major_minor, _)| major_minor)
+            .unwrap();
+        builder
+            .append_data(
+                &mut header,
+                format!("python/bin/python{}", major_minor),
+                script.as_bytes(),
+            )
+            .unwrap();
+        let archive = builder.into_inner().unwrap().finish().unwrap();
+        let archive_name = archive_name(version);
+        let archive_path = mirror_dir.join(&archive_name);
+        fs::write(&archive_path, &archive).unwrap();
+
+        sha256sums.push_str(&format!(
+            "{}  {}\n",
+            hex::encode(Sha256::digest(&archive)),
+            archive_name
+        ));
+        assets.push(serde_json::json!({
+            "name": archive_name,
+            "browser_download_url": Url::from_file_path(&archive_path).unwrap(),
+        }));
+    }
+
+    let sha256sums_path = mirror_dir.join("SHA256SUMS");
+    fs::write(&sha256sums_path, sha256sums).unwrap();
+    assets.push(serde_json::json!({
+        "name": "SHA256SUMS",
+        "browser_download_url": Url::from_file_path(&sha256sums_path).unwrap(),
+    }));
+
+    let releases = serde_json::json!([{ "assets": assets }]);
+    let releases_path = mirror_dir.join("releases.json");
+    fs::write(&releases_path, releases.to_string()).unwrap();
+
+    Url::from_file_path(&releases_path).unwrap()
+}
+
+/// `hygeia install --prebuilt` from the fake mirror, with the index read from fixtures.
+fn install_prebuilt_command(hygeia_home: &Path, cwd: &Path, standalone_releases: &Url) -> Command {
+    let fixtures_dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("html");
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    cmd.arg("install")
+        .arg("--prebuilt")
+        .env(project_home_env_variable(), hygeia_home)
+        .env(
+            "HYGEIA_SOURCE_INDEX_URL",
+            Url::from_directory_path(fixtures_dir.join("source"))
+                .unwrap()
+                .as_str(),
+        )
+        .env(
+            "HYGEIA_WINDOWS_INDEX_URL",
+            Url::from_directory_path(fixtures_dir.join("windows"))
+                .unwrap()
+                .as_str(),
+        )
+        .env(
+            "HYGEIA_STANDALONE_RELEASES_URL",
+            standalone_releases.as_str(),
+        )
+        .env("RUST_LOG", "")
+        .current_dir(cwd);
+    cmd
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64", target_env = "gnu"))]
+#[test]
+fn prebuilt_from_file_mirror() {
+    let home = create_test_temp_dir!();
+    let hygeia_home = home.join(".hygeia");
+    let cwd = home.join("current_dir");
+    fs::create_dir_all(&cwd).unwrap();
+    let standalone_releases = standalone_mirror(&home.join("mirror"), &["3.8.1"]);
+
+    let output = install_prebuilt_command(&hygeia_home, &cwd, &standalone_releases)
+        .arg("=3.8.1")
+        .unwrap();
+    output.assert().success().stdout(predicate::str::contains(
+        "Python 3.8.1 successfully installed!",
+    ));
+
+    let bin_dir = hygeia_home
+        .join("installed")
+        .join("cpython")
+        .join("3.8.1")
+        .join("bin");
+    assert!(bin_dir.join("python3.8").is_file());
+    assert!(bin_dir.join("python3").is_file());
+    assert!(bin_dir.join("python").is_file());
+    assert!(bin_dir.parent().unwrap().join(INFO_FILE).is_file());
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64", target_env = "gnu"))]
+#[test]
+fn several_prebuilt_from_file_mirror() {
+    let home = create_test_temp_dir!();
+    let hygeia_home = home.join(".hygeia");
+    let cwd = home.join("current_dir");
+    fs::create_dir_all(&cwd).unwrap();
+    let standalone_releases = standalone_mirror(&home.join("mirror"), &["3.8.1", "3.7.6"]);
+
+    let output = install_prebuilt_command(&hygeia_home, &cwd, &standalone_releases)
+        .arg("=3.8.1")
+        .arg("~3.7")
+        .arg("--jobs")
+        .arg("2")
+        .arg("--select")
+        .unwrap();
+    output
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Python 3.8.1 successfully installed!",
+        ))
+        .stdout(predicate::str::contains(
+            "Python 3.7.6 successfully installed!",
+        ));
+
+    for (version, major_minor) in &[("3.8.1", "3.8"), ("3.7.6", "3.7")] {
+        let bin_dir = hygeia_home
+            .join("installed")
+            .join("cpython")
+            .join(version)
+            .join("bin");
+        assert!(bin_dir.join(format!("python{}", major_minor)).is_file());
+        assert!(bin_dir.join("python3").is_file());
+    }
+    assert_eq!(
+        fs::read_to_string(cwd.join(TOOLCHAIN_FILE)).unwrap(),
+        "=3.8.1\n=3.7.6\n"
+    );
+
+    // A failed download does not prevent installing the other versions
+    fs::remove_file(home.join("mirror").join(archive_name("3.7.6"))).unwrap();
+    fs::remove_file(
+        hygeia_home
+            .join("cache")
+            .join("downloaded")
+            .join(archive_name("3.7.6")),
+    )
+    .unwrap();
+    let output = install_prebuilt_command(&hygeia_home, &cwd, &standalone_releases)
+        .arg("=3.8.1")
+        .arg("=3.7.6")
+        .arg("--force")
+        .assert();
+    output
+        .failure()
+        .stdout(predicate::str::contains(
+            "Python 3.8.1 successfully installed!",
+        ))
+        .stderr(predicate::str::contains("Failed to install Python 3.7.6"));
+}
+
+/// Fake PyPy 3.10.12 archive, its checksum and the `versions.json` listing it, in
+/// `mirror_dir`. Returns the URLs of `versions.json` and of the checksums.
+fn pypy_mirror(mirror_dir: &Path) -> (Url, Url) {
+    fs::create_dir_all(mirror_dir).unwrap();
+
+    let archive_name = "pypy3.10-v7.3.12-linux64.tar.bz2";
+    let script = "#!/bin/sh\necho 'Python 3.10.12 [PyPy 7.3.12]'\n";
+    let mut builder = tar::Builder::new(BzEncoder::new(Vec::new(), bzip2::Compression::best()));
+    let mut header = tar::Header::new_gnu();
+    header.set_size(script.len() as u64);
+    header.set_mode(0o755);
+    header.set_cksum();
+    builder
+        .append_data(
+            &mut header,
+            "pypy3.10-v7.3.12-linux64/bin/pypy3.10",
+            script.as_bytes(),
+        )
+        .unwrap();
+    let archive = builder.into_inner().unwrap().finish().unwrap();
+    let archive_path = mirror_dir.join(archive_name);
+    fs::write(&archive_path, &archive).unwrap();
+
+    let checksums_path = mirror_dir.join("checksums.txt");
+    fs::write(
+        &checksums_path,
+        format!(
+            "{}  {}\n",
+            hex::encode(Sha256::digest(&archive)),
+            archive_name
+        ),
+    )
+    .unwrap();
+
+    let versions = serde_json::json!([{
+        "pypy_version": "7.3.12",
+        "python_version": "3.10.12",
+        "stable": true,
+        "files": [{
+            "filename": archive_name,
+            "arch": "x64",
+            "platform": "linux",
+            "download_url": Url::from_file_path(&archive_path).unwrap(),
+        }],
+    }]);
+    let versions_path = mirror_dir.join("versions.json");
+    fs::write(&versions_path, versions.to_string()).unwrap();
+
+    (
+        Url::from_file_path(&versions_path).unwrap(),
+        Url::from_file_path(&checksums_path).unwrap(),
+    )
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn pypy_from_file_mirror() {
+    let home = create_test_temp_dir!();
+    let hygeia_home = home.join(".hygeia");
+    let cwd = home.join("current_dir");
+    fs::create_dir_all(&cwd).unwrap();
+    let (pypy_versions, pypy_checksums) = pypy_mirror(&home.join("mirror"));
+    let standalone_releases = standalone_mirror(&home.join("mirror"), &[]);
+
+    let mut cmd = install_prebuilt_command(&hygeia_home, &cwd, &standalone_releases);
+    let output = cmd
+        .arg("pypy3.10")
+        .arg("--select")
+        .env("HYGEIA_PYPY_VERSIONS_URL", pypy_versions.as_str())
+        .env("HYGEIA_PYPY_CHECKSUMS_URL", pypy_checksums.as_str())
+        .unwrap();
+    output.assert().success().stdout(predicate::str::contains(
+        "PyPy 3.10.12 successfully installed!",
+    ));
+
+    let bin_dir = hygeia_home
+        .join("installed")
+        .join("pypy")
+        .join("3.10.12")
+        .join("bin");
+    assert!(bin_dir.join("pypy3.10").is_file());
+    assert!(bin_dir.join("python3.10").is_file());
+    assert!(bin_dir.join("python").is_file());
+    assert!(bin_dir.parent().unwrap().join(INFO_FILE).is_file());
+    assert_eq!(
+        fs::read_to_string(cwd.join(TOOLCHAIN_FILE)).unwrap(),
+        "pypy=3.10.12\n"
+    );
+}
+
+#[test]
+fn untrusted_http_mirror() {
+    let home = create_test_temp_dir!();
+    let hygeia_home = home.join(".hygeia");
+    let cwd = home.join("current_dir");
+    fs::create_dir_all(&cwd).unwrap();
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    let output = cmd
+        .arg("install")
+        .arg("=3.8.1")
+        .env(project_home_env_variable(), &hygeia_home)
+        .env(
+            "HYGEIA_SOURCE_INDEX_URL",
+            "http://mirror.invalid/downloads/source/",
+        )
+        .env("RUST_LOG", "")
+        .current_dir(&cwd);
+    output.assert().failure().stderr(predicate::str::contains(
+        "Refusing to download http://mirror.invalid/downloads/source/ over plain HTTP",
+    ));
+}
+
+#[test]
+fn already_installed_needs_no_digests() {
+    let home = create_test_temp_dir!();
+    let hygeia_home = home.join(".hygeia");
+    let cwd = home.join("current_dir");
+    fs::create_dir_all(&cwd).unwrap();
+    let install_dir = hygeia_home.join("installed").join("cpython").join("3.8.1");
+    fs::create_dir_all(install_dir.join("bin")).unwrap();
+    fs::write(install_dir.join(INFO_FILE), "Python 3.8.1\n").unwrap();
+    let fixtures_dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("html");
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    let output = cmd
+        .arg("install")
+        .arg("=3.8.1")
+        .env(project_home_env_variable(), &hygeia_home)
+        .env(
+            "HYGEIA_SOURCE_INDEX_URL",
+            Url::from_directory_path(fixtures_dir.join("source"))
+                .unwrap()
+                .as_str(),
+        )
+        .env(
+            "HYGEIA_WINDOWS_INDEX_URL",
+            Url::from_directory_path(fixtures_dir.join("windows"))
+                .unwrap()
+                .as_str(),
+        )
+        // Fetching the published digests would fail
+        .env(
+            "HYGEIA_RELEASE_PAGE_URL",
+            Url::from_directory_path(home.join("missing"))
+                .unwrap()
+                .as_str(),
+        )
+        .env("RUST_LOG", "")
+        .current_dir(&cwd)
+        .unwrap();
+    output.assert().success().stdout(predicate::str::contains(
+        "Python 3.8.1 successfully installed!",
+    ));
+}
@@ -17,9 +17,9 @@ fn with_empty_dir() {
     assert_output
             .success()
             .stdout(predicate::str::diff(indoc!("
-                +--------+---------+---------------------+----------+
-                | Active | Version | Installed by hygeia | Location |
-                +--------+---------+---------------------+----------+"
+                +--------+---------+---------------------+-------+----------+
+                | Active | Version | Installed by hygeia | Venvs | Location |
+                +--------+---------+---------------------+-------+----------+"
             )).trim().normalize()
             )
         // .stderr(predicate::str::is_empty().trim())
@@ -51,15 +51,15 @@ fn two_custom_no_system() {
     assert_output
             .success()
             .stdout(predicate::str::diff(format!(
-"+--------+---------+---------------------+-{}-+
-| Active | Version | Installed by hygeia | Location {} |
-+--------+---------+---------------------+-{}-+
-|        |  3.8.0  |                     | {} |
-+--------+---------+---------------------+-{}-+
-|   ✓    |  3.7.5  |          ✓          | {} |
-+--------+---------+---------------------+-{}-+
-|        |  3.7.4  |          ✓          | {} |
-+--------+---------+---------------------+-{}-+
+"+--------+---------+---------------------+-------+-{}-+
+| Active | Version | Installed by hygeia | Venvs | Location {} |
++--------+---------+---------------------+-------+-{}-+
+|        |  3.8.0  |                     |       | {} |
++--------+---------+---------------------+-------+-{}-+
+|   ✓    |  3.7.5  |          ✓          |       | {} |
++--------+---------+---------------------+-------+-{}-+
+|        |  3.7.4  |          ✓          |       | {} |
++--------+---------+---------------------+-------+-{}-+
 ",
                 dashes,
                 spaces,
@@ -94,11 +94,121 @@ fn selected_but_not_installed() {
     assert_output
             .success()
             .stdout(predicate::str::diff(indoc!("
-                +--------+---------+---------------------+----------+
-                | Active | Version | Installed by hygeia | Location |
-                +--------+---------+---------------------+----------+
-                |   ✗    |  3.7.5  |                     |          |
-                +--------+---------+---------------------+----------+"
+                +--------+---------+---------------------+-------+----------+
+                | Active | Version | Installed by hygeia | Venvs | Location |
+                +--------+---------+---------------------+-------+----------+
+                |   ✗    |  3.7.5  |                     |       |          |
+                +--------+---------+---------------------+-------+----------+"
+            )).trim().normalize()
+            )
+        // .stderr(predicate::str::is_empty().trim())
+        ;
+}
+
+fn available(hygeia_home: &Path) {
+    let cache_dir = hygeia_home.join("cache");
+    fs::create_dir_all(&cache_dir).unwrap();
+    let cache = serde_json::json!({
+        "last_updated": chrono::Utc::now(),
+        "available": [
+            {
+                "version": "3.9.0-a1",
+                "base_url": "https://www.python.org/ftp/python/3.9.0",
+                "source_tar_gz": "Python-3.9.0a1.tgz",
+                "win_pre_built": "python-3.9.0a1-embed-amd64.zip"
+            },
+            {
+                "version": "3.8.0",
+                "base_url": "https://www.python.org/ftp/python/3.8.0",
+                "source_tar_gz": "Python-3.8.0.tgz",
+                "win_pre_built": "python-3.8.0-embed-amd64.zip"
+            },
+            {
+                "version": "3.7.5",
+                "base_url": "https://www.python.org/ftp/python/3.7.5",
+                "source_tar_gz": "Python-3.7.5.tgz",
+                "win_pre_built": "python-3.7.5-embed-amd64.zip"
+            },
+            {
+                "version": "3.6.10",
+                "base_url": "https://www.python.org/ftp/python/3.6.10",
+                "source_tar_gz": "Python-3.6.10.tgz",
+                "win_pre_built": null
+            }
+        ]
+    });
+    fs::write(
+        cache_dir.join("available_toolchains.json"),
+        cache.to_string(),
+    )
+    .unwrap();
+}
+
+#[test]
+fn available_all() {
+    let home = create_test_temp_dir!();
+    let hygeia_home = home.join(".hygeia");
+    let cwd = home.join("current_dir");
+    let _ = fs::create_dir_all(&cwd);
+    available(&hygeia_home);
+    let _location_375_dir = installed(&hygeia_home, "3.7.5", true).unwrap();
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    let output = cmd
+        .arg("list")
+        .arg("--available")
+        .env(project_home_env_variable(), &hygeia_home)
+        .env("PATH", hygeia_home.join("usr_bin"))
+        .current_dir(&cwd)
+        .unwrap();
+    let assert_output = output.assert();
+    assert_output
+            .success()
+            .stdout(predicate::str::diff(indoc!("
+                +---------+-------------------+-----------+
+                | Version | Windows pre-built | Installed |
+                +---------+-------------------+-----------+
+                |  3.8.0  |         ✓         |           |
+                +---------+-------------------+-----------+
+                |  3.7.5  |         ✓         |     ✓     |
+                +---------+-------------------+-----------+
+                | 3.6.10  |                   |           |
+                +---------+-------------------+-----------+"
+            )).trim().normalize()
+            )
+        // .stderr(predicate::str::is_empty().trim())
+        ;
+}
+
+#[test]
+fn available_filtered_with_pre_releases() {
+    let home = create_test_temp_dir!();
+    let hygeia_home = home.join(".hygeia");
+    let cwd = home.join("current_dir");
+    let _ = fs::create_dir_all(&cwd);
+    available(&hygeia_home);
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    let output = cmd
+        .arg("list")
+        .arg("--available")
+        .arg("--pre")
+        .arg(">=3.8")
+        .env(project_home_env_variable(), &hygeia_home)
+        .env("PATH", hygeia_home.join("usr_bin"))
+        .current_dir(&cwd)
+        .unwrap();
+    let assert_output = output.assert();
+    assert_output
+            .success()
+            .stdout(predicate::str::diff(indoc!("
+                +----------+-------------------+-----------+
+                | Version  | Windows pre-built | Installed |
+                +----------+-------------------+-----------+
+                | 3.9.0-a1 |         ✓         |           |
+                +----------+-------------------+-----------+
+                |  3.8.0   |         ✓         |           |
+                +----------+-------------------+-----------+"
             )).trim().normalize()
             )
         // .stderr(predicate::str::is_empty().trim())
@@ -15,8 +15,8 @@ use semver::Version;
 
 use hygeia::{
     constants::{
-        home_overwrite_env_variable, project_home_env_variable, EXECUTABLE_NAME, INFO_FILE,
-        TOOLCHAIN_FILE,
+        home_overwrite_env_variable, project_home_env_variable, version_env_variable,
+        EXECUTABLE_NAME, INFO_FILE, TOOLCHAIN_FILE,
     },
     utils::directory::{PycorsHomeProviderTrait, PycorsPathsProvider},
     Result,
@@ -24,13 +24,20 @@ use hygeia::{
 
 use hygeia_test_helpers::{create_test_temp_dir, function_path, mock_executable, MockedOutput};
 
+mod doctor;
+mod global;
 mod help;
 mod install;
 mod list;
 mod path;
+mod rescan;
 mod run;
 mod select;
 mod setup;
+mod uninstall;
+mod venv;
+// Not named `which` to avoid shadowing the `which` crate.
+mod which_command;
 
 mockall::mock! {
     PycorsHomeProviderTrait {}     // Name of the mock struct, less the "Mock" prefix
@@ -0,0 +1,44 @@
+use super::*;
+
+#[test]
+fn rebuilds_index() {
+    let home = create_test_temp_dir!();
+    let hygeia_home = home.join(".hygeia");
+    let usr_bin = hygeia_home.join("usr_bin");
+    let cwd = home.join("current_dir");
+    fs::create_dir_all(&usr_bin).unwrap();
+    fs::create_dir_all(&cwd).unwrap();
+    let index_file = hygeia_home.join("cache").join("interpreters.json");
+    fs::create_dir_all(index_file.parent().unwrap()).unwrap();
+    fs::write(&index_file, "stale content").unwrap();
+
+    mock_executable(
+        &usr_bin,
+        "python3",
+        MockedOutput {
+            out: Some("Python 3.7.5"),
+            err: None,
+        },
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    let output = cmd
+        .arg("rescan")
+        .env(project_home_env_variable(), &hygeia_home)
+        .env("PATH", &usr_bin)
+        .env("RUST_LOG", "")
+        .current_dir(&cwd)
+        .unwrap();
+    output
+        .assert()
+        .success()
+        .stdout(predicate::str::diff(
+            "Found 1 Python interpreter(s) in PATH\n",
+        ))
+        .stderr(predicate::str::is_empty().trim());
+
+    let index = fs::read_to_string(&index_file).unwrap();
+    assert!(index.contains("python3"));
+    assert!(index.contains("3.7.5"));
+}
@@ -0,0 +1,141 @@
+use super::*;
+
+fn shim(hygeia_home: &Path, name: &str) -> PathBuf {
+    let shims_dir = hygeia_home.join("shims");
+    fs::create_dir_all(&shims_dir).unwrap();
+    let shim = shims_dir.join(name);
+    fs::File::create(&shim).unwrap();
+    shim
+}
+
+fn binary(location: &str, name: &str) {
+    fs::File::create(Path::new(location).join(name)).unwrap();
+}
+
+#[test]
+fn custom_install() {
+    let home = create_test_temp_dir!();
+    let hygeia_home = home.join(".hygeia");
+    let cwd = home.join("current_dir");
+    fs::create_dir_all(&cwd).unwrap();
+    let _location_380_dir = installed(&hygeia_home, "3.8.0", true).unwrap();
+    let location_375_dir = installed(&hygeia_home, "3.7.5", true).unwrap();
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    let output = cmd
+        .arg("uninstall")
+        .arg("~3.7")
+        .env(project_home_env_variable(), &hygeia_home)
+        .env("PATH", hygeia_home.join("usr_bin"))
+        .env("RUST_LOG", "")
+        .current_dir(&cwd)
+        .unwrap();
+    let assert_output = output.assert();
+    assert_output
+        .success()
+        .stdout(predicate::str::contains(
+            "Python 3.7.5 successfully uninstalled!",
+        ))
+        .stderr(predicate::str::is_empty().trim());
+
+    assert!(!Path::new(&location_375_dir).exists());
+    assert!(!hygeia_home
+        .join("installed")
+        .join("cpython")
+        .join("3.7.5")
+        .exists());
+    assert!(hygeia_home
+        .join("installed")
+        .join("cpython")
+        .join("3.8.0")
+        .exists());
+}
+
+#[test]
+fn refuses_system_toolchain() {
+    let home = create_test_temp_dir!();
+    let hygeia_home = home.join(".hygeia");
+    let cwd = home.join("current_dir");
+    fs::create_dir_all(&cwd).unwrap();
+    let location_380_dir = installed(&hygeia_home, "3.8.0", false).unwrap();
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    let output = cmd
+        .arg("uninstall")
+        .arg("=3.8.0")
+        .env(project_home_env_variable(), &hygeia_home)
+        .env("PATH", hygeia_home.join("usr_bin"))
+        .env("RUST_LOG", "")
+        .current_dir(&cwd);
+    let assert_output = output.assert();
+    assert_output
+        .failure()
+        .stdout(predicate::str::is_empty().trim())
+        .stderr(predicate::str::contains("refusing to uninstall it"));
+
+    assert!(Path::new(&location_380_dir).exists());
+}
+
+#[test]
+fn not_installed() {
+    let home = create_test_temp_dir!();
+    let hygeia_home = home.join(".hygeia");
+    let cwd = home.join("current_dir");
+    fs::create_dir_all(&cwd).unwrap();
+    let _location_380_dir = installed(&hygeia_home, "3.8.0", true).unwrap();
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    let output = cmd
+        .arg("uninstall")
+        .arg("=3.7.5")
+        .env(project_home_env_variable(), &hygeia_home)
+        .env("PATH", hygeia_home.join("usr_bin"))
+        .env("RUST_LOG", "")
+        .current_dir(&cwd);
+    let assert_output = output.assert();
+    assert_output
+        .failure()
+        .stdout(predicate::str::is_empty().trim())
+        .stderr(predicate::str::diff("Error: Python version =3.7.5 not found!").trim());
+}
+
+#[test]
+fn prunes_stale_shims() {
+    let home = create_test_temp_dir!();
+    let hygeia_home = home.join(".hygeia");
+    let cwd = home.join("current_dir");
+    fs::create_dir_all(&cwd).unwrap();
+    let location_380_dir = installed(&hygeia_home, "3.8.0", true).unwrap();
+    let location_375_dir = installed(&hygeia_home, "3.7.5", true).unwrap();
+
+    let python_ext = format!("python{}", hygeia_test_helpers::EXECUTABLE_EXTENSION);
+    let black_ext = format!("black{}", hygeia_test_helpers::EXECUTABLE_EXTENSION);
+    let flake8_ext = format!("flake8{}", hygeia_test_helpers::EXECUTABLE_EXTENSION);
+
+    binary(&location_375_dir, &python_ext);
+    binary(&location_375_dir, &black_ext);
+    binary(&location_375_dir, &flake8_ext);
+    binary(&location_380_dir, &black_ext);
+
+    let python_shim = shim(&hygeia_home, &python_ext);
+    let black_shim = shim(&hygeia_home, &black_ext);
+    let flake8_shim = shim(&hygeia_home, &flake8_ext);
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    let output = cmd
+        .arg("uninstall")
+        .arg("=3.7.5")
+        .env(project_home_env_variable(), &hygeia_home)
+        .env("PATH", hygeia_home.join("usr_bin"))
+        .env("RUST_LOG", "")
+        .current_dir(&cwd)
+        .unwrap();
+    output.assert().success();
+
+    // Shim created by 'setup' is always kept
+    assert!(python_shim.exists());
+    // Still provided by 3.8.0
+    assert!(black_shim.exists());
+    // Not provided by any toolchain anymore
+    assert!(!flake8_shim.exists());
+}
@@ -0,0 +1,135 @@
+use super::*;
+
+/// A directory looking like a virtual environment created by `python -m venv`.
+fn fake_venv(hygeia_home: &Path, name: &str, base_home: &Path, version: &str) -> PathBuf {
+    let location = hygeia_home.join("venvs").join(name);
+    let bin_dir = location.join("bin");
+    fs::create_dir_all(&bin_dir).unwrap();
+    fs::write(
+        location.join("pyvenv.cfg"),
+        format!(
+            "home = {}\ninclude-system-site-packages = false\nversion = {}\n",
+            base_home.display(),
+            version
+        ),
+    )
+    .unwrap();
+    let output = format!("Python {}", version);
+    mock_executable(
+        &bin_dir,
+        "python3",
+        MockedOutput {
+            out: Some(&output),
+            err: None,
+        },
+    )
+    .unwrap();
+    location
+}
+
+#[test]
+fn list_marks_broken() {
+    let home = create_test_temp_dir!();
+    let hygeia_home = home.join(".hygeia");
+    let usr_bin = hygeia_home.join("usr_bin");
+    let cwd = home.join("current_dir");
+    fs::create_dir_all(&usr_bin).unwrap();
+    fs::create_dir_all(&cwd).unwrap();
+    let web = fake_venv(&hygeia_home, "web", &usr_bin, "3.8.1");
+    let missing = hygeia_home.join("installed").join("cpython").join("3.7.5");
+    let old = fake_venv(&hygeia_home, "old", &missing, "3.7.5");
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    let output = cmd
+        .arg("venv")
+        .arg("list")
+        .env(project_home_env_variable(), &hygeia_home)
+        .env("PATH", &usr_bin)
+        .env("RUST_LOG", "")
+        .current_dir(&cwd)
+        .unwrap();
+    let expected = format!(
+        "{:<20} {:<8} {} (broken: {} not found)\n{:<20} {:<8} {}\n",
+        "old",
+        "3.7.5",
+        old.display(),
+        missing.display(),
+        "web",
+        "3.8.1",
+        web.display()
+    );
+    output
+        .assert()
+        .success()
+        .stdout(predicate::str::diff(expected))
+        .stderr(predicate::str::is_empty().trim());
+}
+
+#[test]
+fn select_and_remove() {
+    let home = create_test_temp_dir!();
+    let hygeia_home = home.join(".hygeia");
+    let usr_bin = hygeia_home.join("usr_bin");
+    let cwd = home.join("current_dir");
+    fs::create_dir_all(&usr_bin).unwrap();
+    fs::create_dir_all(&cwd).unwrap();
+    let web = fake_venv(&hygeia_home, "web", &usr_bin, "3.8.1");
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    let output = cmd
+        .arg("select")
+        .arg("--venv")
+        .arg("web")
+        .env(project_home_env_variable(), &hygeia_home)
+        .env("PATH", &usr_bin)
+        .env("RUST_LOG", "")
+        .current_dir(&cwd)
+        .unwrap();
+    output
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty().trim())
+        .stderr(predicate::str::is_empty().trim());
+    let file_content = fs::read_to_string(cwd.join(TOOLCHAIN_FILE)).unwrap();
+    assert_eq!(file_content.trim(), web.join("bin").display().to_string());
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    let output = cmd
+        .arg("venv")
+        .arg("remove")
+        .arg("web")
+        .env(project_home_env_variable(), &hygeia_home)
+        .env("PATH", &usr_bin)
+        .env("RUST_LOG", "")
+        .current_dir(&cwd)
+        .unwrap();
+    output
+        .assert()
+        .success()
+        .stdout(predicate::str::diff(
+            "🐍 Virtual environment \"web\" successfully removed!\n",
+        ))
+        .stderr(predicate::str::is_empty().trim());
+    assert!(!web.exists());
+}
+
+#[test]
+fn remove_not_found() {
+    let home = create_test_temp_dir!();
+    let hygeia_home = home.join(".hygeia");
+    let cwd = home.join("current_dir");
+    fs::create_dir_all(&cwd).unwrap();
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    let output = cmd
+        .arg("venv")
+        .arg("remove")
+        .arg("web")
+        .env(project_home_env_variable(), &hygeia_home)
+        .env("PATH", hygeia_home.join("usr_bin"))
+        .env("RUST_LOG", "")
+        .current_dir(&cwd);
+    output.assert().failure().stderr(predicate::str::contains(
+        "Virtual environment \"web\" not found",
+    ));
+}
@@ -0,0 +1,81 @@
+use super::*;
+
+fn binary(location: &str, name: &str) -> String {
+    let path = Path::new(location).join(format!(
+        "{}{}",
+        name,
+        hygeia_test_helpers::EXECUTABLE_EXTENSION
+    ));
+    fs::File::create(&path).unwrap();
+    path.to_string_lossy().to_string()
+}
+
+fn which(hygeia_home: &Path, cwd: &Path, command: &str) -> assert_cmd::assert::Assert {
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    cmd.arg("which")
+        .arg(command)
+        .env(project_home_env_variable(), hygeia_home)
+        .env("PATH", hygeia_home.join("usr_bin"))
+        .env("RUST_LOG", "")
+        .current_dir(cwd)
+        .assert()
+}
+
+#[test]
+fn selected_toolchain() {
+    let home = create_test_temp_dir!();
+    let hygeia_home = home.join(".hygeia");
+    let cwd = home.join("current_dir");
+    let location_380_dir = installed(&hygeia_home, "3.8.0", true).unwrap();
+    let location_375_dir = installed(&hygeia_home, "3.7.5", true).unwrap();
+    let _black_380 = binary(&location_380_dir, "black");
+    let black_375 = binary(&location_375_dir, "black");
+    select("=3.7.5", &cwd);
+
+    which(&hygeia_home, &cwd, "black")
+        .success()
+        .stdout(predicate::str::diff(format!("{}\n", black_375)))
+        .stderr(predicate::str::is_empty().trim());
+}
+
+#[test]
+fn versioned_command() {
+    let home = create_test_temp_dir!();
+    let hygeia_home = home.join(".hygeia");
+    let cwd = home.join("current_dir");
+    let location_380_dir = installed(&hygeia_home, "3.8.0", true).unwrap();
+    let location_375_dir = installed(&hygeia_home, "3.7.5", true).unwrap();
+    let pip_380 = binary(&location_380_dir, "pip3.8");
+    let _pip_375 = binary(&location_375_dir, "pip3.7");
+    select("=3.7.5", &cwd);
+
+    which(&hygeia_home, &cwd, "pip3.8")
+        .success()
+        .stdout(predicate::str::diff(format!("{}\n", pip_380)))
+        .stderr(predicate::str::is_empty().trim());
+}
+
+#[test]
+fn not_provided() {
+    let home = create_test_temp_dir!();
+    let hygeia_home = home.join(".hygeia");
+    let cwd = home.join("current_dir");
+    let location_380_dir = installed(&hygeia_home, "3.8.0", true).unwrap();
+    let _location_375_dir = installed(&hygeia_home, "3.7.5", true).unwrap();
+    let flake8_380 = binary(&location_380_dir, "flake8");
+    select("=3.7.5", &cwd);
+
+    which(&hygeia_home, &cwd, "flake8")
+        .failure()
+        .stdout(predicate::str::is_empty().trim())
+        .stderr(
+            predicate::str::contains("Command \"flake8\" is not provided by Python 3.7.5")
+                .and(predicate::str::contains(format!("3.8.0 ({})", flake8_380))),
+        );
+
+    which(&hygeia_home, &cwd, "mypy")
+        .failure()
+        .stderr(predicate::str::contains(
+        "Command \"mypy\" is not provided by Python 3.7.5 (nor by any other installed toolchain)",
+    ));
+}